@@ -1,4 +1,17 @@
-use attodb::{DEFAULT_PORT, connection::Connection, message::Message, value::Value};
+use attodb::{
+    DEFAULT_PORT,
+    command::{Compare, Comparison, Condition, End, Expected, Expiry, KeyRange, Target},
+    connection::Connection,
+    history::Watched,
+    index::vector::Algorithm,
+    message::Message,
+    namespace::Eviction,
+    value::{
+        Value,
+        timeseries::Aggregation,
+        vector::{self, Metric},
+    },
+};
 use clap::{Parser, Subcommand};
 use tokio::net::TcpStream;
 
@@ -12,15 +25,532 @@ struct Cli {
 
     #[arg(long, default_value_t = DEFAULT_PORT)]
     port: u16,
+
+    /// The logical database to use
+    #[arg(long, conflicts_with = "namespace")]
+    db: Option<usize>,
+
+    /// The namespace to use
+    #[arg(long)]
+    namespace: Option<String>,
+
+    /// The namespace's password
+    #[arg(long, requires = "namespace")]
+    password: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
     Ping,
-    Get { key: String },
-    Set { key: String, value: String },
-    Incr { key: String },
-    Del { key: String },
+    Get {
+        key: String,
+    },
+    Set {
+        key: String,
+        value: String,
+        #[arg(long, conflicts_with = "xx")]
+        nx: bool,
+        #[arg(long)]
+        xx: bool,
+        #[arg(long)]
+        get: bool,
+        #[arg(long, group = "expiry")]
+        ex: Option<u64>,
+        #[arg(long, group = "expiry")]
+        px: Option<u64>,
+        #[arg(long, group = "expiry")]
+        exat: Option<u64>,
+        #[arg(long, group = "expiry")]
+        pxat: Option<u64>,
+        #[arg(long, group = "expiry")]
+        keepttl: bool,
+        /// Attach the key to this lease, so it's removed when the lease is.
+        #[arg(long)]
+        lease: Option<u64>,
+    },
+    Incr {
+        key: String,
+    },
+    Del {
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    JsonSet {
+        key: String,
+        path: String,
+        json: String,
+    },
+    JsonGet {
+        key: String,
+        paths: Vec<String>,
+    },
+    JsonDel {
+        key: String,
+        path: Option<String>,
+    },
+    JsonNumIncrBy {
+        key: String,
+        path: String,
+        number: String,
+    },
+    JsonArrAppend {
+        key: String,
+        path: String,
+        values: Vec<String>,
+    },
+    JsonType {
+        key: String,
+        path: Option<String>,
+    },
+    TsCreate {
+        key: String,
+        #[arg(long)]
+        retention: Option<u64>,
+    },
+    TsAdd {
+        key: String,
+        /// A timestamp in milliseconds, or `*` for the server's current time.
+        timestamp: String,
+        value: f64,
+    },
+    TsMAdd {
+        /// Repeated `key timestamp value` triples.
+        samples: Vec<String>,
+    },
+    TsRange {
+        key: String,
+        /// A timestamp in milliseconds, or `-` for the oldest sample.
+        from: String,
+        /// A timestamp in milliseconds, or `+` for the newest sample.
+        to: String,
+        #[arg(long, requires = "bucket")]
+        aggregation: Option<String>,
+        #[arg(long, requires = "aggregation")]
+        bucket: Option<u64>,
+    },
+    TsCreateRule {
+        source: String,
+        dest: String,
+        aggregation: String,
+        bucket: u64,
+    },
+    TsDeleteRule {
+        source: String,
+        dest: String,
+    },
+    VCreate {
+        index: String,
+        prefix: String,
+        dim: usize,
+        /// One of `cosine`, `l2` or `dot`.
+        metric: String,
+        /// Builds an approximate HNSW index instead of searching exhaustively.
+        #[arg(long)]
+        hnsw: bool,
+        #[arg(long, requires = "hnsw", default_value_t = Algorithm::DEFAULT_M)]
+        m: usize,
+        #[arg(long, requires = "hnsw", default_value_t = Algorithm::DEFAULT_EF_CONSTRUCTION)]
+        ef_construction: usize,
+    },
+    VDrop {
+        index: String,
+    },
+    VAdd {
+        key: String,
+        /// Comma separated components.
+        vector: String,
+    },
+    VSearch {
+        index: String,
+        k: usize,
+        /// Comma separated components.
+        vector: String,
+        #[arg(long)]
+        ef: Option<usize>,
+    },
+    FtCreate {
+        index: String,
+        prefix: String,
+    },
+    FtDrop {
+        index: String,
+    },
+    FtSearch {
+        index: String,
+        query: String,
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    Type {
+        key: String,
+    },
+    Exists {
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    Rename {
+        key: String,
+        new_key: String,
+    },
+    RenameNx {
+        key: String,
+        new_key: String,
+    },
+    Copy {
+        key: String,
+        dest: String,
+        #[arg(long)]
+        replace: bool,
+    },
+    Touch {
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    Unlink {
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    Keys {
+        pattern: String,
+    },
+    Scan {
+        cursor: u64,
+        #[arg(long = "match")]
+        pattern: Option<String>,
+        #[arg(long)]
+        count: Option<usize>,
+        #[arg(long = "type")]
+        kind: Option<String>,
+    },
+    /// Bounds are `-`/`+` for unbounded, `[key` inclusive or `(key` exclusive.
+    Range {
+        #[arg(allow_hyphen_values = true, required_unless_present = "prefix")]
+        start: Option<String>,
+        #[arg(required_unless_present = "prefix")]
+        end: Option<String>,
+        #[arg(long, conflicts_with_all = ["start", "end"])]
+        prefix: Option<String>,
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// The same as range, but in reverse order.
+    RevRange {
+        #[arg(allow_hyphen_values = true, required_unless_present = "prefix")]
+        start: Option<String>,
+        #[arg(required_unless_present = "prefix")]
+        end: Option<String>,
+        #[arg(long, conflicts_with_all = ["start", "end"])]
+        prefix: Option<String>,
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    SetNx {
+        key: String,
+        value: String,
+    },
+    GetSet {
+        key: String,
+        value: String,
+    },
+    GetDel {
+        key: String,
+    },
+    Expire {
+        key: String,
+        seconds: u64,
+    },
+    PExpire {
+        key: String,
+        millis: u64,
+    },
+    ExpireAt {
+        key: String,
+        timestamp: u64,
+    },
+    PExpireAt {
+        key: String,
+        timestamp: u64,
+    },
+    Ttl {
+        key: String,
+    },
+    PTtl {
+        key: String,
+    },
+    Persist {
+        key: String,
+    },
+    MGet {
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    /// Takes alternating keys and values.
+    MSet {
+        #[arg(required = true)]
+        pairs: Vec<String>,
+    },
+    /// Takes alternating keys and values.
+    MSetNx {
+        #[arg(required = true)]
+        pairs: Vec<String>,
+    },
+    Append {
+        key: String,
+        value: String,
+    },
+    StrLen {
+        key: String,
+    },
+    GetRange {
+        key: String,
+        #[arg(allow_hyphen_values = true)]
+        start: i64,
+        #[arg(allow_hyphen_values = true)]
+        end: i64,
+    },
+    SetRange {
+        key: String,
+        offset: usize,
+        value: String,
+    },
+    /// Runs the `then` commands if every comparison holds, or else the `else` commands.
+    Txn {
+        /// A comparison like `MOD key = 3`, against VALUE, VERSION, CREATE, MOD or EXISTS
+        #[arg(long = "if")]
+        compares: Vec<String>,
+        /// A command, like `set key value`
+        #[arg(long = "then")]
+        success: Vec<String>,
+        /// A command, like `get key`
+        #[arg(long = "else")]
+        failure: Vec<String>,
+    },
+    /// Swap a key's value if it holds the expected value, or version with --version
+    Cas {
+        key: String,
+        expected: String,
+        value: String,
+        #[arg(long)]
+        version: bool,
+    },
+    /// Delete a key if it holds the expected value, or version with --version
+    Cad {
+        key: String,
+        expected: String,
+        #[arg(long)]
+        version: bool,
+    },
+    GetVer {
+        key: String,
+    },
+    /// Print every message published to the channels, until interrupted
+    Subscribe {
+        #[arg(required = true)]
+        channels: Vec<String>,
+    },
+    /// Print every message published to channels matching the patterns, until interrupted
+    PSubscribe {
+        #[arg(required = true)]
+        patterns: Vec<String>,
+    },
+    Publish {
+        channel: String,
+        message: String,
+    },
+    ConfigGet {
+        parameter: String,
+    },
+    ConfigSet {
+        parameter: String,
+        value: String,
+    },
+    /// Print every change to a key, or keys starting with it with --prefix, until interrupted
+    WatchRev {
+        key: String,
+        #[arg(long)]
+        prefix: bool,
+        /// Start from this revision, rather than only printing new changes
+        #[arg(long)]
+        from: Option<u64>,
+    },
+    LPush {
+        key: String,
+        #[arg(required = true)]
+        elements: Vec<String>,
+    },
+    RPush {
+        key: String,
+        #[arg(required = true)]
+        elements: Vec<String>,
+    },
+    LPop {
+        key: String,
+    },
+    RPop {
+        key: String,
+    },
+    LLen {
+        key: String,
+    },
+    LRange {
+        key: String,
+        #[arg(allow_hyphen_values = true)]
+        start: i64,
+        #[arg(allow_hyphen_values = true)]
+        stop: i64,
+    },
+    /// Move an element between lists, where the ends are LEFT or RIGHT
+    LMove {
+        source: String,
+        destination: String,
+        from: String,
+        to: String,
+    },
+    BLPop {
+        #[arg(required = true)]
+        keys: Vec<String>,
+        /// Seconds to wait for an element, or zero to wait forever
+        #[arg(long, default_value_t = 0.0)]
+        timeout: f64,
+    },
+    BRPop {
+        #[arg(required = true)]
+        keys: Vec<String>,
+        /// Seconds to wait for an element, or zero to wait forever
+        #[arg(long, default_value_t = 0.0)]
+        timeout: f64,
+    },
+    BLMove {
+        source: String,
+        destination: String,
+        from: String,
+        to: String,
+        /// Seconds to wait for an element, or zero to wait forever
+        #[arg(long, default_value_t = 0.0)]
+        timeout: f64,
+    },
+    QCreate {
+        key: String,
+        /// How long a received message stays invisible, in milliseconds.
+        #[arg(long)]
+        visibility: Option<u64>,
+        /// How many times a message can be received before it's dead-lettered.
+        #[arg(long, requires = "dead_letter")]
+        max_receives: Option<u32>,
+        #[arg(long, requires = "max_receives")]
+        dead_letter: Option<String>,
+    },
+    Enqueue {
+        key: String,
+        body: String,
+        /// How long until the message can be received, in milliseconds.
+        #[arg(long)]
+        delay: Option<u64>,
+    },
+    Dequeue {
+        key: String,
+        #[arg(long)]
+        visibility: Option<u64>,
+    },
+    Ack {
+        key: String,
+        receipt: String,
+    },
+    Nack {
+        key: String,
+        receipt: String,
+        #[arg(long)]
+        delay: Option<u64>,
+    },
+    QStats {
+        key: String,
+    },
+    Schedule {
+        key: String,
+        /// When the job is due, as a unix time in milliseconds.
+        at: u64,
+        payload: String,
+    },
+    Lock {
+        key: String,
+        owner: String,
+        /// The lease, in milliseconds.
+        ttl: u64,
+        /// How long to wait for the lock, in seconds, or zero to wait forever.
+        #[arg(long)]
+        timeout: Option<f64>,
+    },
+    Unlock {
+        key: String,
+        owner: String,
+    },
+    Extend {
+        key: String,
+        owner: String,
+        ttl: u64,
+    },
+    LeaseGrant {
+        /// How long the lease lasts, in seconds.
+        ttl: u64,
+    },
+    LeaseKeepAlive {
+        id: u64,
+    },
+    LeaseRevoke {
+        id: u64,
+    },
+    LeaseTtl {
+        id: u64,
+    },
+    Throttle {
+        key: String,
+        max_burst: u64,
+        /// How many actions are allowed per period.
+        rate: u64,
+        /// The period, in seconds.
+        period: u64,
+        #[arg(long, default_value_t = 1)]
+        quantity: u64,
+    },
+    Select {
+        index: usize,
+    },
+    SwapDb {
+        first: usize,
+        second: usize,
+    },
+    Move {
+        key: String,
+        db: usize,
+    },
+    FlushDb,
+    DbSize,
+    NamespaceCreate {
+        name: String,
+        /// The most bytes of keys and values it may hold.
+        #[arg(long)]
+        max_memory: Option<usize>,
+        /// How long keys created without an expiry live, in seconds.
+        #[arg(long)]
+        ttl: Option<u64>,
+        /// One of `noeviction`, `volatile-ttl`, `allkeys-oldest` or `allkeys-lru`.
+        #[arg(long)]
+        eviction: Option<String>,
+        #[arg(long)]
+        password: Option<String>,
+        #[arg(long, requires = "password")]
+        read_password: Option<String>,
+    },
+    NamespaceDrop {
+        name: String,
+    },
+    NamespaceUse {
+        name: String,
+        password: Option<String>,
+    },
+    NamespaceInfo {
+        name: String,
+    },
 }
 
 #[tokio::main(flavor = "current_thread")]
@@ -29,49 +559,713 @@ async fn main() -> attodb::Result<()> {
     let addr = format!("{}:{}", cli.host, cli.port);
     let socket = TcpStream::connect(addr).await?;
     let mut connection = Connection::new(socket);
-    match cli.command {
-        Command::Ping => connection.write_message(Message::Ping).await?,
-        Command::Get { key } => {
-            connection
-                .write_message(Message::Command(attodb::Command::Get(
-                    attodb::command::Get { key },
-                )))
-                .await?
-        }
-        Command::Set { key, value } => {
-            connection
-                .write_message(Message::Command(attodb::Command::Set(
-                    attodb::command::Set {
-                        key,
-                        value: input_to_value(&value).into_vec(),
-                    },
-                )))
-                .await?
-        }
-        Command::Incr { key } => {
-            connection
-                .write_message(Message::Command(attodb::Command::Incr(
-                    attodb::command::Incr { key },
-                )))
-                .await?
-        }
-        Command::Del { key } => {
-            connection
-                .write_message(Message::Command(attodb::Command::Del(
-                    attodb::command::Del { key },
-                )))
-                .await?
+    let Some(message) = to_message(cli.command) else {
+        return Ok(());
+    };
+    let switch = match (cli.db, cli.namespace) {
+        (Some(index), _) => Some(attodb::Command::Select(attodb::command::Select { index })),
+        (_, Some(name)) => Some(attodb::Command::NamespaceUse(
+            attodb::command::NamespaceUse {
+                name,
+                password: cli.password,
+            },
+        )),
+        _ => None,
+    };
+    if let Some(switch) = switch {
+        connection.write_message(Message::Command(switch)).await?;
+        if let Some(err @ Message::Err(_)) = connection.read_message().await? {
+            println!("{err:?}");
+            return Ok(());
         }
     }
-    match connection.read_message().await? {
-        Some(message) => {
-            println!("{message:?}");
+    // Subscribing keeps the connection open, to print whatever is pushed to it.
+    let subscribing = matches!(
+        message,
+        Message::Command(
+            attodb::Command::Subscribe(_)
+                | attodb::Command::PSubscribe(_)
+                | attodb::Command::WatchRev(_)
+        )
+    );
+    connection.write_message(message).await?;
+    while let Some(message) = connection.read_message().await? {
+        println!("{message:?}");
+        if !subscribing || matches!(message, Message::Err(_)) {
+            break;
         }
-        None => {}
     }
     Ok(())
 }
 
+fn to_message(command: Command) -> Option<Message> {
+    Some(match command {
+        Command::Ping => Message::Ping,
+        Command::Get { key } => {
+            Message::Command(attodb::Command::Get(attodb::command::Get { key }))
+        }
+        Command::Set {
+            key,
+            value,
+            nx,
+            xx,
+            get,
+            ex,
+            px,
+            exat,
+            pxat,
+            keepttl,
+            lease,
+        } => {
+            let condition = match (nx, xx) {
+                (true, _) => Some(Condition::Nx),
+                (_, true) => Some(Condition::Xx),
+                _ => None,
+            };
+            let expiry = ex
+                .map(Expiry::Ex)
+                .or(px.map(Expiry::Px))
+                .or(exat.map(Expiry::ExAt))
+                .or(pxat.map(Expiry::PxAt))
+                .or(keepttl.then_some(Expiry::KeepTtl));
+            Message::Command(attodb::Command::Set(attodb::command::Set {
+                key,
+                value: input_to_value(&value).into_vec(),
+                condition,
+                get,
+                expiry,
+                lease,
+            }))
+        }
+        Command::Incr { key } => {
+            Message::Command(attodb::Command::Incr(attodb::command::Incr { key }))
+        }
+        Command::Del { keys } => {
+            Message::Command(attodb::Command::Del(attodb::command::Del { keys }))
+        }
+        Command::JsonSet { key, path, json } => {
+            Message::Command(attodb::Command::JsonSet(attodb::command::JsonSet {
+                key,
+                path,
+                json,
+            }))
+        }
+        Command::JsonGet { key, paths } => {
+            Message::Command(attodb::Command::JsonGet(attodb::command::JsonGet {
+                key,
+                paths,
+            }))
+        }
+        Command::JsonDel { key, path } => {
+            Message::Command(attodb::Command::JsonDel(attodb::command::JsonDel {
+                key,
+                path,
+            }))
+        }
+        Command::JsonNumIncrBy { key, path, number } => Message::Command(
+            attodb::Command::JsonNumIncrBy(attodb::command::JsonNumIncrBy { key, path, number }),
+        ),
+        Command::JsonArrAppend { key, path, values } => Message::Command(
+            attodb::Command::JsonArrAppend(attodb::command::JsonArrAppend { key, path, values }),
+        ),
+        Command::JsonType { key, path } => {
+            Message::Command(attodb::Command::JsonType(attodb::command::JsonType {
+                key,
+                path,
+            }))
+        }
+        Command::TsCreate { key, retention } => {
+            Message::Command(attodb::Command::TsCreate(attodb::command::TsCreate {
+                key,
+                retention,
+            }))
+        }
+        Command::TsAdd {
+            key,
+            timestamp,
+            value,
+        } => Message::Command(attodb::Command::TsAdd(attodb::command::TsAdd {
+            key,
+            timestamp: timestamp.parse().ok(),
+            value,
+        })),
+        Command::TsMAdd { samples } => {
+            let samples = samples
+                .chunks(3)
+                .map(|sample| attodb::command::TsSample {
+                    key: sample[0].clone(),
+                    timestamp: sample.get(1).and_then(|t| t.parse().ok()),
+                    value: sample.get(2).and_then(|v| v.parse().ok()).unwrap_or(0.0),
+                })
+                .collect();
+            Message::Command(attodb::Command::TsMAdd(attodb::command::TsMAdd { samples }))
+        }
+        Command::TsRange {
+            key,
+            from,
+            to,
+            aggregation,
+            bucket,
+        } => {
+            let aggregation = aggregation
+                .and_then(|name| Aggregation::parse(&name))
+                .zip(bucket);
+            Message::Command(attodb::Command::TsRange(attodb::command::TsRange {
+                key,
+                from: from.parse().ok(),
+                to: to.parse().ok(),
+                aggregation,
+            }))
+        }
+        Command::TsCreateRule {
+            source,
+            dest,
+            aggregation,
+            bucket,
+        } => {
+            let Some(aggregation) = Aggregation::parse(&aggregation) else {
+                println!("unknown aggregation {aggregation}");
+                return None;
+            };
+            Message::Command(attodb::Command::TsCreateRule(
+                attodb::command::TsCreateRule {
+                    source,
+                    dest,
+                    aggregation,
+                    bucket,
+                },
+            ))
+        }
+        Command::TsDeleteRule { source, dest } => Message::Command(attodb::Command::TsDeleteRule(
+            attodb::command::TsDeleteRule { source, dest },
+        )),
+        Command::VCreate {
+            index,
+            prefix,
+            dim,
+            metric,
+            hnsw,
+            m,
+            ef_construction,
+        } => {
+            let Some(metric) = Metric::parse(&metric) else {
+                println!("unknown metric {metric}");
+                return None;
+            };
+            let algorithm = if hnsw {
+                Algorithm::Hnsw { m, ef_construction }
+            } else {
+                Algorithm::Flat
+            };
+            Message::Command(attodb::Command::VCreate(attodb::command::VCreate {
+                index,
+                prefix,
+                dim,
+                metric,
+                algorithm,
+            }))
+        }
+        Command::VDrop { index } => {
+            Message::Command(attodb::Command::VDrop(attodb::command::VDrop { index }))
+        }
+        Command::VAdd { key, vector } => {
+            let Some(vector) = vector::parse(&vector) else {
+                println!("invalid vector");
+                return None;
+            };
+            Message::Command(attodb::Command::VAdd(attodb::command::VAdd { key, vector }))
+        }
+        Command::VSearch {
+            index,
+            k,
+            vector,
+            ef,
+        } => {
+            let Some(vector) = vector::parse(&vector) else {
+                println!("invalid vector");
+                return None;
+            };
+            Message::Command(attodb::Command::VSearch(attodb::command::VSearch {
+                index,
+                k,
+                vector,
+                ef,
+            }))
+        }
+        Command::FtCreate { index, prefix } => {
+            Message::Command(attodb::Command::FtCreate(attodb::command::FtCreate {
+                index,
+                prefix,
+            }))
+        }
+        Command::FtDrop { index } => {
+            Message::Command(attodb::Command::FtDrop(attodb::command::FtDrop { index }))
+        }
+        Command::FtSearch {
+            index,
+            query,
+            limit,
+        } => Message::Command(attodb::Command::FtSearch(attodb::command::FtSearch {
+            index,
+            query,
+            limit,
+        })),
+        Command::Type { key } => {
+            Message::Command(attodb::Command::Type(attodb::command::Type { key }))
+        }
+        Command::Exists { keys } => {
+            Message::Command(attodb::Command::Exists(attodb::command::Exists { keys }))
+        }
+        Command::Rename { key, new_key } => {
+            Message::Command(attodb::Command::Rename(attodb::command::Rename {
+                key,
+                new_key,
+            }))
+        }
+        Command::RenameNx { key, new_key } => {
+            Message::Command(attodb::Command::RenameNx(attodb::command::RenameNx {
+                key,
+                new_key,
+            }))
+        }
+        Command::Copy { key, dest, replace } => {
+            Message::Command(attodb::Command::Copy(attodb::command::Copy {
+                key,
+                dest,
+                replace,
+            }))
+        }
+        Command::Touch { keys } => {
+            Message::Command(attodb::Command::Touch(attodb::command::Touch { keys }))
+        }
+        Command::Unlink { keys } => {
+            Message::Command(attodb::Command::Unlink(attodb::command::Unlink { keys }))
+        }
+        Command::Keys { pattern } => {
+            Message::Command(attodb::Command::Keys(attodb::command::Keys { pattern }))
+        }
+        Command::Scan {
+            cursor,
+            pattern,
+            count,
+            kind,
+        } => Message::Command(attodb::Command::Scan(attodb::command::Scan {
+            cursor,
+            pattern,
+            count,
+            kind,
+        })),
+        Command::Range {
+            start,
+            end,
+            prefix,
+            limit,
+        } => {
+            let range = key_range(start, end, prefix)?;
+            Message::Command(attodb::Command::Range(attodb::command::Range {
+                range,
+                limit,
+            }))
+        }
+        Command::RevRange {
+            start,
+            end,
+            prefix,
+            limit,
+        } => {
+            let range = key_range(start, end, prefix)?;
+            Message::Command(attodb::Command::RevRange(attodb::command::RevRange {
+                range,
+                limit,
+            }))
+        }
+        Command::SetNx { key, value } => {
+            Message::Command(attodb::Command::SetNx(attodb::command::SetNx {
+                key,
+                value: input_to_value(&value).into_vec(),
+            }))
+        }
+        Command::GetSet { key, value } => {
+            Message::Command(attodb::Command::GetSet(attodb::command::GetSet {
+                key,
+                value: input_to_value(&value).into_vec(),
+            }))
+        }
+        Command::GetDel { key } => {
+            Message::Command(attodb::Command::GetDel(attodb::command::GetDel { key }))
+        }
+        Command::Expire { key, seconds } => {
+            Message::Command(attodb::Command::Expire(attodb::command::Expire {
+                key,
+                seconds,
+            }))
+        }
+        Command::PExpire { key, millis } => {
+            Message::Command(attodb::Command::PExpire(attodb::command::PExpire {
+                key,
+                millis,
+            }))
+        }
+        Command::ExpireAt { key, timestamp } => {
+            Message::Command(attodb::Command::ExpireAt(attodb::command::ExpireAt {
+                key,
+                timestamp,
+            }))
+        }
+        Command::PExpireAt { key, timestamp } => {
+            Message::Command(attodb::Command::PExpireAt(attodb::command::PExpireAt {
+                key,
+                timestamp,
+            }))
+        }
+        Command::Ttl { key } => {
+            Message::Command(attodb::Command::Ttl(attodb::command::Ttl { key }))
+        }
+        Command::PTtl { key } => {
+            Message::Command(attodb::Command::PTtl(attodb::command::PTtl { key }))
+        }
+        Command::Persist { key } => {
+            Message::Command(attodb::Command::Persist(attodb::command::Persist { key }))
+        }
+        Command::MGet { keys } => {
+            Message::Command(attodb::Command::MGet(attodb::command::MGet { keys }))
+        }
+        Command::MSet { pairs } => {
+            let Some(pairs) = input_to_pairs(pairs) else {
+                println!("every key needs a value");
+                return None;
+            };
+            Message::Command(attodb::Command::MSet(attodb::command::MSet { pairs }))
+        }
+        Command::MSetNx { pairs } => {
+            let Some(pairs) = input_to_pairs(pairs) else {
+                println!("every key needs a value");
+                return None;
+            };
+            Message::Command(attodb::Command::MSetNx(attodb::command::MSetNx { pairs }))
+        }
+        Command::Append { key, value } => {
+            Message::Command(attodb::Command::Append(attodb::command::Append {
+                key,
+                value,
+            }))
+        }
+        Command::StrLen { key } => {
+            Message::Command(attodb::Command::StrLen(attodb::command::StrLen { key }))
+        }
+        Command::GetRange { key, start, end } => {
+            Message::Command(attodb::Command::GetRange(attodb::command::GetRange {
+                key,
+                start,
+                end,
+            }))
+        }
+        Command::SetRange { key, offset, value } => {
+            Message::Command(attodb::Command::SetRange(attodb::command::SetRange {
+                key,
+                offset,
+                value,
+            }))
+        }
+        Command::Txn {
+            compares,
+            success,
+            failure,
+        } => {
+            let compares = compares
+                .iter()
+                .map(|compare| input_to_compare(compare))
+                .collect::<Option<_>>()?;
+            let success = success
+                .iter()
+                .map(|command| input_to_command(command))
+                .collect::<Option<_>>()?;
+            let failure = failure
+                .iter()
+                .map(|command| input_to_command(command))
+                .collect::<Option<_>>()?;
+            Message::Command(attodb::Command::Txn(attodb::command::Txn {
+                compares,
+                success,
+                failure,
+            }))
+        }
+        Command::Cas {
+            key,
+            expected,
+            value,
+            version,
+        } => Message::Command(attodb::Command::Cas(attodb::command::Cas {
+            key,
+            expected: input_to_expected(&expected, version)?,
+            value: input_to_value(&value).into_vec(),
+        })),
+        Command::Cad {
+            key,
+            expected,
+            version,
+        } => Message::Command(attodb::Command::Cad(attodb::command::Cad {
+            key,
+            expected: input_to_expected(&expected, version)?,
+        })),
+        Command::GetVer { key } => {
+            Message::Command(attodb::Command::GetVer(attodb::command::GetVer { key }))
+        }
+        Command::Subscribe { channels } => {
+            Message::Command(attodb::Command::Subscribe(attodb::command::Subscribe {
+                channels,
+            }))
+        }
+        Command::PSubscribe { patterns } => {
+            Message::Command(attodb::Command::PSubscribe(attodb::command::PSubscribe {
+                patterns,
+            }))
+        }
+        Command::Publish { channel, message } => {
+            Message::Command(attodb::Command::Publish(attodb::command::Publish {
+                channel,
+                message,
+            }))
+        }
+        Command::ConfigGet { parameter } => {
+            Message::Command(attodb::Command::ConfigGet(attodb::command::ConfigGet {
+                parameter,
+            }))
+        }
+        Command::ConfigSet { parameter, value } => {
+            Message::Command(attodb::Command::ConfigSet(attodb::command::ConfigSet {
+                parameter,
+                value,
+            }))
+        }
+        Command::WatchRev { key, prefix, from } => {
+            let watched = if prefix {
+                Watched::Prefix(key)
+            } else {
+                Watched::Key(key)
+            };
+            Message::Command(attodb::Command::WatchRev(attodb::command::WatchRev {
+                watched,
+                from,
+            }))
+        }
+        Command::LPush { key, elements } => {
+            Message::Command(attodb::Command::LPush(attodb::command::LPush {
+                key,
+                elements,
+            }))
+        }
+        Command::RPush { key, elements } => {
+            Message::Command(attodb::Command::RPush(attodb::command::RPush {
+                key,
+                elements,
+            }))
+        }
+        Command::LPop { key } => {
+            Message::Command(attodb::Command::LPop(attodb::command::LPop { key }))
+        }
+        Command::RPop { key } => {
+            Message::Command(attodb::Command::RPop(attodb::command::RPop { key }))
+        }
+        Command::LLen { key } => {
+            Message::Command(attodb::Command::LLen(attodb::command::LLen { key }))
+        }
+        Command::LRange { key, start, stop } => {
+            Message::Command(attodb::Command::LRange(attodb::command::LRange {
+                key,
+                start,
+                stop,
+            }))
+        }
+        Command::LMove {
+            source,
+            destination,
+            from,
+            to,
+        } => Message::Command(attodb::Command::LMove(attodb::command::LMove {
+            source,
+            destination,
+            from: input_to_end(&from)?,
+            to: input_to_end(&to)?,
+        })),
+        Command::BLPop { keys, timeout } => {
+            Message::Command(attodb::Command::BLPop(attodb::command::BLPop {
+                keys,
+                timeout,
+            }))
+        }
+        Command::BRPop { keys, timeout } => {
+            Message::Command(attodb::Command::BRPop(attodb::command::BRPop {
+                keys,
+                timeout,
+            }))
+        }
+        Command::BLMove {
+            source,
+            destination,
+            from,
+            to,
+            timeout,
+        } => Message::Command(attodb::Command::BLMove(attodb::command::BLMove {
+            source,
+            destination,
+            from: input_to_end(&from)?,
+            to: input_to_end(&to)?,
+            timeout,
+        })),
+        Command::QCreate {
+            key,
+            visibility,
+            max_receives,
+            dead_letter,
+        } => Message::Command(attodb::Command::QCreate(attodb::command::QCreate {
+            key,
+            visibility,
+            dead_letter: max_receives.zip(dead_letter),
+        })),
+        Command::Enqueue { key, body, delay } => {
+            Message::Command(attodb::Command::Enqueue(attodb::command::Enqueue {
+                key,
+                body,
+                delay,
+            }))
+        }
+        Command::Dequeue { key, visibility } => {
+            Message::Command(attodb::Command::Dequeue(attodb::command::Dequeue {
+                key,
+                visibility,
+            }))
+        }
+        Command::Ack { key, receipt } => {
+            Message::Command(attodb::Command::Ack(attodb::command::Ack { key, receipt }))
+        }
+        Command::Nack {
+            key,
+            receipt,
+            delay,
+        } => Message::Command(attodb::Command::Nack(attodb::command::Nack {
+            key,
+            receipt,
+            delay,
+        })),
+        Command::QStats { key } => {
+            Message::Command(attodb::Command::QStats(attodb::command::QStats { key }))
+        }
+        Command::Schedule { key, at, payload } => {
+            Message::Command(attodb::Command::Schedule(attodb::command::Schedule {
+                key,
+                at,
+                payload,
+            }))
+        }
+        Command::Lock {
+            key,
+            owner,
+            ttl,
+            timeout,
+        } => Message::Command(attodb::Command::Lock(attodb::command::Lock {
+            key,
+            owner,
+            ttl,
+            timeout,
+        })),
+        Command::Unlock { key, owner } => {
+            Message::Command(attodb::Command::Unlock(attodb::command::Unlock {
+                key,
+                owner,
+            }))
+        }
+        Command::Extend { key, owner, ttl } => {
+            Message::Command(attodb::Command::Extend(attodb::command::Extend {
+                key,
+                owner,
+                ttl,
+            }))
+        }
+        Command::LeaseGrant { ttl } => {
+            Message::Command(attodb::Command::LeaseGrant(attodb::command::LeaseGrant {
+                ttl,
+            }))
+        }
+        Command::LeaseKeepAlive { id } => Message::Command(attodb::Command::LeaseKeepAlive(
+            attodb::command::LeaseKeepAlive { id },
+        )),
+        Command::LeaseRevoke { id } => {
+            Message::Command(attodb::Command::LeaseRevoke(attodb::command::LeaseRevoke {
+                id,
+            }))
+        }
+        Command::LeaseTtl { id } => {
+            Message::Command(attodb::Command::LeaseTtl(attodb::command::LeaseTtl { id }))
+        }
+        Command::Throttle {
+            key,
+            max_burst,
+            rate,
+            period,
+            quantity,
+        } => Message::Command(attodb::Command::Throttle(attodb::command::Throttle {
+            key,
+            max_burst,
+            rate,
+            period,
+            quantity,
+        })),
+        Command::Select { index } => {
+            Message::Command(attodb::Command::Select(attodb::command::Select { index }))
+        }
+        Command::SwapDb { first, second } => {
+            Message::Command(attodb::Command::SwapDb(attodb::command::SwapDb {
+                first,
+                second,
+            }))
+        }
+        Command::Move { key, db } => {
+            Message::Command(attodb::Command::Move(attodb::command::Move { key, db }))
+        }
+        Command::FlushDb => Message::Command(attodb::Command::FlushDb(attodb::command::FlushDb)),
+        Command::DbSize => Message::Command(attodb::Command::DbSize(attodb::command::DbSize)),
+        Command::NamespaceCreate {
+            name,
+            max_memory,
+            ttl,
+            eviction,
+            password,
+            read_password,
+        } => {
+            let eviction = match eviction {
+                Some(eviction) => match Eviction::parse(&eviction) {
+                    Some(eviction) => Some(eviction),
+                    None => {
+                        println!("unknown eviction policy {eviction}");
+                        return None;
+                    }
+                },
+                None => None,
+            };
+            Message::Command(attodb::Command::NamespaceCreate(
+                attodb::command::NamespaceCreate {
+                    name,
+                    max_memory,
+                    ttl,
+                    eviction,
+                    password,
+                    read_password,
+                },
+            ))
+        }
+        Command::NamespaceDrop { name } => Message::Command(attodb::Command::NamespaceDrop(
+            attodb::command::NamespaceDrop { name },
+        )),
+        Command::NamespaceUse { name, password } => Message::Command(
+            attodb::Command::NamespaceUse(attodb::command::NamespaceUse { name, password }),
+        ),
+        Command::NamespaceInfo { name } => Message::Command(attodb::Command::NamespaceInfo(
+            attodb::command::NamespaceInfo { name },
+        )),
+    })
+}
+
 fn input_to_value<'a>(input: &'a str) -> Value<'a> {
     if let Ok(num) = input.parse::<i32>() {
         Value::Int(num)
@@ -79,3 +1273,109 @@ fn input_to_value<'a>(input: &'a str) -> Value<'a> {
         Value::String(input)
     }
 }
+
+fn key_range(
+    start: Option<String>,
+    end: Option<String>,
+    prefix: Option<String>,
+) -> Option<KeyRange> {
+    match prefix {
+        Some(prefix) => Some(KeyRange::Prefix(prefix)),
+        None => {
+            let range = KeyRange::between(start.unwrap_or_default(), end.unwrap_or_default());
+            if range.is_err() {
+                println!("invalid range");
+            }
+            range.ok()
+        }
+    }
+}
+
+fn input_to_pairs(args: Vec<String>) -> Option<Vec<(String, Vec<u8>)>> {
+    if !args.len().is_multiple_of(2) {
+        return None;
+    }
+    Some(
+        args.chunks(2)
+            .map(|pair| (pair[0].clone(), input_to_value(&pair[1]).into_vec()))
+            .collect(),
+    )
+}
+
+/// Parses a comparison like `MOD key = 3`.
+fn input_to_compare(input: &str) -> Option<Compare> {
+    let mut words = input.splitn(4, ' ');
+    let (Some(target), Some(key), Some(op), Some(operand)) =
+        (words.next(), words.next(), words.next(), words.next())
+    else {
+        println!("invalid comparison: {input}");
+        return None;
+    };
+    let Some(comparison) = Comparison::parse(op) else {
+        println!("invalid operator: {op}");
+        return None;
+    };
+    let target = match (target.to_ascii_uppercase().as_str(), operand.parse::<u64>()) {
+        ("VALUE", _) => Some(Target::Value(operand.to_string())),
+        ("VERSION", Ok(n)) => Some(Target::Version(n)),
+        ("CREATE", Ok(n)) => Some(Target::Create(n)),
+        ("MOD", Ok(n)) => Some(Target::Mod(n)),
+        ("EXISTS", Ok(n)) if n <= 1 => Some(Target::Exists(n == 1)),
+        _ => None,
+    };
+    let Some(target) = target else {
+        println!("invalid comparison: {input}");
+        return None;
+    };
+    Some(Compare {
+        key: key.to_string(),
+        comparison,
+        target,
+    })
+}
+
+/// A command given as the argument of another, like `txn --then "set key value"`.
+#[derive(Parser, Debug)]
+#[command(no_binary_name = true)]
+struct Nested {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+fn input_to_command(input: &str) -> Option<attodb::Command> {
+    let nested = match Nested::try_parse_from(input.split_whitespace()) {
+        Ok(nested) => nested,
+        Err(err) => {
+            println!("{err}");
+            return None;
+        }
+    };
+    match to_message(nested.command)? {
+        Message::Command(command) => Some(command),
+        _ => {
+            println!("not a command: {input}");
+            None
+        }
+    }
+}
+
+fn input_to_expected(input: &str, version: bool) -> Option<Expected> {
+    if !version {
+        return Some(Expected::Value(input_to_value(input).into_vec()));
+    }
+    match input.parse() {
+        Ok(version) => Some(Expected::Version(version)),
+        Err(_) => {
+            println!("invalid version '{input}'");
+            None
+        }
+    }
+}
+
+fn input_to_end(input: &str) -> Option<End> {
+    let end = End::parse(input);
+    if end.is_none() {
+        println!("unknown end {input}, expected LEFT or RIGHT");
+    }
+    end
+}
@@ -1,60 +1,176 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Weak},
+    time::Duration,
+};
 
-use attodb::{command::Command, connection::Connection, message::Message};
-use dashmap::DashMap;
+use attodb::{
+    Db,
+    connection::Connection,
+    databases::{DEFAULT_DATABASES, Databases},
+    history::DEFAULT_HISTORY,
+    message::Message,
+    session::Session,
+};
+use clap::Parser;
 use tokio::net::{TcpListener, TcpStream};
 
+/// How often expired keys and leases are looked for.
+const EXPIRY_INTERVAL: Duration = Duration::from_millis(100);
+/// The most expired keys removed before letting other tasks run.
+const EXPIRY_BATCH: usize = 200;
+/// The most scheduled jobs released before letting other tasks run.
+const JOB_BATCH: usize = 200;
+/// The longest the job timer waits before checking its database still exists.
+const JOB_IDLE: Duration = Duration::from_secs(1);
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Keep keys in order, so they can be queried by range
+    #[arg(long)]
+    ordered: bool,
+    /// How many changes to keep, for watchers resuming from an earlier revision
+    #[arg(long, default_value_t = DEFAULT_HISTORY)]
+    history: usize,
+    /// How many logical databases there are, numbered from zero
+    #[arg(long, default_value_t = DEFAULT_DATABASES)]
+    databases: usize,
+}
+
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
     let listener = TcpListener::bind("127.0.0.1:7676").await.unwrap();
-    let db: Arc<DashMap<String, Vec<u8>>> = Arc::new(DashMap::new());
+    let databases = Arc::new(Databases::new(args.databases, move || {
+        let db = Arc::new(if args.ordered {
+            Db::ordered()
+        } else {
+            Db::new()
+        });
+        db.history.set_capacity(args.history);
+        // The tasks stop once the database is dropped, along with its namespace.
+        tokio::spawn(remove_expired(Arc::downgrade(&db)));
+        tokio::spawn(release_jobs(Arc::downgrade(&db)));
+        db
+    }));
 
     loop {
         let (socket, _) = listener.accept().await.unwrap();
-        let db = db.clone();
+        let databases = databases.clone();
         tokio::spawn(async move {
-            process(db, socket).await.unwrap();
+            process(databases, socket).await.unwrap();
         });
     }
 }
 
-async fn process(db: Arc<DashMap<String, Vec<u8>>>, socket: TcpStream) -> attodb::Result<()> {
-    let mut connection = Connection::new(socket);
-    let message = connection.read_message().await;
-    println!("Received message: {:?}", &message);
-    match message {
-        Ok(Some(Message::Ping)) => {
-            connection.write_message(Message::Ok).await?;
-        }
-        Ok(Some(Message::Command(Command::Get(get)))) => {
-            let message = get.perform(db)?;
-            connection.write_message(message).await?;
+async fn remove_expired(db: Weak<Db>) {
+    let mut interval = tokio::time::interval(EXPIRY_INTERVAL);
+    loop {
+        interval.tick().await;
+        let Some(db) = db.upgrade() else {
+            return;
+        };
+        loop {
+            let removed = {
+                let _lock = db.shared();
+                db.remove_expired(EXPIRY_BATCH)
+            };
+            if removed < EXPIRY_BATCH {
+                break;
+            }
+            tokio::task::yield_now().await;
         }
-        Ok(Some(Message::Command(Command::Set(set)))) => {
-            let message = set.perform(db)?;
-            connection.write_message(message).await?;
+        // Revoking a lease removes all of its keys at once, so needs the exclusive lock.
+        if db.leases.any_expired(attodb::command::now_millis()) {
+            let _lock = db.exclusive();
+            db.revoke_expired_leases();
         }
-        Ok(Some(Message::Command(Command::Incr(incr)))) => {
-            let message = incr.perform(db)?;
-            connection.write_message(message).await?;
+    }
+}
+
+/// Pushes scheduled jobs onto their ready lists as they fall due, sleeping until the next one is
+/// due or another is scheduled.
+async fn release_jobs(db: Weak<Db>) {
+    loop {
+        let Some(db) = db.upgrade() else {
+            return;
+        };
+        let released = {
+            let _lock = db.shared();
+            db.release_jobs(JOB_BATCH)
+        };
+        if released == JOB_BATCH {
+            tokio::task::yield_now().await;
+            continue;
         }
-        Ok(Some(Message::Command(Command::Del(del)))) => {
-            let message = del.perform(db)?;
-            connection.write_message(message).await?;
+        let wait = match db.schedule.next_due() {
+            Some(due) => Duration::from_millis(due.saturating_sub(attodb::command::now_millis())),
+            None => JOB_IDLE,
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(wait.min(JOB_IDLE)) => {}
+            _ = db.schedule.added() => {}
         }
-        // None means the connection closed gracefully
-        Ok(None) => {}
-        Err(err) => match err {
-            attodb::Error::InvalidUtf8
-            | attodb::Error::ParseMessage(_)
-            | attodb::Error::ParseCommand(_) => {
-                connection
-                    .write_message(Message::Err(err.to_string()))
+    }
+}
+
+async fn process(databases: Arc<Databases>, socket: TcpStream) -> attodb::Result<()> {
+    let mut connection = Connection::new(socket);
+    let mut session = Session::new();
+    loop {
+        let message = tokio::select! {
+            message = connection.read_message() => message,
+            pushed = session.pushed() => {
+                match pushed {
+                    Some(message) => reply(&mut connection, message).await?,
+                    None => {
+                        let err = "disconnected for falling too far behind on subscriptions";
+                        connection.write_message(Message::Err(err.to_string())).await?;
+                        return Ok(());
+                    }
+                }
+                continue;
+            }
+        };
+        match message {
+            Ok(Some(Message::Ping)) => {
+                connection.write_message(Message::Ok).await?;
+            }
+            Ok(Some(Message::Command(command))) => {
+                // None means the connection closed while the command was blocked
+                let Some(message) = session
+                    .perform(command, &databases, &mut connection)
                     .await?
+                else {
+                    return Ok(());
+                };
+                reply(&mut connection, message).await?;
             }
-            _ => return Err(err),
-        },
-        _ => {}
-    };
-    Ok(())
+            // None means the connection closed gracefully
+            Ok(None) => return Ok(()),
+            Err(err) => match err {
+                attodb::Error::InvalidUtf8
+                | attodb::Error::ParseMessage(_)
+                | attodb::Error::ParseCommand(_) => {
+                    session.failed();
+                    connection
+                        .write_message(Message::Err(err.to_string()))
+                        .await?
+                }
+                _ => return Err(err),
+            },
+            _ => {}
+        };
+    }
+}
+
+/// Writes a reply, or an error in its place if it's too large for the message format.
+async fn reply(connection: &mut Connection, message: Message) -> attodb::Result<()> {
+    match connection.write_message(message).await {
+        Err(err @ attodb::Error::WriteMessage(_)) => {
+            connection
+                .write_message(Message::Err(err.to_string()))
+                .await
+        }
+        result => result,
+    }
 }
@@ -0,0 +1,97 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+use tokio::sync::Notify;
+
+/// Those waiting for each key, in the order they started waiting.
+type Queues = HashMap<String, VecDeque<(u64, Arc<Notify>)>>;
+
+/// The connections blocked waiting for keys to be written, queued per key in the order they
+/// started waiting.
+///
+/// Only the connection at the front of a key's queue is woken when the key is written. It
+/// wakes the next once it's done waiting, whether it got an element or not, so elements go to
+/// whoever has waited longest.
+#[derive(Default)]
+pub struct Blocked {
+    next_id: AtomicU64,
+    /// How many are waiting, so keys can be written without taking the lock when nobody is.
+    waiting: AtomicUsize,
+    queues: Mutex<Queues>,
+}
+
+/// A place in the queues for some keys, given up when dropped.
+pub struct Waiter<'a> {
+    blocked: &'a Blocked,
+    id: u64,
+    keys: Vec<String>,
+    notify: Arc<Notify>,
+}
+
+impl Blocked {
+    /// Joins the back of the queue for each of the keys.
+    pub fn wait(&self, keys: Vec<String>) -> Waiter<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let notify = Arc::new(Notify::new());
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        let mut queues = self.lock();
+        for key in &keys {
+            queues
+                .entry(key.clone())
+                .or_default()
+                .push_back((id, notify.clone()));
+        }
+        Waiter {
+            blocked: self,
+            id,
+            keys,
+            notify,
+        }
+    }
+
+    /// Wakes whoever has waited longest for the key, so it can try again.
+    pub fn wake(&self, key: &str) {
+        if self.waiting.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        if let Some((_, notify)) = self.lock().get(key).and_then(VecDeque::front) {
+            notify.notify_one();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queues> {
+        self.queues.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Waiter<'_> {
+    /// Waits to be woken by one of the keys being written.
+    pub async fn woken(&self) {
+        self.notify.notified().await;
+    }
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        let mut queues = self.blocked.lock();
+        for key in &self.keys {
+            let Some(queue) = queues.get_mut(key) else {
+                continue;
+            };
+            queue.retain(|(id, _)| *id != self.id);
+            // The next in line may be owed a wakeup this waiter took, so it gets one either way.
+            match queue.front() {
+                Some((_, notify)) => notify.notify_one(),
+                None => {
+                    queues.remove(key);
+                }
+            }
+        }
+        self.blocked.waiting.fetch_sub(1, Ordering::SeqCst);
+    }
+}
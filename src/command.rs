@@ -1,18 +1,234 @@
-use std::io::Cursor;
+use std::{
+    io::Cursor,
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-use crate::Result;
+use crate::{Db, Message, Result, message};
 
+mod ack;
+mod append;
+mod blmove;
+mod blpop;
+mod brpop;
+mod cad;
+mod cas;
+mod config_get;
+mod config_set;
+mod copy;
+mod dbsize;
 mod del;
+mod dequeue;
+mod discard;
+mod enqueue;
+mod exec;
+mod exists;
+mod expire;
+mod expireat;
+mod extend;
+mod flushdb;
+mod ft_create;
+mod ft_drop;
+mod ft_search;
 mod get;
+mod getdel;
+mod getrange;
+mod getset;
+mod getver;
 mod incr;
+mod json_arrappend;
+mod json_del;
+mod json_get;
+mod json_numincrby;
+mod json_set;
+mod json_type;
+mod key_move;
+mod key_type;
+mod keys;
+mod lease_grant;
+mod lease_keepalive;
+mod lease_revoke;
+mod lease_ttl;
+mod llen;
+mod lmove;
+mod lock;
+mod lpop;
+mod lpush;
+mod lrange;
+mod mget;
+mod mset;
+mod msetnx;
+mod multi;
+mod nack;
+mod namespace_create;
+mod namespace_drop;
+mod namespace_info;
+mod namespace_use;
+mod persist;
+mod pexpire;
+mod pexpireat;
+mod psubscribe;
+mod pttl;
+mod publish;
+mod punsubscribe;
+mod qcreate;
+mod qstats;
+mod range;
+mod rename;
+mod renamenx;
+mod revrange;
+mod rpop;
+mod rpush;
+mod scan;
+mod schedule;
+mod select;
 mod set;
+mod setnx;
+mod setrange;
+mod strlen;
+mod subscribe;
+mod swapdb;
+mod throttle;
+mod touch;
+mod ts_add;
+mod ts_create;
+mod ts_createrule;
+mod ts_deleterule;
+mod ts_madd;
+mod ts_range;
+mod ttl;
+mod txn;
+mod unlink;
+mod unlock;
+mod unsubscribe;
+mod unwatch;
+mod unwatchrev;
+mod vadd;
+mod vcreate;
+mod vdrop;
+mod vsearch;
+mod watch;
+mod watchrev;
 
+pub use ack::Ack;
+pub use append::Append;
+pub use blmove::BLMove;
+pub use blpop::BLPop;
+pub use brpop::BRPop;
+pub use cad::Cad;
+pub use cas::Cas;
+pub use cas::Expected;
+pub use config_get::ConfigGet;
+pub use config_set::ConfigSet;
+pub use copy::Copy;
+pub use dbsize::DbSize;
 pub use del::Del;
+pub use dequeue::Dequeue;
+pub use discard::Discard;
+pub use enqueue::Enqueue;
+pub use exec::Exec;
+pub use exists::Exists;
+pub use expire::Expire;
+pub use expireat::ExpireAt;
+pub use extend::Extend;
+pub use flushdb::FlushDb;
+pub use ft_create::FtCreate;
+pub use ft_drop::FtDrop;
+pub use ft_search::FtSearch;
 pub use get::Get;
+pub use getdel::GetDel;
+pub use getrange::GetRange;
+pub use getset::GetSet;
+pub use getver::GetVer;
 pub use incr::Incr;
+pub use json_arrappend::JsonArrAppend;
+pub use json_del::JsonDel;
+pub use json_get::JsonGet;
+pub use json_numincrby::JsonNumIncrBy;
+pub use json_set::JsonSet;
+pub use json_type::JsonType;
+pub use key_move::Move;
+pub use key_type::Type;
+pub use keys::Keys;
+pub use lease_grant::LeaseGrant;
+pub use lease_keepalive::LeaseKeepAlive;
+pub use lease_revoke::LeaseRevoke;
+pub use lease_ttl::LeaseTtl;
+pub use llen::LLen;
+pub use lmove::End;
+pub use lmove::LMove;
+pub use lock::Lock;
+pub use lpop::LPop;
+pub use lpush::LPush;
+pub use lrange::LRange;
+pub use mget::MGet;
+pub use mset::MSet;
+pub use msetnx::MSetNx;
+pub use multi::Multi;
+pub use nack::Nack;
+pub use namespace_create::NamespaceCreate;
+pub use namespace_drop::NamespaceDrop;
+pub use namespace_info::NamespaceInfo;
+pub use namespace_use::NamespaceUse;
+pub use persist::Persist;
+pub use pexpire::PExpire;
+pub use pexpireat::PExpireAt;
+pub use psubscribe::PSubscribe;
+pub use pttl::PTtl;
+pub use publish::Publish;
+pub use punsubscribe::PUnsubscribe;
+pub use qcreate::QCreate;
+pub use qstats::QStats;
+pub use range::KeyRange;
+pub use range::Range;
+pub use rename::Rename;
+pub use renamenx::RenameNx;
+pub use revrange::RevRange;
+pub use rpop::RPop;
+pub use rpush::RPush;
+pub use scan::Scan;
+pub use schedule::Schedule;
+pub use select::Select;
+pub use set::Condition;
+pub use set::Expiry;
 pub use set::Set;
+pub use setnx::SetNx;
+pub use setrange::SetRange;
+pub use strlen::StrLen;
+pub use subscribe::Subscribe;
+pub use swapdb::SwapDb;
+pub use throttle::Throttle;
+pub use touch::Touch;
+pub use ts_add::TsAdd;
+pub use ts_create::TsCreate;
+pub use ts_createrule::TsCreateRule;
+pub use ts_deleterule::TsDeleteRule;
+pub use ts_madd::TsMAdd;
+pub use ts_madd::TsSample;
+pub use ts_range::TsRange;
+pub use ttl::Ttl;
+pub use txn::Compare;
+pub use txn::Comparison;
+pub use txn::Target;
+pub use txn::Txn;
+pub use unlink::Unlink;
+pub use unlock::Unlock;
+pub use unsubscribe::Unsubscribe;
+pub use unwatch::Unwatch;
+pub use unwatchrev::UnwatchRev;
+pub use vadd::VAdd;
+pub use vcreate::VCreate;
+pub use vdrop::VDrop;
+pub use vsearch::VSearch;
+pub use watch::Watch;
+pub use watchrev::WatchRev;
+
+pub(crate) use get::to_message;
+pub(crate) use lpush::push;
+pub(crate) use namespace_create::prefix as namespace_prefix;
 
 #[derive(Debug)]
 pub enum Command {
@@ -20,6 +236,105 @@ pub enum Command {
     Set(Set),
     Incr(Incr),
     Del(Del),
+    JsonSet(JsonSet),
+    JsonGet(JsonGet),
+    JsonDel(JsonDel),
+    JsonNumIncrBy(JsonNumIncrBy),
+    JsonArrAppend(JsonArrAppend),
+    JsonType(JsonType),
+    TsCreate(TsCreate),
+    TsAdd(TsAdd),
+    TsMAdd(TsMAdd),
+    TsRange(TsRange),
+    TsCreateRule(TsCreateRule),
+    TsDeleteRule(TsDeleteRule),
+    VCreate(VCreate),
+    VDrop(VDrop),
+    VAdd(VAdd),
+    VSearch(VSearch),
+    FtCreate(FtCreate),
+    FtDrop(FtDrop),
+    FtSearch(FtSearch),
+    Type(Type),
+    Exists(Exists),
+    Rename(Rename),
+    RenameNx(RenameNx),
+    Copy(Copy),
+    Touch(Touch),
+    Unlink(Unlink),
+    Keys(Keys),
+    Scan(Scan),
+    Range(Range),
+    RevRange(RevRange),
+    SetNx(SetNx),
+    GetSet(GetSet),
+    GetDel(GetDel),
+    Expire(Expire),
+    PExpire(PExpire),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    Persist(Persist),
+    MGet(MGet),
+    MSet(MSet),
+    MSetNx(MSetNx),
+    Append(Append),
+    StrLen(StrLen),
+    GetRange(GetRange),
+    SetRange(SetRange),
+    Multi(Multi),
+    Exec(Exec),
+    Discard(Discard),
+    Watch(Watch),
+    Unwatch(Unwatch),
+    Txn(Txn),
+    Cas(Cas),
+    Cad(Cad),
+    GetVer(GetVer),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    Publish(Publish),
+    ConfigGet(ConfigGet),
+    ConfigSet(ConfigSet),
+    WatchRev(WatchRev),
+    UnwatchRev(UnwatchRev),
+    LPush(LPush),
+    RPush(RPush),
+    LPop(LPop),
+    RPop(RPop),
+    LLen(LLen),
+    LRange(LRange),
+    LMove(LMove),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    BLMove(BLMove),
+    QCreate(QCreate),
+    Enqueue(Enqueue),
+    Dequeue(Dequeue),
+    Ack(Ack),
+    Nack(Nack),
+    QStats(QStats),
+    Schedule(Schedule),
+    Lock(Lock),
+    Unlock(Unlock),
+    Extend(Extend),
+    LeaseGrant(LeaseGrant),
+    LeaseKeepAlive(LeaseKeepAlive),
+    LeaseRevoke(LeaseRevoke),
+    LeaseTtl(LeaseTtl),
+    Throttle(Throttle),
+    Select(Select),
+    SwapDb(SwapDb),
+    Move(Move),
+    FlushDb(FlushDb),
+    DbSize(DbSize),
+    NamespaceCreate(NamespaceCreate),
+    NamespaceDrop(NamespaceDrop),
+    NamespaceUse(NamespaceUse),
+    NamespaceInfo(NamespaceInfo),
 }
 
 #[repr(u8)]
@@ -28,12 +343,112 @@ pub enum Variant {
     Set = 1,
     Incr = 2,
     Del = 3,
+    JsonSet = 4,
+    JsonGet = 5,
+    JsonDel = 6,
+    JsonNumIncrBy = 7,
+    JsonArrAppend = 8,
+    JsonType = 9,
+    TsCreate = 10,
+    TsAdd = 11,
+    TsMAdd = 12,
+    TsRange = 13,
+    TsCreateRule = 14,
+    TsDeleteRule = 15,
+    VCreate = 16,
+    VDrop = 17,
+    VAdd = 18,
+    VSearch = 19,
+    FtCreate = 20,
+    FtDrop = 21,
+    FtSearch = 22,
+    Type = 23,
+    Exists = 24,
+    Rename = 25,
+    RenameNx = 26,
+    Copy = 27,
+    Touch = 28,
+    Unlink = 29,
+    Keys = 30,
+    Scan = 31,
+    Range = 32,
+    RevRange = 33,
+    SetNx = 34,
+    GetSet = 35,
+    GetDel = 36,
+    Expire = 37,
+    PExpire = 38,
+    ExpireAt = 39,
+    PExpireAt = 40,
+    Ttl = 41,
+    PTtl = 42,
+    Persist = 43,
+    MGet = 44,
+    MSet = 45,
+    MSetNx = 46,
+    Append = 47,
+    StrLen = 48,
+    GetRange = 49,
+    SetRange = 50,
+    Multi = 51,
+    Exec = 52,
+    Discard = 53,
+    Watch = 54,
+    Unwatch = 55,
+    Txn = 56,
+    Cas = 57,
+    Cad = 58,
+    GetVer = 59,
+    Subscribe = 60,
+    Unsubscribe = 61,
+    PSubscribe = 62,
+    PUnsubscribe = 63,
+    Publish = 64,
+    ConfigGet = 65,
+    ConfigSet = 66,
+    WatchRev = 67,
+    UnwatchRev = 68,
+    LPush = 69,
+    RPush = 70,
+    LPop = 71,
+    RPop = 72,
+    LLen = 73,
+    LRange = 74,
+    LMove = 75,
+    BLPop = 76,
+    BRPop = 77,
+    BLMove = 78,
+    QCreate = 79,
+    Enqueue = 80,
+    Dequeue = 81,
+    Ack = 82,
+    Nack = 83,
+    QStats = 84,
+    Schedule = 85,
+    Lock = 86,
+    Unlock = 87,
+    Extend = 88,
+    LeaseGrant = 89,
+    LeaseKeepAlive = 90,
+    LeaseRevoke = 91,
+    LeaseTtl = 92,
+    Throttle = 93,
+    Select = 94,
+    SwapDb = 95,
+    Move = 96,
+    FlushDb = 97,
+    DbSize = 98,
+    NamespaceCreate = 99,
+    NamespaceDrop = 100,
+    NamespaceUse = 101,
+    NamespaceInfo = 102,
 }
 
 #[derive(Debug)]
 pub enum Error {
     UnknownCommandType(u8),
     WrongNumberArguments,
+    InvalidArgument,
 }
 
 impl TryFrom<u8> for Variant {
@@ -45,6 +460,105 @@ impl TryFrom<u8> for Variant {
             1 => Ok(Variant::Set),
             2 => Ok(Variant::Incr),
             3 => Ok(Variant::Del),
+            4 => Ok(Variant::JsonSet),
+            5 => Ok(Variant::JsonGet),
+            6 => Ok(Variant::JsonDel),
+            7 => Ok(Variant::JsonNumIncrBy),
+            8 => Ok(Variant::JsonArrAppend),
+            9 => Ok(Variant::JsonType),
+            10 => Ok(Variant::TsCreate),
+            11 => Ok(Variant::TsAdd),
+            12 => Ok(Variant::TsMAdd),
+            13 => Ok(Variant::TsRange),
+            14 => Ok(Variant::TsCreateRule),
+            15 => Ok(Variant::TsDeleteRule),
+            16 => Ok(Variant::VCreate),
+            17 => Ok(Variant::VDrop),
+            18 => Ok(Variant::VAdd),
+            19 => Ok(Variant::VSearch),
+            20 => Ok(Variant::FtCreate),
+            21 => Ok(Variant::FtDrop),
+            22 => Ok(Variant::FtSearch),
+            23 => Ok(Variant::Type),
+            24 => Ok(Variant::Exists),
+            25 => Ok(Variant::Rename),
+            26 => Ok(Variant::RenameNx),
+            27 => Ok(Variant::Copy),
+            28 => Ok(Variant::Touch),
+            29 => Ok(Variant::Unlink),
+            30 => Ok(Variant::Keys),
+            31 => Ok(Variant::Scan),
+            32 => Ok(Variant::Range),
+            33 => Ok(Variant::RevRange),
+            34 => Ok(Variant::SetNx),
+            35 => Ok(Variant::GetSet),
+            36 => Ok(Variant::GetDel),
+            37 => Ok(Variant::Expire),
+            38 => Ok(Variant::PExpire),
+            39 => Ok(Variant::ExpireAt),
+            40 => Ok(Variant::PExpireAt),
+            41 => Ok(Variant::Ttl),
+            42 => Ok(Variant::PTtl),
+            43 => Ok(Variant::Persist),
+            44 => Ok(Variant::MGet),
+            45 => Ok(Variant::MSet),
+            46 => Ok(Variant::MSetNx),
+            47 => Ok(Variant::Append),
+            48 => Ok(Variant::StrLen),
+            49 => Ok(Variant::GetRange),
+            50 => Ok(Variant::SetRange),
+            51 => Ok(Variant::Multi),
+            52 => Ok(Variant::Exec),
+            53 => Ok(Variant::Discard),
+            54 => Ok(Variant::Watch),
+            55 => Ok(Variant::Unwatch),
+            56 => Ok(Variant::Txn),
+            57 => Ok(Variant::Cas),
+            58 => Ok(Variant::Cad),
+            59 => Ok(Variant::GetVer),
+            60 => Ok(Variant::Subscribe),
+            61 => Ok(Variant::Unsubscribe),
+            62 => Ok(Variant::PSubscribe),
+            63 => Ok(Variant::PUnsubscribe),
+            64 => Ok(Variant::Publish),
+            65 => Ok(Variant::ConfigGet),
+            66 => Ok(Variant::ConfigSet),
+            67 => Ok(Variant::WatchRev),
+            68 => Ok(Variant::UnwatchRev),
+            69 => Ok(Variant::LPush),
+            70 => Ok(Variant::RPush),
+            71 => Ok(Variant::LPop),
+            72 => Ok(Variant::RPop),
+            73 => Ok(Variant::LLen),
+            74 => Ok(Variant::LRange),
+            75 => Ok(Variant::LMove),
+            76 => Ok(Variant::BLPop),
+            77 => Ok(Variant::BRPop),
+            78 => Ok(Variant::BLMove),
+            79 => Ok(Variant::QCreate),
+            80 => Ok(Variant::Enqueue),
+            81 => Ok(Variant::Dequeue),
+            82 => Ok(Variant::Ack),
+            83 => Ok(Variant::Nack),
+            84 => Ok(Variant::QStats),
+            85 => Ok(Variant::Schedule),
+            86 => Ok(Variant::Lock),
+            87 => Ok(Variant::Unlock),
+            88 => Ok(Variant::Extend),
+            89 => Ok(Variant::LeaseGrant),
+            90 => Ok(Variant::LeaseKeepAlive),
+            91 => Ok(Variant::LeaseRevoke),
+            92 => Ok(Variant::LeaseTtl),
+            93 => Ok(Variant::Throttle),
+            94 => Ok(Variant::Select),
+            95 => Ok(Variant::SwapDb),
+            96 => Ok(Variant::Move),
+            97 => Ok(Variant::FlushDb),
+            98 => Ok(Variant::DbSize),
+            99 => Ok(Variant::NamespaceCreate),
+            100 => Ok(Variant::NamespaceDrop),
+            101 => Ok(Variant::NamespaceUse),
+            102 => Ok(Variant::NamespaceInfo),
             _ => Err(Error::UnknownCommandType(value)),
         }
     }
@@ -62,6 +576,427 @@ impl Command {
             Variant::Set => Set::parse(src).await.map(Command::Set),
             Variant::Incr => Incr::parse(src).await.map(Command::Incr),
             Variant::Del => Del::parse(src).await.map(Command::Del),
+            Variant::JsonSet => JsonSet::parse(src).await.map(Command::JsonSet),
+            Variant::JsonGet => JsonGet::parse(src).await.map(Command::JsonGet),
+            Variant::JsonDel => JsonDel::parse(src).await.map(Command::JsonDel),
+            Variant::JsonNumIncrBy => JsonNumIncrBy::parse(src).await.map(Command::JsonNumIncrBy),
+            Variant::JsonArrAppend => JsonArrAppend::parse(src).await.map(Command::JsonArrAppend),
+            Variant::JsonType => JsonType::parse(src).await.map(Command::JsonType),
+            Variant::TsCreate => TsCreate::parse(src).await.map(Command::TsCreate),
+            Variant::TsAdd => TsAdd::parse(src).await.map(Command::TsAdd),
+            Variant::TsMAdd => TsMAdd::parse(src).await.map(Command::TsMAdd),
+            Variant::TsRange => TsRange::parse(src).await.map(Command::TsRange),
+            Variant::TsCreateRule => TsCreateRule::parse(src).await.map(Command::TsCreateRule),
+            Variant::TsDeleteRule => TsDeleteRule::parse(src).await.map(Command::TsDeleteRule),
+            Variant::VCreate => VCreate::parse(src).await.map(Command::VCreate),
+            Variant::VDrop => VDrop::parse(src).await.map(Command::VDrop),
+            Variant::VAdd => VAdd::parse(src).await.map(Command::VAdd),
+            Variant::VSearch => VSearch::parse(src).await.map(Command::VSearch),
+            Variant::FtCreate => FtCreate::parse(src).await.map(Command::FtCreate),
+            Variant::FtDrop => FtDrop::parse(src).await.map(Command::FtDrop),
+            Variant::FtSearch => FtSearch::parse(src).await.map(Command::FtSearch),
+            Variant::Type => Type::parse(src).await.map(Command::Type),
+            Variant::Exists => Exists::parse(src).await.map(Command::Exists),
+            Variant::Rename => Rename::parse(src).await.map(Command::Rename),
+            Variant::RenameNx => RenameNx::parse(src).await.map(Command::RenameNx),
+            Variant::Copy => Copy::parse(src).await.map(Command::Copy),
+            Variant::Touch => Touch::parse(src).await.map(Command::Touch),
+            Variant::Unlink => Unlink::parse(src).await.map(Command::Unlink),
+            Variant::Keys => Keys::parse(src).await.map(Command::Keys),
+            Variant::Scan => Scan::parse(src).await.map(Command::Scan),
+            Variant::Range => Range::parse(src).await.map(Command::Range),
+            Variant::RevRange => RevRange::parse(src).await.map(Command::RevRange),
+            Variant::SetNx => SetNx::parse(src).await.map(Command::SetNx),
+            Variant::GetSet => GetSet::parse(src).await.map(Command::GetSet),
+            Variant::GetDel => GetDel::parse(src).await.map(Command::GetDel),
+            Variant::Expire => Expire::parse(src).await.map(Command::Expire),
+            Variant::PExpire => PExpire::parse(src).await.map(Command::PExpire),
+            Variant::ExpireAt => ExpireAt::parse(src).await.map(Command::ExpireAt),
+            Variant::PExpireAt => PExpireAt::parse(src).await.map(Command::PExpireAt),
+            Variant::Ttl => Ttl::parse(src).await.map(Command::Ttl),
+            Variant::PTtl => PTtl::parse(src).await.map(Command::PTtl),
+            Variant::Persist => Persist::parse(src).await.map(Command::Persist),
+            Variant::MGet => MGet::parse(src).await.map(Command::MGet),
+            Variant::MSet => MSet::parse(src).await.map(Command::MSet),
+            Variant::MSetNx => MSetNx::parse(src).await.map(Command::MSetNx),
+            Variant::Append => Append::parse(src).await.map(Command::Append),
+            Variant::StrLen => StrLen::parse(src).await.map(Command::StrLen),
+            Variant::GetRange => GetRange::parse(src).await.map(Command::GetRange),
+            Variant::SetRange => SetRange::parse(src).await.map(Command::SetRange),
+            Variant::Multi => Multi::parse(src).await.map(Command::Multi),
+            Variant::Exec => Exec::parse(src).await.map(Command::Exec),
+            Variant::Discard => Discard::parse(src).await.map(Command::Discard),
+            Variant::Watch => Watch::parse(src).await.map(Command::Watch),
+            Variant::Unwatch => Unwatch::parse(src).await.map(Command::Unwatch),
+            Variant::Txn => Txn::parse(src).await.map(Command::Txn),
+            Variant::Cas => Cas::parse(src).await.map(Command::Cas),
+            Variant::Cad => Cad::parse(src).await.map(Command::Cad),
+            Variant::GetVer => GetVer::parse(src).await.map(Command::GetVer),
+            Variant::Subscribe => Subscribe::parse(src).await.map(Command::Subscribe),
+            Variant::Unsubscribe => Unsubscribe::parse(src).await.map(Command::Unsubscribe),
+            Variant::PSubscribe => PSubscribe::parse(src).await.map(Command::PSubscribe),
+            Variant::PUnsubscribe => PUnsubscribe::parse(src).await.map(Command::PUnsubscribe),
+            Variant::Publish => Publish::parse(src).await.map(Command::Publish),
+            Variant::ConfigGet => ConfigGet::parse(src).await.map(Command::ConfigGet),
+            Variant::ConfigSet => ConfigSet::parse(src).await.map(Command::ConfigSet),
+            Variant::WatchRev => WatchRev::parse(src).await.map(Command::WatchRev),
+            Variant::UnwatchRev => UnwatchRev::parse(src).await.map(Command::UnwatchRev),
+            Variant::LPush => LPush::parse(src).await.map(Command::LPush),
+            Variant::RPush => RPush::parse(src).await.map(Command::RPush),
+            Variant::LPop => LPop::parse(src).await.map(Command::LPop),
+            Variant::RPop => RPop::parse(src).await.map(Command::RPop),
+            Variant::LLen => LLen::parse(src).await.map(Command::LLen),
+            Variant::LRange => LRange::parse(src).await.map(Command::LRange),
+            Variant::LMove => LMove::parse(src).await.map(Command::LMove),
+            Variant::BLPop => BLPop::parse(src).await.map(Command::BLPop),
+            Variant::BRPop => BRPop::parse(src).await.map(Command::BRPop),
+            Variant::BLMove => BLMove::parse(src).await.map(Command::BLMove),
+            Variant::QCreate => QCreate::parse(src).await.map(Command::QCreate),
+            Variant::Enqueue => Enqueue::parse(src).await.map(Command::Enqueue),
+            Variant::Dequeue => Dequeue::parse(src).await.map(Command::Dequeue),
+            Variant::Ack => Ack::parse(src).await.map(Command::Ack),
+            Variant::Nack => Nack::parse(src).await.map(Command::Nack),
+            Variant::QStats => QStats::parse(src).await.map(Command::QStats),
+            Variant::Schedule => Schedule::parse(src).await.map(Command::Schedule),
+            Variant::Lock => Lock::parse(src).await.map(Command::Lock),
+            Variant::Unlock => Unlock::parse(src).await.map(Command::Unlock),
+            Variant::Extend => Extend::parse(src).await.map(Command::Extend),
+            Variant::LeaseGrant => LeaseGrant::parse(src).await.map(Command::LeaseGrant),
+            Variant::LeaseKeepAlive => LeaseKeepAlive::parse(src)
+                .await
+                .map(Command::LeaseKeepAlive),
+            Variant::LeaseRevoke => LeaseRevoke::parse(src).await.map(Command::LeaseRevoke),
+            Variant::LeaseTtl => LeaseTtl::parse(src).await.map(Command::LeaseTtl),
+            Variant::Throttle => Throttle::parse(src).await.map(Command::Throttle),
+            Variant::Select => Select::parse(src).await.map(Command::Select),
+            Variant::SwapDb => SwapDb::parse(src).await.map(Command::SwapDb),
+            Variant::Move => Move::parse(src).await.map(Command::Move),
+            Variant::FlushDb => FlushDb::parse(src).await.map(Command::FlushDb),
+            Variant::DbSize => DbSize::parse(src).await.map(Command::DbSize),
+            Variant::NamespaceCreate => NamespaceCreate::parse(src)
+                .await
+                .map(Command::NamespaceCreate),
+            Variant::NamespaceDrop => NamespaceDrop::parse(src).await.map(Command::NamespaceDrop),
+            Variant::NamespaceUse => NamespaceUse::parse(src).await.map(Command::NamespaceUse),
+            Variant::NamespaceInfo => NamespaceInfo::parse(src).await.map(Command::NamespaceInfo),
+        }
+    }
+
+    pub fn perform(self, db: Arc<Db>) -> Result<Message> {
+        if let Some(refused) = self.refused(&db) {
+            return Ok(refused);
+        }
+        let shared = db.shared();
+        let message = if self.is_multi_key(&db) {
+            drop(shared);
+            let _lock = db.exclusive();
+            self.execute(db.clone())
+        } else {
+            // Holding the shared lock while checking means no compaction rule can be created
+            // before the command runs.
+            let message = self.execute(db.clone());
+            drop(shared);
+            message
+        };
+        let _lock = db.shared();
+        db.evict();
+        message
+    }
+
+    /// Whether the command writes several keys, and so must exclude every other command while
+    /// it runs to appear atomic.
+    ///
+    /// Adding samples to a time series with compaction rules also writes their destinations.
+    fn is_multi_key(&self, db: &Db) -> bool {
+        match self {
+            Command::TsAdd(add) => ts_add::has_rules(db, &add.key),
+            Command::TsMAdd(madd) => madd.samples.iter().any(|s| ts_add::has_rules(db, &s.key)),
+            Command::Del(del) => del.keys.len() > 1,
+            Command::Unlink(unlink) => unlink.keys.len() > 1,
+            Command::MSet(mset) => mset.pairs.len() > 1,
+            Command::MSetNx(_)
+            | Command::Rename(_)
+            | Command::RenameNx(_)
+            | Command::Copy(_)
+            | Command::Txn(_)
+            | Command::TsCreateRule(_)
+            | Command::LMove(_)
+            | Command::BLMove(_)
+            | Command::Dequeue(_)
+            | Command::LeaseRevoke(_)
+            | Command::FlushDb(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The keys the command names, for addressing a namespace by their prefix.
+    pub(crate) fn keys_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            Command::Get(command) => vec![&mut command.key],
+            Command::Set(command) => vec![&mut command.key],
+            Command::Incr(command) => vec![&mut command.key],
+            Command::JsonSet(command) => vec![&mut command.key],
+            Command::JsonGet(command) => vec![&mut command.key],
+            Command::JsonDel(command) => vec![&mut command.key],
+            Command::JsonNumIncrBy(command) => vec![&mut command.key],
+            Command::JsonArrAppend(command) => vec![&mut command.key],
+            Command::JsonType(command) => vec![&mut command.key],
+            Command::TsCreate(command) => vec![&mut command.key],
+            Command::TsAdd(command) => vec![&mut command.key],
+            Command::TsRange(command) => vec![&mut command.key],
+            Command::VAdd(command) => vec![&mut command.key],
+            Command::Type(command) => vec![&mut command.key],
+            Command::SetNx(command) => vec![&mut command.key],
+            Command::GetSet(command) => vec![&mut command.key],
+            Command::GetDel(command) => vec![&mut command.key],
+            Command::Expire(command) => vec![&mut command.key],
+            Command::PExpire(command) => vec![&mut command.key],
+            Command::ExpireAt(command) => vec![&mut command.key],
+            Command::PExpireAt(command) => vec![&mut command.key],
+            Command::Ttl(command) => vec![&mut command.key],
+            Command::PTtl(command) => vec![&mut command.key],
+            Command::Persist(command) => vec![&mut command.key],
+            Command::Append(command) => vec![&mut command.key],
+            Command::StrLen(command) => vec![&mut command.key],
+            Command::GetRange(command) => vec![&mut command.key],
+            Command::SetRange(command) => vec![&mut command.key],
+            Command::Cas(command) => vec![&mut command.key],
+            Command::Cad(command) => vec![&mut command.key],
+            Command::GetVer(command) => vec![&mut command.key],
+            Command::LPush(command) => vec![&mut command.key],
+            Command::RPush(command) => vec![&mut command.key],
+            Command::LPop(command) => vec![&mut command.key],
+            Command::RPop(command) => vec![&mut command.key],
+            Command::LLen(command) => vec![&mut command.key],
+            Command::LRange(command) => vec![&mut command.key],
+            Command::Enqueue(command) => vec![&mut command.key],
+            Command::Dequeue(command) => vec![&mut command.key],
+            Command::Ack(command) => vec![&mut command.key],
+            Command::Nack(command) => vec![&mut command.key],
+            Command::QStats(command) => vec![&mut command.key],
+            Command::Schedule(command) => vec![&mut command.key],
+            Command::Lock(command) => vec![&mut command.key],
+            Command::Unlock(command) => vec![&mut command.key],
+            Command::Extend(command) => vec![&mut command.key],
+            Command::Throttle(command) => vec![&mut command.key],
+            Command::Move(command) => vec![&mut command.key],
+            Command::Del(command) => command.keys.iter_mut().collect(),
+            Command::Exists(command) => command.keys.iter_mut().collect(),
+            Command::Touch(command) => command.keys.iter_mut().collect(),
+            Command::Unlink(command) => command.keys.iter_mut().collect(),
+            Command::MGet(command) => command.keys.iter_mut().collect(),
+            Command::BLPop(command) => command.keys.iter_mut().collect(),
+            Command::BRPop(command) => command.keys.iter_mut().collect(),
+            Command::Rename(rename) => vec![&mut rename.key, &mut rename.new_key],
+            Command::RenameNx(renamenx) => vec![&mut renamenx.key, &mut renamenx.new_key],
+            Command::Copy(copy) => vec![&mut copy.key, &mut copy.dest],
+            Command::MSet(mset) => mset.pairs.iter_mut().map(|(key, _)| key).collect(),
+            Command::MSetNx(msetnx) => msetnx.pairs.iter_mut().map(|(key, _)| key).collect(),
+            Command::TsMAdd(ts_madd) => ts_madd.samples.iter_mut().map(|s| &mut s.key).collect(),
+            Command::TsCreateRule(rule) => vec![&mut rule.source, &mut rule.dest],
+            Command::TsDeleteRule(rule) => vec![&mut rule.source, &mut rule.dest],
+            Command::LMove(lmove) => vec![&mut lmove.source, &mut lmove.destination],
+            Command::BLMove(blmove) => vec![&mut blmove.source, &mut blmove.destination],
+            Command::QCreate(qcreate) => {
+                let mut keys = vec![&mut qcreate.key];
+                keys.extend(qcreate.dead_letter.as_mut().map(|(_, key)| key));
+                keys
+            }
+            Command::Txn(txn) => {
+                let mut keys: Vec<&mut String> =
+                    txn.compares.iter_mut().map(|c| &mut c.key).collect();
+                for command in txn.success.iter_mut().chain(txn.failure.iter_mut()) {
+                    keys.extend(command.keys_mut());
+                }
+                keys
+            }
+            // Session commands like WATCH act on the connection's keyspace, and the rest name
+            // indexes, channels, leases or patterns rather than keys.
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether the command never writes to the keyspace, so can be used with read-only access
+    /// or once the memory limit is reached. `EXEC` is allowed since what it runs was checked
+    /// when queued.
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Command::Get(_)
+                | Command::MGet(_)
+                | Command::Exists(_)
+                | Command::Ttl(_)
+                | Command::PTtl(_)
+                | Command::Type(_)
+                | Command::Keys(_)
+                | Command::Scan(_)
+                | Command::Range(_)
+                | Command::RevRange(_)
+                | Command::JsonGet(_)
+                | Command::JsonType(_)
+                | Command::StrLen(_)
+                | Command::GetRange(_)
+                | Command::LLen(_)
+                | Command::LRange(_)
+                | Command::TsRange(_)
+                | Command::VSearch(_)
+                | Command::FtSearch(_)
+                | Command::GetVer(_)
+                | Command::QStats(_)
+                | Command::LeaseTtl(_)
+                | Command::DbSize(_)
+                | Command::ConfigGet(_)
+                | Command::Publish(_)
+                | Command::Multi(_)
+                | Command::Exec(_)
+                | Command::Discard(_)
+                | Command::Watch(_)
+                | Command::Unwatch(_)
+                | Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::WatchRev(_)
+                | Command::UnwatchRev(_)
+                | Command::Select(_)
+                | Command::NamespaceUse(_)
+                | Command::NamespaceInfo(_)
+        )
+    }
+
+    /// The error to reply with if the command writes and the keyspace is over its memory limit
+    /// with nothing to evict.
+    pub(crate) fn refused(&self, db: &Db) -> Option<Message> {
+        if self.is_read_only() || !db.limits.refuses_writes(db.used_memory()) {
+            return None;
+        }
+        Some(Message::Err(
+            "OOM command not allowed when used memory > 'maxmemory'".to_string(),
+        ))
+    }
+
+    /// Performs one of a batch of commands, as in MULTI or TXN, refusing writes and evicting keys
+    /// as [`Command::perform`] does. The caller must hold the database's exclusive lock.
+    pub(crate) fn execute_in_batch(self, db: Arc<Db>) -> Message {
+        if let Some(refused) = self.refused(&db) {
+            return refused;
+        }
+        let message = self
+            .execute(db.clone())
+            .unwrap_or_else(|err| Message::Err(err.to_string()));
+        db.evict();
+        message
+    }
+
+    /// Performs the command without taking the database's lock, which the caller must hold.
+    pub(crate) fn execute(self, db: Arc<Db>) -> Result<Message> {
+        match self {
+            Command::Get(get) => get.perform(db),
+            Command::Set(set) => set.perform(db),
+            Command::Incr(incr) => incr.perform(db),
+            Command::Del(del) => del.perform(db),
+            Command::JsonSet(json_set) => json_set.perform(db),
+            Command::JsonGet(json_get) => json_get.perform(db),
+            Command::JsonDel(json_del) => json_del.perform(db),
+            Command::JsonNumIncrBy(json_numincrby) => json_numincrby.perform(db),
+            Command::JsonArrAppend(json_arrappend) => json_arrappend.perform(db),
+            Command::JsonType(json_type) => json_type.perform(db),
+            Command::TsCreate(ts_create) => ts_create.perform(db),
+            Command::TsAdd(ts_add) => ts_add.perform(db),
+            Command::TsMAdd(ts_madd) => ts_madd.perform(db),
+            Command::TsRange(ts_range) => ts_range.perform(db),
+            Command::TsCreateRule(ts_createrule) => ts_createrule.perform(db),
+            Command::TsDeleteRule(ts_deleterule) => ts_deleterule.perform(db),
+            Command::VCreate(vcreate) => vcreate.perform(db),
+            Command::VDrop(vdrop) => vdrop.perform(db),
+            Command::VAdd(vadd) => vadd.perform(db),
+            Command::VSearch(vsearch) => vsearch.perform(db),
+            Command::FtCreate(ft_create) => ft_create.perform(db),
+            Command::FtDrop(ft_drop) => ft_drop.perform(db),
+            Command::FtSearch(ft_search) => ft_search.perform(db),
+            Command::Type(key_type) => key_type.perform(db),
+            Command::Exists(exists) => exists.perform(db),
+            Command::Rename(rename) => rename.perform(db),
+            Command::RenameNx(renamenx) => renamenx.perform(db),
+            Command::Copy(copy) => copy.perform(db),
+            Command::Touch(touch) => touch.perform(db),
+            Command::Unlink(unlink) => unlink.perform(db),
+            Command::Keys(keys) => keys.perform(db),
+            Command::Scan(scan) => scan.perform(db),
+            Command::Range(range) => range.perform(db),
+            Command::RevRange(revrange) => revrange.perform(db),
+            Command::SetNx(setnx) => setnx.perform(db),
+            Command::GetSet(getset) => getset.perform(db),
+            Command::GetDel(getdel) => getdel.perform(db),
+            Command::Expire(expire) => expire.perform(db),
+            Command::PExpire(pexpire) => pexpire.perform(db),
+            Command::ExpireAt(expireat) => expireat.perform(db),
+            Command::PExpireAt(pexpireat) => pexpireat.perform(db),
+            Command::Ttl(ttl) => ttl.perform(db),
+            Command::PTtl(pttl) => pttl.perform(db),
+            Command::Persist(persist) => persist.perform(db),
+            Command::MGet(mget) => mget.perform(db),
+            Command::MSet(mset) => mset.perform(db),
+            Command::MSetNx(msetnx) => msetnx.perform(db),
+            Command::Append(append) => append.perform(db),
+            Command::StrLen(strlen) => strlen.perform(db),
+            Command::GetRange(getrange) => getrange.perform(db),
+            Command::SetRange(setrange) => setrange.perform(db),
+            // These change the state of a connection, so are handled by its session instead.
+            Command::Multi(_)
+            | Command::Exec(_)
+            | Command::Discard(_)
+            | Command::Watch(_)
+            | Command::Unwatch(_)
+            | Command::Subscribe(_)
+            | Command::Unsubscribe(_)
+            | Command::PSubscribe(_)
+            | Command::PUnsubscribe(_)
+            | Command::WatchRev(_)
+            | Command::UnwatchRev(_)
+            | Command::Select(_)
+            | Command::SwapDb(_)
+            | Command::Move(_)
+            | Command::NamespaceCreate(_)
+            | Command::NamespaceDrop(_)
+            | Command::NamespaceUse(_)
+            | Command::NamespaceInfo(_) => Ok(Message::Err(
+                "command is only available on a connection".to_string(),
+            )),
+            Command::Txn(txn) => txn.perform(db),
+            Command::Cas(cas) => cas.perform(db),
+            Command::Cad(cad) => cad.perform(db),
+            Command::GetVer(getver) => getver.perform(db),
+            Command::Publish(publish) => publish.perform(db),
+            Command::ConfigGet(config_get) => config_get.perform(db),
+            Command::ConfigSet(config_set) => config_set.perform(db),
+            Command::LPush(lpush) => lpush.perform(db),
+            Command::RPush(rpush) => rpush.perform(db),
+            Command::LPop(lpop) => lpop.perform(db),
+            Command::RPop(rpop) => rpop.perform(db),
+            Command::LLen(llen) => llen.perform(db),
+            Command::LRange(lrange) => lrange.perform(db),
+            Command::LMove(lmove) => lmove.perform(db),
+            Command::BLPop(blpop) => blpop.perform(db),
+            Command::BRPop(brpop) => brpop.perform(db),
+            Command::BLMove(blmove) => blmove.perform(db),
+            Command::QCreate(qcreate) => qcreate.perform(db),
+            Command::Enqueue(enqueue) => enqueue.perform(db),
+            Command::Dequeue(dequeue) => dequeue.perform(db),
+            Command::Ack(ack) => ack.perform(db),
+            Command::Nack(nack) => nack.perform(db),
+            Command::QStats(qstats) => qstats.perform(db),
+            Command::Schedule(schedule) => schedule.perform(db),
+            Command::Lock(lock) => lock.perform(db),
+            Command::Unlock(unlock) => unlock.perform(db),
+            Command::Extend(extend) => extend.perform(db),
+            Command::LeaseGrant(lease_grant) => lease_grant.perform(db),
+            Command::LeaseKeepAlive(lease_keepalive) => lease_keepalive.perform(db),
+            Command::LeaseRevoke(lease_revoke) => lease_revoke.perform(db),
+            Command::LeaseTtl(lease_ttl) => lease_ttl.perform(db),
+            Command::Throttle(throttle) => throttle.perform(db),
+            Command::FlushDb(flushdb) => flushdb.perform(db),
+            Command::DbSize(dbsize) => dbsize.perform(db),
         }
     }
 
@@ -90,10 +1025,528 @@ impl Command {
                 del.write(buf).await?;
                 Ok(())
             }
+            Command::JsonSet(json_set) => {
+                buf.write_u8(Variant::JsonSet as u8).await?;
+                json_set.write(buf).await?;
+                Ok(())
+            }
+            Command::JsonGet(json_get) => {
+                buf.write_u8(Variant::JsonGet as u8).await?;
+                json_get.write(buf).await?;
+                Ok(())
+            }
+            Command::JsonDel(json_del) => {
+                buf.write_u8(Variant::JsonDel as u8).await?;
+                json_del.write(buf).await?;
+                Ok(())
+            }
+            Command::JsonNumIncrBy(json_numincrby) => {
+                buf.write_u8(Variant::JsonNumIncrBy as u8).await?;
+                json_numincrby.write(buf).await?;
+                Ok(())
+            }
+            Command::JsonArrAppend(json_arrappend) => {
+                buf.write_u8(Variant::JsonArrAppend as u8).await?;
+                json_arrappend.write(buf).await?;
+                Ok(())
+            }
+            Command::JsonType(json_type) => {
+                buf.write_u8(Variant::JsonType as u8).await?;
+                json_type.write(buf).await?;
+                Ok(())
+            }
+            Command::TsCreate(ts_create) => {
+                buf.write_u8(Variant::TsCreate as u8).await?;
+                ts_create.write(buf).await?;
+                Ok(())
+            }
+            Command::TsAdd(ts_add) => {
+                buf.write_u8(Variant::TsAdd as u8).await?;
+                ts_add.write(buf).await?;
+                Ok(())
+            }
+            Command::TsMAdd(ts_madd) => {
+                buf.write_u8(Variant::TsMAdd as u8).await?;
+                ts_madd.write(buf).await?;
+                Ok(())
+            }
+            Command::TsRange(ts_range) => {
+                buf.write_u8(Variant::TsRange as u8).await?;
+                ts_range.write(buf).await?;
+                Ok(())
+            }
+            Command::TsCreateRule(ts_createrule) => {
+                buf.write_u8(Variant::TsCreateRule as u8).await?;
+                ts_createrule.write(buf).await?;
+                Ok(())
+            }
+            Command::TsDeleteRule(ts_deleterule) => {
+                buf.write_u8(Variant::TsDeleteRule as u8).await?;
+                ts_deleterule.write(buf).await?;
+                Ok(())
+            }
+            Command::VCreate(vcreate) => {
+                buf.write_u8(Variant::VCreate as u8).await?;
+                vcreate.write(buf).await?;
+                Ok(())
+            }
+            Command::VDrop(vdrop) => {
+                buf.write_u8(Variant::VDrop as u8).await?;
+                vdrop.write(buf).await?;
+                Ok(())
+            }
+            Command::VAdd(vadd) => {
+                buf.write_u8(Variant::VAdd as u8).await?;
+                vadd.write(buf).await?;
+                Ok(())
+            }
+            Command::VSearch(vsearch) => {
+                buf.write_u8(Variant::VSearch as u8).await?;
+                vsearch.write(buf).await?;
+                Ok(())
+            }
+            Command::FtCreate(ft_create) => {
+                buf.write_u8(Variant::FtCreate as u8).await?;
+                ft_create.write(buf).await?;
+                Ok(())
+            }
+            Command::FtDrop(ft_drop) => {
+                buf.write_u8(Variant::FtDrop as u8).await?;
+                ft_drop.write(buf).await?;
+                Ok(())
+            }
+            Command::FtSearch(ft_search) => {
+                buf.write_u8(Variant::FtSearch as u8).await?;
+                ft_search.write(buf).await?;
+                Ok(())
+            }
+            Command::Type(key_type) => {
+                buf.write_u8(Variant::Type as u8).await?;
+                key_type.write(buf).await?;
+                Ok(())
+            }
+            Command::Exists(exists) => {
+                buf.write_u8(Variant::Exists as u8).await?;
+                exists.write(buf).await?;
+                Ok(())
+            }
+            Command::Rename(rename) => {
+                buf.write_u8(Variant::Rename as u8).await?;
+                rename.write(buf).await?;
+                Ok(())
+            }
+            Command::RenameNx(renamenx) => {
+                buf.write_u8(Variant::RenameNx as u8).await?;
+                renamenx.write(buf).await?;
+                Ok(())
+            }
+            Command::Copy(copy) => {
+                buf.write_u8(Variant::Copy as u8).await?;
+                copy.write(buf).await?;
+                Ok(())
+            }
+            Command::Touch(touch) => {
+                buf.write_u8(Variant::Touch as u8).await?;
+                touch.write(buf).await?;
+                Ok(())
+            }
+            Command::Unlink(unlink) => {
+                buf.write_u8(Variant::Unlink as u8).await?;
+                unlink.write(buf).await?;
+                Ok(())
+            }
+            Command::Keys(keys) => {
+                buf.write_u8(Variant::Keys as u8).await?;
+                keys.write(buf).await?;
+                Ok(())
+            }
+            Command::Scan(scan) => {
+                buf.write_u8(Variant::Scan as u8).await?;
+                scan.write(buf).await?;
+                Ok(())
+            }
+            Command::Range(range) => {
+                buf.write_u8(Variant::Range as u8).await?;
+                range.write(buf).await?;
+                Ok(())
+            }
+            Command::RevRange(revrange) => {
+                buf.write_u8(Variant::RevRange as u8).await?;
+                revrange.write(buf).await?;
+                Ok(())
+            }
+            Command::SetNx(setnx) => {
+                buf.write_u8(Variant::SetNx as u8).await?;
+                setnx.write(buf).await?;
+                Ok(())
+            }
+            Command::GetSet(getset) => {
+                buf.write_u8(Variant::GetSet as u8).await?;
+                getset.write(buf).await?;
+                Ok(())
+            }
+            Command::GetDel(getdel) => {
+                buf.write_u8(Variant::GetDel as u8).await?;
+                getdel.write(buf).await?;
+                Ok(())
+            }
+            Command::Expire(expire) => {
+                buf.write_u8(Variant::Expire as u8).await?;
+                expire.write(buf).await?;
+                Ok(())
+            }
+            Command::PExpire(pexpire) => {
+                buf.write_u8(Variant::PExpire as u8).await?;
+                pexpire.write(buf).await?;
+                Ok(())
+            }
+            Command::ExpireAt(expireat) => {
+                buf.write_u8(Variant::ExpireAt as u8).await?;
+                expireat.write(buf).await?;
+                Ok(())
+            }
+            Command::PExpireAt(pexpireat) => {
+                buf.write_u8(Variant::PExpireAt as u8).await?;
+                pexpireat.write(buf).await?;
+                Ok(())
+            }
+            Command::Ttl(ttl) => {
+                buf.write_u8(Variant::Ttl as u8).await?;
+                ttl.write(buf).await?;
+                Ok(())
+            }
+            Command::PTtl(pttl) => {
+                buf.write_u8(Variant::PTtl as u8).await?;
+                pttl.write(buf).await?;
+                Ok(())
+            }
+            Command::Persist(persist) => {
+                buf.write_u8(Variant::Persist as u8).await?;
+                persist.write(buf).await?;
+                Ok(())
+            }
+            Command::MGet(mget) => {
+                buf.write_u8(Variant::MGet as u8).await?;
+                mget.write(buf).await?;
+                Ok(())
+            }
+            Command::MSet(mset) => {
+                buf.write_u8(Variant::MSet as u8).await?;
+                mset.write(buf).await?;
+                Ok(())
+            }
+            Command::MSetNx(msetnx) => {
+                buf.write_u8(Variant::MSetNx as u8).await?;
+                msetnx.write(buf).await?;
+                Ok(())
+            }
+            Command::Append(append) => {
+                buf.write_u8(Variant::Append as u8).await?;
+                append.write(buf).await?;
+                Ok(())
+            }
+            Command::StrLen(strlen) => {
+                buf.write_u8(Variant::StrLen as u8).await?;
+                strlen.write(buf).await?;
+                Ok(())
+            }
+            Command::GetRange(getrange) => {
+                buf.write_u8(Variant::GetRange as u8).await?;
+                getrange.write(buf).await?;
+                Ok(())
+            }
+            Command::SetRange(setrange) => {
+                buf.write_u8(Variant::SetRange as u8).await?;
+                setrange.write(buf).await?;
+                Ok(())
+            }
+            Command::Multi(multi) => {
+                buf.write_u8(Variant::Multi as u8).await?;
+                multi.write(buf).await?;
+                Ok(())
+            }
+            Command::Exec(exec) => {
+                buf.write_u8(Variant::Exec as u8).await?;
+                exec.write(buf).await?;
+                Ok(())
+            }
+            Command::Discard(discard) => {
+                buf.write_u8(Variant::Discard as u8).await?;
+                discard.write(buf).await?;
+                Ok(())
+            }
+            Command::Watch(watch) => {
+                buf.write_u8(Variant::Watch as u8).await?;
+                watch.write(buf).await?;
+                Ok(())
+            }
+            Command::Unwatch(unwatch) => {
+                buf.write_u8(Variant::Unwatch as u8).await?;
+                unwatch.write(buf).await?;
+                Ok(())
+            }
+            Command::Txn(txn) => {
+                buf.write_u8(Variant::Txn as u8).await?;
+                txn.write(buf).await?;
+                Ok(())
+            }
+            Command::Cas(cas) => {
+                buf.write_u8(Variant::Cas as u8).await?;
+                cas.write(buf).await?;
+                Ok(())
+            }
+            Command::Cad(cad) => {
+                buf.write_u8(Variant::Cad as u8).await?;
+                cad.write(buf).await?;
+                Ok(())
+            }
+            Command::GetVer(getver) => {
+                buf.write_u8(Variant::GetVer as u8).await?;
+                getver.write(buf).await?;
+                Ok(())
+            }
+            Command::Subscribe(subscribe) => {
+                buf.write_u8(Variant::Subscribe as u8).await?;
+                subscribe.write(buf).await?;
+                Ok(())
+            }
+            Command::Unsubscribe(unsubscribe) => {
+                buf.write_u8(Variant::Unsubscribe as u8).await?;
+                unsubscribe.write(buf).await?;
+                Ok(())
+            }
+            Command::PSubscribe(psubscribe) => {
+                buf.write_u8(Variant::PSubscribe as u8).await?;
+                psubscribe.write(buf).await?;
+                Ok(())
+            }
+            Command::PUnsubscribe(punsubscribe) => {
+                buf.write_u8(Variant::PUnsubscribe as u8).await?;
+                punsubscribe.write(buf).await?;
+                Ok(())
+            }
+            Command::Publish(publish) => {
+                buf.write_u8(Variant::Publish as u8).await?;
+                publish.write(buf).await?;
+                Ok(())
+            }
+            Command::ConfigGet(config_get) => {
+                buf.write_u8(Variant::ConfigGet as u8).await?;
+                config_get.write(buf).await?;
+                Ok(())
+            }
+            Command::ConfigSet(config_set) => {
+                buf.write_u8(Variant::ConfigSet as u8).await?;
+                config_set.write(buf).await?;
+                Ok(())
+            }
+            Command::WatchRev(watchrev) => {
+                buf.write_u8(Variant::WatchRev as u8).await?;
+                watchrev.write(buf).await?;
+                Ok(())
+            }
+            Command::UnwatchRev(unwatchrev) => {
+                buf.write_u8(Variant::UnwatchRev as u8).await?;
+                unwatchrev.write(buf).await?;
+                Ok(())
+            }
+            Command::LPush(lpush) => {
+                buf.write_u8(Variant::LPush as u8).await?;
+                lpush.write(buf).await?;
+                Ok(())
+            }
+            Command::RPush(rpush) => {
+                buf.write_u8(Variant::RPush as u8).await?;
+                rpush.write(buf).await?;
+                Ok(())
+            }
+            Command::LPop(lpop) => {
+                buf.write_u8(Variant::LPop as u8).await?;
+                lpop.write(buf).await?;
+                Ok(())
+            }
+            Command::RPop(rpop) => {
+                buf.write_u8(Variant::RPop as u8).await?;
+                rpop.write(buf).await?;
+                Ok(())
+            }
+            Command::LLen(llen) => {
+                buf.write_u8(Variant::LLen as u8).await?;
+                llen.write(buf).await?;
+                Ok(())
+            }
+            Command::LRange(lrange) => {
+                buf.write_u8(Variant::LRange as u8).await?;
+                lrange.write(buf).await?;
+                Ok(())
+            }
+            Command::LMove(lmove) => {
+                buf.write_u8(Variant::LMove as u8).await?;
+                lmove.write(buf).await?;
+                Ok(())
+            }
+            Command::BLPop(blpop) => {
+                buf.write_u8(Variant::BLPop as u8).await?;
+                blpop.write(buf).await?;
+                Ok(())
+            }
+            Command::BRPop(brpop) => {
+                buf.write_u8(Variant::BRPop as u8).await?;
+                brpop.write(buf).await?;
+                Ok(())
+            }
+            Command::BLMove(blmove) => {
+                buf.write_u8(Variant::BLMove as u8).await?;
+                blmove.write(buf).await?;
+                Ok(())
+            }
+            Command::QCreate(qcreate) => {
+                buf.write_u8(Variant::QCreate as u8).await?;
+                qcreate.write(buf).await?;
+                Ok(())
+            }
+            Command::Enqueue(enqueue) => {
+                buf.write_u8(Variant::Enqueue as u8).await?;
+                enqueue.write(buf).await?;
+                Ok(())
+            }
+            Command::Dequeue(dequeue) => {
+                buf.write_u8(Variant::Dequeue as u8).await?;
+                dequeue.write(buf).await?;
+                Ok(())
+            }
+            Command::Ack(ack) => {
+                buf.write_u8(Variant::Ack as u8).await?;
+                ack.write(buf).await?;
+                Ok(())
+            }
+            Command::Nack(nack) => {
+                buf.write_u8(Variant::Nack as u8).await?;
+                nack.write(buf).await?;
+                Ok(())
+            }
+            Command::QStats(qstats) => {
+                buf.write_u8(Variant::QStats as u8).await?;
+                qstats.write(buf).await?;
+                Ok(())
+            }
+            Command::Schedule(schedule) => {
+                buf.write_u8(Variant::Schedule as u8).await?;
+                schedule.write(buf).await?;
+                Ok(())
+            }
+            Command::Lock(lock) => {
+                buf.write_u8(Variant::Lock as u8).await?;
+                lock.write(buf).await?;
+                Ok(())
+            }
+            Command::Unlock(unlock) => {
+                buf.write_u8(Variant::Unlock as u8).await?;
+                unlock.write(buf).await?;
+                Ok(())
+            }
+            Command::Extend(extend) => {
+                buf.write_u8(Variant::Extend as u8).await?;
+                extend.write(buf).await?;
+                Ok(())
+            }
+            Command::LeaseGrant(lease_grant) => {
+                buf.write_u8(Variant::LeaseGrant as u8).await?;
+                lease_grant.write(buf).await?;
+                Ok(())
+            }
+            Command::LeaseKeepAlive(lease_keepalive) => {
+                buf.write_u8(Variant::LeaseKeepAlive as u8).await?;
+                lease_keepalive.write(buf).await?;
+                Ok(())
+            }
+            Command::LeaseRevoke(lease_revoke) => {
+                buf.write_u8(Variant::LeaseRevoke as u8).await?;
+                lease_revoke.write(buf).await?;
+                Ok(())
+            }
+            Command::LeaseTtl(lease_ttl) => {
+                buf.write_u8(Variant::LeaseTtl as u8).await?;
+                lease_ttl.write(buf).await?;
+                Ok(())
+            }
+            Command::Throttle(throttle) => {
+                buf.write_u8(Variant::Throttle as u8).await?;
+                throttle.write(buf).await?;
+                Ok(())
+            }
+            Command::Select(select) => {
+                buf.write_u8(Variant::Select as u8).await?;
+                select.write(buf).await?;
+                Ok(())
+            }
+            Command::SwapDb(swapdb) => {
+                buf.write_u8(Variant::SwapDb as u8).await?;
+                swapdb.write(buf).await?;
+                Ok(())
+            }
+            Command::Move(key_move) => {
+                buf.write_u8(Variant::Move as u8).await?;
+                key_move.write(buf).await?;
+                Ok(())
+            }
+            Command::FlushDb(flushdb) => {
+                buf.write_u8(Variant::FlushDb as u8).await?;
+                flushdb.write(buf).await?;
+                Ok(())
+            }
+            Command::DbSize(dbsize) => {
+                buf.write_u8(Variant::DbSize as u8).await?;
+                dbsize.write(buf).await?;
+                Ok(())
+            }
+            Command::NamespaceCreate(namespace_create) => {
+                buf.write_u8(Variant::NamespaceCreate as u8).await?;
+                namespace_create.write(buf).await?;
+                Ok(())
+            }
+            Command::NamespaceDrop(namespace_drop) => {
+                buf.write_u8(Variant::NamespaceDrop as u8).await?;
+                namespace_drop.write(buf).await?;
+                Ok(())
+            }
+            Command::NamespaceUse(namespace_use) => {
+                buf.write_u8(Variant::NamespaceUse as u8).await?;
+                namespace_use.write(buf).await?;
+                Ok(())
+            }
+            Command::NamespaceInfo(namespace_info) => {
+                buf.write_u8(Variant::NamespaceInfo as u8).await?;
+                namespace_info.write(buf).await?;
+                Ok(())
+            }
         }
     }
 }
 
+pub async fn read_number<T: FromStr>(src: &mut Cursor<&[u8]>) -> Result<T> {
+    let arg = message::read_string(src).await?;
+    match arg.parse() {
+        Ok(n) => Ok(n),
+        Err(_) => Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+    }
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Writes a command's argument count, which must fit in a byte.
+pub async fn write_count<W: AsyncWriteExt + Unpin>(buf: &mut W, count: usize) -> crate::Result<()> {
+    let Ok(count) = u8::try_from(count) else {
+        return Err(crate::Error::WriteMessage(message::Error::TooManyArguments));
+    };
+    buf.write_u8(count).await?;
+    Ok(())
+}
+
 pub async fn read_count(src: &mut Cursor<&[u8]>) -> Result<u8> {
     match src.read_u8().await {
         Ok(n) => Ok(n),
@@ -0,0 +1,62 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::queue::Queue,
+};
+
+/// Deletes a received message from a queue. Replies with 1 if it was deleted, or 0 if the
+/// receipt is unknown, including when the message has since been received again.
+#[derive(Debug)]
+pub struct Ack {
+    pub key: String,
+    pub receipt: String,
+}
+
+impl Ack {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        update(&db, self.key, |queue| queue.ack(&self.receipt))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Ack> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let receipt = message::read_string(src).await?;
+        Ok(Ack { key, receipt })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.receipt).await?;
+        Ok(())
+    }
+}
+
+/// Applies `f` to the queue at `key`, storing it and replying with 1 if `f` returns true.
+pub(crate) fn update(
+    db: &Db,
+    key: String,
+    f: impl FnOnce(&mut Queue) -> bool,
+) -> crate::Result<Message> {
+    db.update(key, |slot| {
+        let Some(val) = slot.get() else {
+            return Ok(Message::Int(0));
+        };
+        let Some(mut queue) = Queue::decode(val)? else {
+            return Ok(Message::Err("value is not a queue".to_string()));
+        };
+        if !f(&mut queue) {
+            return Ok(Message::Int(0));
+        }
+        slot.insert(queue.encode());
+        Ok(Message::Int(1))
+    })
+}
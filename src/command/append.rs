@@ -0,0 +1,69 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    db::Slot,
+    message,
+    pubsub::Event,
+    value::Value,
+};
+
+#[derive(Debug)]
+pub struct Append {
+    pub key: String,
+    pub value: String,
+}
+
+impl Append {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        db.update(self.key, |slot| {
+            if let Some(err) = ensure_string(slot)? {
+                return Ok(err);
+            }
+            let Some(val) = slot.get_mut() else {
+                let val = Value::String(&self.value).into_vec();
+                let len = val.len() - 1;
+                slot.insert(val);
+                slot.notify(Event::Set);
+                return Ok(Message::Int(len as i64));
+            };
+            val.extend_from_slice(self.value.as_bytes());
+            let len = val.len() - 1;
+            slot.notify(Event::Set);
+            Ok(Message::Int(len as i64))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Append> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let value = message::read_string(src).await?;
+        Ok(Append { key, value })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.value).await?;
+        Ok(())
+    }
+}
+
+/// Makes sure an occupied slot holds a string, so its bytes can be edited in place. Integers
+/// are converted to their decimal representation, and anything else is an error.
+pub(crate) fn ensure_string(slot: &mut Slot) -> crate::Result<Option<Message>> {
+    match slot.get().map(|val| Value::parse(val)).transpose()? {
+        None | Some(Value::String(_)) => Ok(None),
+        Some(Value::Int(int)) => {
+            slot.insert(Value::String(&int.to_string()).into_vec());
+            Ok(None)
+        }
+        Some(_) => Ok(Some(Message::Err("value is not a string".to_string()))),
+    }
+}
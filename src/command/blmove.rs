@@ -0,0 +1,65 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{
+        self, Error, blpop,
+        lmove::{self, End},
+    },
+    message,
+};
+
+/// Like `LMOVE`, but waits like `BLPOP` for the source to be pushed to if it's empty.
+#[derive(Debug)]
+pub struct BLMove {
+    pub source: String,
+    pub destination: String,
+    pub from: End,
+    pub to: End,
+    pub timeout: f64,
+}
+
+impl BLMove {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(self.attempt(&db)?.unwrap_or(Message::Null))
+    }
+
+    /// Moves an element if there is one, without waiting.
+    pub(crate) fn attempt(&self, db: &Db) -> crate::Result<Option<Message>> {
+        match lmove::move_element(db, &self.source, &self.destination, self.from, self.to)? {
+            Message::Null => Ok(None),
+            message => Ok(Some(message)),
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<BLMove> {
+        let count = command::read_count(src).await?;
+        if count != 5 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let source = message::read_string(src).await?;
+        let destination = message::read_string(src).await?;
+        let from = End::read(src).await?;
+        let to = End::read(src).await?;
+        let timeout = blpop::read_timeout(src).await?;
+        Ok(BLMove {
+            source,
+            destination,
+            from,
+            to,
+            timeout,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(5).await?;
+        message::write_string(buf, &self.source).await?;
+        message::write_string(buf, &self.destination).await?;
+        message::write_string(buf, self.from.name()).await?;
+        message::write_string(buf, self.to.name()).await?;
+        message::write_string(buf, &self.timeout.to_string()).await?;
+        Ok(())
+    }
+}
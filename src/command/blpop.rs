@@ -0,0 +1,94 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, lmove::End, lpop},
+    message,
+};
+
+/// Pops the head of the first non-empty list among the keys, replying with the key and the
+/// element.
+///
+/// On a connection, waits for up to `timeout` seconds (or forever, if zero) for an element to
+/// be pushed if every list is empty, before replying with null. Inside a transaction it never
+/// waits.
+#[derive(Debug)]
+pub struct BLPop {
+    pub keys: Vec<String>,
+    pub timeout: f64,
+}
+
+impl BLPop {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(self.attempt(&db)?.unwrap_or(Message::Null))
+    }
+
+    /// Pops an element if there is one, without waiting.
+    pub(crate) fn attempt(&self, db: &Db) -> crate::Result<Option<Message>> {
+        pop_first(db, &self.keys, End::Left)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<BLPop> {
+        let (keys, timeout) = read_keys(src).await?;
+        Ok(BLPop { keys, timeout })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        write_keys(buf, &self.keys, self.timeout).await
+    }
+}
+
+/// Pops from the first of the keys holding a non-empty list, returning `None` if they're all
+/// empty.
+pub(crate) fn pop_first(db: &Db, keys: &[String], end: End) -> crate::Result<Option<Message>> {
+    for key in keys {
+        match lpop::pop(db, key.clone(), end)? {
+            Message::Null => continue,
+            Message::Text(element) => {
+                return Ok(Some(Message::Array(vec![
+                    Message::Text(key.clone()),
+                    Message::Text(element),
+                ])));
+            }
+            message => return Ok(Some(message)),
+        }
+    }
+    Ok(None)
+}
+
+/// Reads a timeout in seconds, which can be fractional.
+pub(crate) async fn read_timeout(src: &mut Cursor<&[u8]>) -> crate::Result<f64> {
+    let timeout: f64 = command::read_number(src).await?;
+    if !timeout.is_finite() || timeout < 0.0 {
+        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+    }
+    Ok(timeout)
+}
+
+pub(crate) async fn read_keys(src: &mut Cursor<&[u8]>) -> crate::Result<(Vec<String>, f64)> {
+    let count = command::read_count(src).await?;
+    if count < 2 {
+        return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+    }
+    let mut keys = Vec::with_capacity(count as usize - 1);
+    for _ in 1..count {
+        keys.push(message::read_string(src).await?);
+    }
+    let timeout = read_timeout(src).await?;
+    Ok((keys, timeout))
+}
+
+pub(crate) async fn write_keys<W: AsyncWriteExt + Unpin>(
+    buf: &mut W,
+    keys: &[String],
+    timeout: f64,
+) -> crate::Result<()> {
+    command::write_count(buf, keys.len() + 1).await?;
+    for key in keys {
+        message::write_string(buf, key).await?;
+    }
+    message::write_string(buf, &timeout.to_string()).await?;
+    Ok(())
+}
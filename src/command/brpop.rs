@@ -0,0 +1,35 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{blpop, lmove::End},
+};
+
+/// Like `BLPOP`, but pops the tail of the list.
+#[derive(Debug)]
+pub struct BRPop {
+    pub keys: Vec<String>,
+    pub timeout: f64,
+}
+
+impl BRPop {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(self.attempt(&db)?.unwrap_or(Message::Null))
+    }
+
+    /// Pops an element if there is one, without waiting.
+    pub(crate) fn attempt(&self, db: &Db) -> crate::Result<Option<Message>> {
+        blpop::pop_first(db, &self.keys, End::Right)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<BRPop> {
+        let (keys, timeout) = blpop::read_keys(src).await?;
+        Ok(BRPop { keys, timeout })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        blpop::write_keys(buf, &self.keys, self.timeout).await
+    }
+}
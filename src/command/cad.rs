@@ -0,0 +1,47 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, cas::Expected},
+    message,
+    pubsub::Event,
+};
+
+/// Removes a key only if it holds what's expected.
+#[derive(Debug)]
+pub struct Cad {
+    pub key: String,
+    pub expected: Expected,
+}
+
+impl Cad {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        db.update(self.key, |slot| {
+            if !slot.is_occupied() || !self.expected.matches(slot) {
+                return Ok(Message::Int(0));
+            }
+            slot.remove();
+            slot.notify(Event::Del);
+            Ok(Message::Int(1))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Cad> {
+        let count = command::read_count(src).await?;
+        if count != 2 && count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let expected = Expected::read(src, count - 1).await?;
+        Ok(Cad { key, expected })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1 + self.expected.count()).await?;
+        message::write_string(buf, &self.key).await?;
+        self.expected.write(buf).await?;
+        Ok(())
+    }
+}
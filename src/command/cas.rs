@@ -0,0 +1,106 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, set},
+    db::Slot,
+    message,
+    pubsub::Event,
+};
+
+/// What a key must hold for a compare-and-swap or compare-and-delete to go ahead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    /// The key holds exactly this value.
+    Value(Vec<u8>),
+    /// The key has this version, where zero means the key doesn't exist.
+    Version(u64),
+}
+
+impl Expected {
+    pub(crate) fn matches(&self, slot: &Slot) -> bool {
+        match self {
+            Expected::Value(value) => slot.get() == Some(value),
+            Expected::Version(version) => slot.version() == *version,
+        }
+    }
+
+    /// Reads the expected value, or `VERSION` followed by the expected version.
+    pub(crate) async fn read(src: &mut Cursor<&[u8]>, count: u8) -> crate::Result<Expected> {
+        let arg = message::read_bytes(src).await?;
+        if count == 1 {
+            return Ok(Expected::Value(arg));
+        }
+        if !arg.eq_ignore_ascii_case(b"VERSION") {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        Ok(Expected::Version(command::read_number(src).await?))
+    }
+
+    /// The number of arguments the expectation is written as.
+    pub(crate) fn count(&self) -> u8 {
+        match self {
+            Expected::Value(_) => 1,
+            Expected::Version(_) => 2,
+        }
+    }
+
+    pub(crate) async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        match self {
+            Expected::Value(value) => message::write_bytes(buf, value).await,
+            Expected::Version(version) => {
+                message::write_string(buf, "VERSION").await?;
+                message::write_string(buf, &version.to_string()).await
+            }
+        }
+    }
+}
+
+/// Replaces a key's value, keeping its expiry, only if it holds what's expected.
+#[derive(Debug)]
+pub struct Cas {
+    pub key: String,
+    pub expected: Expected,
+    pub value: Vec<u8>,
+}
+
+impl Cas {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if let Some(err) = set::validate(&self.value) {
+            return Ok(err);
+        }
+        db.update(self.key, |slot| {
+            if !self.expected.matches(slot) {
+                return Ok(Message::Int(0));
+            }
+            slot.insert(self.value);
+            slot.notify(Event::Set);
+            Ok(Message::Int(1))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Cas> {
+        let count = command::read_count(src).await?;
+        if count != 3 && count != 4 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let expected = Expected::read(src, count - 2).await?;
+        let value = message::read_bytes(src).await?;
+        Ok(Cas {
+            key,
+            expected,
+            value,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2 + self.expected.count()).await?;
+        message::write_string(buf, &self.key).await?;
+        self.expected.write(buf).await?;
+        message::write_bytes(buf, &self.value).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    pubsub::Notify,
+};
+
+/// Reads one of the settings of the database or namespace in use, which can be changed with
+/// `CONFIG.SET`.
+#[derive(Debug)]
+pub struct ConfigGet {
+    pub parameter: String,
+}
+
+impl ConfigGet {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        match self.parameter.to_ascii_lowercase().as_str() {
+            "notify-keyspace-events" => {
+                Ok(Message::Text(Notify::format(db.channels.notify_flags())))
+            }
+            "history-size" => Ok(Message::Int(db.history.capacity() as i64)),
+            _ => Ok(Message::Err(format!(
+                "unknown configuration parameter '{}'",
+                self.parameter
+            ))),
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<ConfigGet> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let parameter = message::read_string(src).await?;
+        Ok(ConfigGet { parameter })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.parameter).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    pubsub::Notify,
+};
+
+/// Changes one of the settings of the database or namespace in use, taking effect immediately.
+/// Other databases and namespaces keep their own.
+///
+/// `notify-keyspace-events` chooses which keyspace events are published, as a string of
+/// letters: `K` and `E` for the keyspace and keyevent channels, `g`, `$`, `x` and `e` for
+/// generic, string, expired and evicted events (or `A` for all of them), and `v` to include new
+/// values. An empty string turns them off.
+///
+/// `history-size` is how many changes are kept for `WATCHREV` to resume from.
+#[derive(Debug)]
+pub struct ConfigSet {
+    pub parameter: String,
+    pub value: String,
+}
+
+impl ConfigSet {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        match self.parameter.to_ascii_lowercase().as_str() {
+            "notify-keyspace-events" => match Notify::parse(&self.value) {
+                Some(flags) => {
+                    db.channels.set_notify_flags(flags);
+                    Ok(Message::Ok)
+                }
+                None => Ok(Message::Err(format!(
+                    "invalid notify-keyspace-events '{}'",
+                    self.value
+                ))),
+            },
+            "history-size" => match self.value.parse() {
+                Ok(capacity) => {
+                    db.history.set_capacity(capacity);
+                    Ok(Message::Ok)
+                }
+                Err(_) => Ok(Message::Err(format!(
+                    "invalid history-size '{}'",
+                    self.value
+                ))),
+            },
+            _ => Ok(Message::Err(format!(
+                "unknown configuration parameter '{}'",
+                self.parameter
+            ))),
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<ConfigSet> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let parameter = message::read_string(src).await?;
+        let value = message::read_string(src).await?;
+        Ok(ConfigSet { parameter, value })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.parameter).await?;
+        message::write_string(buf, &self.value).await?;
+        Ok(())
+    }
+}
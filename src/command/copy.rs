@@ -0,0 +1,72 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    pubsub::Event,
+};
+
+#[derive(Debug)]
+pub struct Copy {
+    pub key: String,
+    pub dest: String,
+    pub replace: bool,
+}
+
+impl Copy {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if self.key == self.dest {
+            return Ok(Message::Err(
+                "source and destination are the same".to_string(),
+            ));
+        }
+        if !self.replace && db.contains_key(&self.dest) {
+            return Ok(Message::Int(0));
+        }
+        // Clone the value before writing, as the destination may share the source's shard.
+        let copied = db.update(self.key, |slot| {
+            Some((slot.get()?.clone(), slot.expires_at()))
+        });
+        let Some((value, expires_at)) = copied else {
+            return Ok(Message::Int(0));
+        };
+        db.update(self.dest, |slot| {
+            slot.insert(value);
+            slot.set_expiry(expires_at);
+            slot.notify(Event::Set);
+        });
+        Ok(Message::Int(1))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Copy> {
+        let count = command::read_count(src).await?;
+        if count != 2 && count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let dest = message::read_string(src).await?;
+        let replace = if count == 3 {
+            let option = message::read_string(src).await?;
+            if !option.eq_ignore_ascii_case("REPLACE") {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+            true
+        } else {
+            false
+        };
+        Ok(Copy { key, dest, replace })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(if self.replace { 3 } else { 2 }).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.dest).await?;
+        if self.replace {
+            message::write_string(buf, "REPLACE").await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+};
+
+/// Replies with how many keys the connection's database holds.
+#[derive(Debug)]
+pub struct DbSize;
+
+impl DbSize {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(Message::Int(db.len() as i64))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<DbSize> {
+        let count = command::read_count(src).await?;
+        if count != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(DbSize)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(0).await?;
+        Ok(())
+    }
+}
@@ -1,39 +1,45 @@
 use std::{io::Cursor, sync::Arc};
 
-use dashmap::DashMap;
 use tokio::io::AsyncWriteExt;
 
 use crate::{
-    Message,
+    Db, Message,
     command::{self, Error},
     message,
 };
 
 #[derive(Debug)]
 pub struct Del {
-    pub key: String,
+    pub keys: Vec<String>,
 }
 
 impl Del {
-    pub fn perform(self, db: Arc<DashMap<String, Vec<u8>>>) -> crate::Result<Message> {
-        match db.remove(&self.key) {
-            Some(_) => Ok(Message::Ok),
-            None => Ok(Message::Null),
-        }
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let removed = self
+            .keys
+            .iter()
+            .filter(|key| db.remove(key).is_some())
+            .count();
+        Ok(Message::Int(removed as i64))
     }
 
     pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Del> {
         let count = command::read_count(src).await?;
-        if count != 1 {
+        if count == 0 {
             return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
         }
-        let key = message::read_string(src).await?;
-        Ok(Del { key })
+        let mut keys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            keys.push(message::read_string(src).await?);
+        }
+        Ok(Del { keys })
     }
 
     pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
-        buf.write_u8(1).await?;
-        message::write_string(buf, &self.key).await?;
+        command::write_count(buf, self.keys.len()).await?;
+        for key in &self.keys {
+            message::write_string(buf, key).await?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,108 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, enqueue},
+    message,
+    value::queue::Queue,
+};
+
+/// Receives the oldest visible message in a queue, hiding it from other receivers until it's
+/// acknowledged or its visibility timeout passes. Replies with the message's id, a receipt to
+/// acknowledge it with, its body and how many times it has been received, or null if there's
+/// nothing to receive.
+///
+/// Messages which have been received as many times as the queue allows are moved to its
+/// dead-letter queue instead of being received again.
+#[derive(Debug)]
+pub struct Dequeue {
+    pub key: String,
+    /// How long the message stays invisible, in milliseconds, overriding the queue's setting.
+    pub visibility: Option<u64>,
+}
+
+impl Dequeue {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let now = command::now_millis();
+        let dead_letter = match db.get(&self.key) {
+            Some(val) => match Queue::decode(&val)? {
+                Some(queue) => queue.dead_letter,
+                None => return Ok(Message::Err("value is not a queue".to_string())),
+            },
+            None => return Ok(Message::Null),
+        };
+        // Check before taking any messages out, so none are lost.
+        if let Some(dead_letter) = &dead_letter
+            && let Some(val) = db.get(dead_letter)
+            && Queue::decode(&val)?.is_none()
+        {
+            return Ok(Message::Err("dead-letter key is not a queue".to_string()));
+        }
+        let (message, exhausted) = db.update(self.key, |slot| -> crate::Result<_> {
+            let Some(mut queue) = slot
+                .get()
+                .map(|val| Queue::decode(val))
+                .transpose()?
+                .flatten()
+            else {
+                return Ok((Message::Null, Vec::new()));
+            };
+            let exhausted = queue.take_exhausted(now);
+            let visibility = self.visibility.unwrap_or(queue.visibility);
+            let message = match queue.receive(now, visibility) {
+                Some(received) => Message::Array(vec![
+                    Message::Int(received.id as i64),
+                    Message::Text(received.receipt),
+                    Message::Text(received.body.to_string()),
+                    Message::Int(received.receives as i64),
+                ]),
+                None if exhausted.is_empty() => return Ok((Message::Null, exhausted)),
+                None => Message::Null,
+            };
+            slot.insert(queue.encode());
+            Ok((message, exhausted))
+        })?;
+        if let Some(dead_letter) = dead_letter
+            && !exhausted.is_empty()
+        {
+            enqueue::enqueue(&db, dead_letter, exhausted, now)?;
+        }
+        Ok(message)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Dequeue> {
+        let count = command::read_count(src).await?;
+        if count != 1 && count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let visibility = if count == 3 {
+            let option = message::read_string(src).await?;
+            if !option.eq_ignore_ascii_case("VISIBILITY") {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+            Some(command::read_number(src).await?)
+        } else {
+            None
+        };
+        Ok(Dequeue { key, visibility })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        match self.visibility {
+            Some(visibility) => {
+                buf.write_u8(3).await?;
+                message::write_string(buf, &self.key).await?;
+                message::write_string(buf, "VISIBILITY").await?;
+                message::write_string(buf, &visibility.to_string()).await?;
+            }
+            None => {
+                buf.write_u8(1).await?;
+                message::write_string(buf, &self.key).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::command::{self, Error};
+
+/// Drops the commands queued since `MULTI`.
+#[derive(Debug)]
+pub struct Discard;
+
+impl Discard {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Discard> {
+        let count = command::read_count(src).await?;
+        if count != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(Discard)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(0).await?;
+        Ok(())
+    }
+}
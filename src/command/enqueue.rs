@@ -0,0 +1,95 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::queue::{DEFAULT_VISIBILITY, Queue},
+};
+
+/// Adds a message to a queue, creating it with the default settings if needed, and replies
+/// with the message's id.
+#[derive(Debug)]
+pub struct Enqueue {
+    pub key: String,
+    pub body: String,
+    /// How long the message stays invisible before it can first be received, in milliseconds.
+    pub delay: Option<u64>,
+}
+
+impl Enqueue {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let visible_at = command::now_millis().saturating_add(self.delay.unwrap_or(0));
+        enqueue(&db, self.key, vec![self.body], visible_at)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Enqueue> {
+        let count = command::read_count(src).await?;
+        if count != 2 && count != 4 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let body = message::read_string(src).await?;
+        let delay = if count == 4 {
+            Some(read_delay(src).await?)
+        } else {
+            None
+        };
+        Ok(Enqueue { key, body, delay })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(if self.delay.is_some() { 4 } else { 2 })
+            .await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.body).await?;
+        if let Some(delay) = self.delay {
+            write_delay(buf, delay).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds messages to the queue at `key`, replying with the last one's id.
+pub(crate) fn enqueue(
+    db: &Db,
+    key: String,
+    bodies: Vec<String>,
+    visible_at: u64,
+) -> crate::Result<Message> {
+    db.update(key, |slot| {
+        let mut queue = match slot.get() {
+            Some(val) => match Queue::decode(val)? {
+                Some(queue) => queue,
+                None => return Ok(Message::Err("value is not a queue".to_string())),
+            },
+            None => Queue::new(DEFAULT_VISIBILITY, 0, None),
+        };
+        let mut id = 0;
+        for body in bodies {
+            id = queue.enqueue(body, visible_at);
+        }
+        slot.insert(queue.encode());
+        Ok(Message::Int(id as i64))
+    })
+}
+
+/// Reads a `DELAY ms` option.
+pub(crate) async fn read_delay(src: &mut Cursor<&[u8]>) -> crate::Result<u64> {
+    let option = message::read_string(src).await?;
+    if !option.eq_ignore_ascii_case("DELAY") {
+        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+    }
+    command::read_number(src).await
+}
+
+pub(crate) async fn write_delay<W: AsyncWriteExt + Unpin>(
+    buf: &mut W,
+    delay: u64,
+) -> crate::Result<()> {
+    message::write_string(buf, "DELAY").await?;
+    message::write_string(buf, &delay.to_string()).await?;
+    Ok(())
+}
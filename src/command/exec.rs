@@ -0,0 +1,24 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::command::{self, Error};
+
+/// Runs the commands queued since `MULTI`, without any other commands running in between.
+#[derive(Debug)]
+pub struct Exec;
+
+impl Exec {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Exec> {
+        let count = command::read_count(src).await?;
+        if count != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(Exec)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(0).await?;
+        Ok(())
+    }
+}
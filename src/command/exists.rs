@@ -0,0 +1,42 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+#[derive(Debug)]
+pub struct Exists {
+    pub keys: Vec<String>,
+}
+
+impl Exists {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        // Repeated keys are counted each time they're given, as they are by Redis.
+        let existing = self.keys.iter().filter(|key| db.contains_key(key)).count();
+        Ok(Message::Int(existing as i64))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Exists> {
+        let count = command::read_count(src).await?;
+        if count == 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut keys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            keys.push(message::read_string(src).await?);
+        }
+        Ok(Exists { keys })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.keys.len()).await?;
+        for key in &self.keys {
+            message::write_string(buf, key).await?;
+        }
+        Ok(())
+    }
+}
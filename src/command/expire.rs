@@ -0,0 +1,63 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    pubsub::Event,
+};
+
+/// Sets a key to expire after a number of seconds.
+#[derive(Debug)]
+pub struct Expire {
+    pub key: String,
+    pub seconds: u64,
+}
+
+impl Expire {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let now = command::now_millis();
+        Ok(expire_at(
+            &db,
+            self.key,
+            now.saturating_add(self.seconds.saturating_mul(1000)),
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Expire> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let seconds = command::read_number(src).await?;
+        Ok(Expire { key, seconds })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.seconds.to_string()).await?;
+        Ok(())
+    }
+}
+
+/// Sets `key` to expire at `deadline`, in milliseconds since the unix epoch, removing it
+/// straight away if that has already passed.
+pub(crate) fn expire_at(db: &Db, key: String, deadline: u64) -> Message {
+    db.update(key, |slot| {
+        if !slot.is_occupied() {
+            return Message::Int(0);
+        }
+        if deadline <= command::now_millis() {
+            slot.remove();
+            slot.notify(Event::Del);
+        } else {
+            slot.set_expiry(Some(deadline));
+            slot.notify(Event::Expire);
+        }
+        Message::Int(1)
+    })
+}
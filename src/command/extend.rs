@@ -0,0 +1,50 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, lock},
+    message,
+};
+
+/// Renews the lease on the lock at `key` to `ttl` milliseconds from now, if `owner` still holds
+/// it. Replies with 1 if it did and 0 otherwise. The fencing token is unchanged.
+#[derive(Debug)]
+pub struct Extend {
+    pub key: String,
+    pub owner: String,
+    pub ttl: u64,
+}
+
+impl Extend {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let expires_at = command::now_millis().saturating_add(self.ttl);
+        Ok(db.update(self.key, |slot| match slot.get() {
+            Some(val) if lock::is_owner(val, &self.owner) => {
+                slot.set_expiry(Some(expires_at));
+                Message::Int(1)
+            }
+            _ => Message::Int(0),
+        }))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Extend> {
+        let count = command::read_count(src).await?;
+        if count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let owner = message::read_string(src).await?;
+        let ttl = lock::read_ttl(src).await?;
+        Ok(Extend { key, owner, ttl })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(3).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.owner).await?;
+        message::write_string(buf, &self.ttl.to_string()).await?;
+        Ok(())
+    }
+}
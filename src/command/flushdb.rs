@@ -0,0 +1,34 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+};
+
+/// Removes every key from the connection's database.
+#[derive(Debug)]
+pub struct FlushDb;
+
+impl FlushDb {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        for key in db.keys_with_prefix("") {
+            db.remove(&key);
+        }
+        Ok(Message::Ok)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<FlushDb> {
+        let count = command::read_count(src).await?;
+        if count != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(FlushDb)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(0).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use std::{
+    io::Cursor,
+    sync::{Arc, PoisonError},
+};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    index::fulltext::TextIndex,
+    message,
+    value::Value,
+};
+
+#[derive(Debug)]
+pub struct FtCreate {
+    pub index: String,
+    /// Every string stored under a key starting with this prefix is indexed.
+    pub prefix: String,
+}
+
+impl FtCreate {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let index = TextIndex::new(self.prefix.clone());
+        let Some(index) = db.text.create(self.index, index) else {
+            return Ok(Message::Err("index already exists".to_string()));
+        };
+        db.backfill(&self.prefix, |key, val| {
+            if let Ok(Value::String(text)) = Value::parse(val) {
+                index
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .upsert(key, text);
+            }
+        });
+        Ok(Message::Ok)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<FtCreate> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let index = message::read_string(src).await?;
+        let prefix = message::read_string(src).await?;
+        Ok(FtCreate { index, prefix })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.index).await?;
+        message::write_string(buf, &self.prefix).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    io::Cursor,
+    sync::{Arc, PoisonError},
+};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    index::fulltext::Query,
+    message,
+};
+
+/// The number of results returned when a search doesn't specify a limit.
+const DEFAULT_LIMIT: usize = 10;
+
+#[derive(Debug)]
+pub struct FtSearch {
+    pub index: String,
+    pub query: String,
+    pub limit: Option<usize>,
+}
+
+impl FtSearch {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Ok(query) = Query::parse(&self.query) else {
+            return Ok(Message::Err("invalid query".to_string()));
+        };
+        let Some(index) = db.text.get(&self.index) else {
+            return Ok(Message::Err("no such index".to_string()));
+        };
+        let results = index
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .search(&query, self.limit.unwrap_or(DEFAULT_LIMIT));
+        Ok(Message::Array(
+            results
+                .into_iter()
+                .map(|(key, score)| Message::Array(vec![Message::Text(key), Message::Float(score)]))
+                .collect(),
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<FtSearch> {
+        let count = command::read_count(src).await?;
+        if count != 2 && count != 4 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let index = message::read_string(src).await?;
+        let query = message::read_string(src).await?;
+        let limit = if count == 4 {
+            let option = message::read_string(src).await?;
+            if !option.eq_ignore_ascii_case("LIMIT") {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+            Some(command::read_number(src).await?)
+        } else {
+            None
+        };
+        Ok(FtSearch {
+            index,
+            query,
+            limit,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(if self.limit.is_some() { 4 } else { 2 })
+            .await?;
+        message::write_string(buf, &self.index).await?;
+        message::write_string(buf, &self.query).await?;
+        if let Some(limit) = self.limit {
+            message::write_string(buf, "LIMIT").await?;
+            message::write_string(buf, &limit.to_string()).await?;
+        }
+        Ok(())
+    }
+}
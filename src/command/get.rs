@@ -1,13 +1,12 @@
 use std::{io::Cursor, sync::Arc};
 
-use dashmap::DashMap;
 use tokio::io::AsyncWriteExt;
 
 use crate::{
-    Message,
+    Db, Message,
     command::{self, Error},
     message,
-    value::Value,
+    value::{Value, vector},
 };
 
 #[derive(Debug)]
@@ -16,15 +15,9 @@ pub struct Get {
 }
 
 impl Get {
-    pub fn perform(self, db: Arc<DashMap<String, Vec<u8>>>) -> crate::Result<Message> {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
         match db.get(&self.key) {
-            Some(val) => {
-                let value = Value::parse(val.as_ref())?;
-                match value {
-                    Value::Int(int) => Ok(Message::Int(int)),
-                    Value::String(string) => Ok(Message::Text(string.to_string())),
-                }
-            }
+            Some(val) => to_message(&val),
             None => Ok(Message::Null),
         }
     }
@@ -44,3 +37,23 @@ impl Get {
         Ok(())
     }
 }
+
+/// Converts a stored value into the message sent back to clients reading it.
+pub(crate) fn to_message(val: &[u8]) -> crate::Result<Message> {
+    match Value::parse(val)? {
+        Value::Int(int) => Ok(Message::Int(int.into())),
+        Value::String(string) | Value::Json(string) => Ok(Message::Text(string.to_string())),
+        Value::TimeSeries(_) => Ok(Message::Err("value is a time series".to_string())),
+        Value::List(_) => Ok(Message::Err("value is a list".to_string())),
+        Value::Queue(_) => Ok(Message::Err("value is a queue".to_string())),
+        Value::Vector(_) => match vector::decode(val)? {
+            Some(vector) => Ok(Message::Array(
+                vector
+                    .into_iter()
+                    .map(|c| Message::Float(c.into()))
+                    .collect(),
+            )),
+            None => Ok(Message::Null),
+        },
+    }
+}
@@ -0,0 +1,47 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, get},
+    message,
+    pubsub::Event,
+};
+
+#[derive(Debug)]
+pub struct GetDel {
+    pub key: String,
+}
+
+impl GetDel {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        db.update(self.key, |slot| {
+            let Some(val) = slot.get() else {
+                return Ok(Message::Null);
+            };
+            let old = get::to_message(val)?;
+            if let Message::Err(_) = old {
+                return Ok(old);
+            }
+            slot.remove();
+            slot.notify(Event::Del);
+            Ok(old)
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<GetDel> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        Ok(GetDel { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
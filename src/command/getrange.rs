@@ -0,0 +1,65 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::Value,
+};
+
+/// Returns the bytes of a string between two offsets, both inclusive. Negative offsets count
+/// back from the end.
+#[derive(Debug)]
+pub struct GetRange {
+    pub key: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+impl GetRange {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Some(val) = db.get(&self.key) else {
+            return Ok(Message::Text(String::new()));
+        };
+        let int;
+        let bytes = match Value::parse(&val)? {
+            Value::String(string) => string.as_bytes(),
+            Value::Int(i) => {
+                int = i.to_string();
+                int.as_bytes()
+            }
+            _ => return Ok(Message::Err("value is not a string".to_string())),
+        };
+        let len = bytes.len() as i64;
+        let resolve = |offset: i64| if offset < 0 { len + offset } else { offset };
+        let start = resolve(self.start).max(0);
+        let end = resolve(self.end).min(len - 1);
+        if start > end {
+            return Ok(Message::Text(String::new()));
+        }
+        // The range may split a character, which can't be sent back as text.
+        let range = &bytes[start as usize..=end as usize];
+        Ok(Message::Text(String::from_utf8_lossy(range).into_owned()))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<GetRange> {
+        let count = command::read_count(src).await?;
+        if count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let start = command::read_number(src).await?;
+        let end = command::read_number(src).await?;
+        Ok(GetRange { key, start, end })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(3).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.start.to_string()).await?;
+        message::write_string(buf, &self.end.to_string()).await?;
+        Ok(())
+    }
+}
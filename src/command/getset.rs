@@ -0,0 +1,54 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, get, set},
+    message,
+    pubsub::Event,
+};
+
+#[derive(Debug)]
+pub struct GetSet {
+    pub key: String,
+    pub value: Vec<u8>,
+}
+
+impl GetSet {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if let Some(err) = set::validate(&self.value) {
+            return Ok(err);
+        }
+        db.update(self.key, |slot| {
+            let old = match slot.get() {
+                Some(val) => get::to_message(val)?,
+                None => Message::Null,
+            };
+            if let Message::Err(_) = old {
+                return Ok(old);
+            }
+            slot.insert(self.value);
+            slot.set_expiry(None);
+            slot.notify(Event::Set);
+            Ok(old)
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<GetSet> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let value = message::read_bytes(src).await?;
+        Ok(GetSet { key, value })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_bytes(buf, &self.value).await?;
+        Ok(())
+    }
+}
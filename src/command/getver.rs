@@ -0,0 +1,37 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+/// Returns a key's version, which increases every time it's written, or zero if it doesn't
+/// exist.
+#[derive(Debug)]
+pub struct GetVer {
+    pub key: String,
+}
+
+impl GetVer {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(Message::Int(db.version(&self.key) as i64))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<GetVer> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        Ok(GetVer { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
@@ -1,9 +1,8 @@
 use std::{io::Cursor, sync::Arc};
 
-use dashmap::DashMap;
 use tokio::io::AsyncWriteExt;
 
-use crate::{Message, command, message, value::Value};
+use crate::{Db, Message, command, message, pubsub::Event, value::Value};
 
 #[derive(Debug)]
 pub struct Incr {
@@ -11,20 +10,22 @@ pub struct Incr {
 }
 
 impl Incr {
-    pub fn perform(self, db: Arc<DashMap<String, Vec<u8>>>) -> crate::Result<Message> {
-        let e = db
-            .entry(self.key)
-            .and_modify(|e| {
-                if let Ok(Value::Int(int)) = Value::parse(&e) {
-                    Value::Int(int + 1).write(e);
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        db.update(self.key, |slot| {
+            let int = match slot.get().map(|val| Value::parse(val)) {
+                Some(Ok(Value::Int(int))) => int + 1,
+                Some(_) => return Ok(Message::Err("not a number".to_string())),
+                None => 1,
+            };
+            match slot.get_mut() {
+                Some(val) => Value::Int(int).write(val),
+                None => {
+                    slot.insert(Value::Int(int).into_vec());
                 }
-            })
-            .or_insert_with(|| Value::Int(1).into_vec());
-        if let Ok(Value::Int(int)) = Value::parse(&e) {
-            Ok(Message::Int(int))
-        } else {
-            Ok(Message::Err("not a number".to_string()))
-        }
+            }
+            slot.notify(Event::Incr);
+            Ok(Message::Int(int.into()))
+        })
     }
 
     pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Incr> {
@@ -0,0 +1,81 @@
+use std::{io::Cursor, sync::Arc};
+
+use serde_json::Value as Json;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::json::{self, Path},
+};
+
+#[derive(Debug)]
+pub struct JsonArrAppend {
+    pub key: String,
+    pub path: String,
+    pub values: Vec<String>,
+}
+
+impl JsonArrAppend {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Ok(path) = Path::parse(&self.path) else {
+            return Ok(Message::Err("invalid path".to_string()));
+        };
+        let mut values = Vec::with_capacity(self.values.len());
+        for value in &self.values {
+            match json::parse(value) {
+                Ok(value) => values.push(value),
+                Err(_) => return Ok(Message::Err("invalid json".to_string())),
+            }
+        }
+        db.update(self.key, |slot| {
+            let Some(val) = slot.get() else {
+                return Ok(Message::Null);
+            };
+            let Some(mut doc) = json::decode(val)? else {
+                return Ok(Message::Err("value is not json".to_string()));
+            };
+            let mut lengths = Vec::new();
+            let mut appended = 0;
+            for location in path.locate(&doc) {
+                match json::resolve_mut(&mut doc, &location) {
+                    Some(Json::Array(array)) => {
+                        array.extend(values.iter().cloned());
+                        appended += 1;
+                        lengths.push(Message::Int(array.len() as i64));
+                    }
+                    _ => lengths.push(Message::Null),
+                }
+            }
+            if appended > 0 {
+                slot.insert(json::encode(&doc));
+            }
+            Ok(Message::Array(lengths))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<JsonArrAppend> {
+        let count = command::read_count(src).await?;
+        if count < 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let path = message::read_string(src).await?;
+        let mut values = Vec::with_capacity(count as usize - 2);
+        for _ in 2..count {
+            values.push(message::read_string(src).await?);
+        }
+        Ok(JsonArrAppend { key, path, values })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, 2 + self.values.len()).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.path).await?;
+        for value in &self.values {
+            message::write_string(buf, value).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::json::{self, Path},
+};
+
+#[derive(Debug)]
+pub struct JsonDel {
+    pub key: String,
+    /// Deletes the whole document when `None`.
+    pub path: Option<String>,
+}
+
+impl JsonDel {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let path = match self.path.as_deref().map(Path::parse) {
+            Some(Ok(path)) => Some(path),
+            Some(Err(_)) => return Ok(Message::Err("invalid path".to_string())),
+            None => None,
+        };
+        db.update(self.key, |slot| {
+            let Some(val) = slot.get() else {
+                return Ok(Message::Int(0));
+            };
+            let Some(mut doc) = json::decode(val)? else {
+                return Ok(Message::Err("value is not json".to_string()));
+            };
+            match path {
+                Some(path) if !path.is_root() => {
+                    let removed = path.delete(&mut doc);
+                    if removed > 0 {
+                        slot.insert(json::encode(&doc));
+                    }
+                    Ok(Message::Int(removed as i64))
+                }
+                _ => {
+                    slot.remove();
+                    Ok(Message::Int(1))
+                }
+            }
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<JsonDel> {
+        let count = command::read_count(src).await?;
+        if !(1..=2).contains(&count) {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let path = if count == 2 {
+            Some(message::read_string(src).await?)
+        } else {
+            None
+        };
+        Ok(JsonDel { key, path })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1 + self.path.is_some() as u8).await?;
+        message::write_string(buf, &self.key).await?;
+        if let Some(path) = &self.path {
+            message::write_string(buf, path).await?;
+        }
+        Ok(())
+    }
+}
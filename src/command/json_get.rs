@@ -0,0 +1,73 @@
+use std::{io::Cursor, sync::Arc};
+
+use serde_json::Value as Json;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::json::{self, Path},
+};
+
+#[derive(Debug)]
+pub struct JsonGet {
+    pub key: String,
+    /// Defaults to the root path when empty.
+    pub paths: Vec<String>,
+}
+
+impl JsonGet {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let mut paths = Vec::with_capacity(self.paths.len());
+        for path in &self.paths {
+            match Path::parse(path) {
+                Ok(path) => paths.push(path),
+                Err(_) => return Ok(Message::Err("invalid path".to_string())),
+            }
+        }
+        let Some(val) = db.get(&self.key) else {
+            return Ok(Message::Null);
+        };
+        let Some(doc) = json::decode(val.as_ref())? else {
+            return Ok(Message::Err("value is not json".to_string()));
+        };
+        let reply = match paths.as_slice() {
+            [] => Json::Array(vec![doc]),
+            [path] => Json::Array(path.query(&doc).into_iter().cloned().collect()),
+            _ => Json::Object(
+                self.paths
+                    .into_iter()
+                    .zip(&paths)
+                    .map(|(name, path)| {
+                        let matches = path.query(&doc).into_iter().cloned().collect();
+                        (name, Json::Array(matches))
+                    })
+                    .collect(),
+            ),
+        };
+        Ok(Message::Text(reply.to_string()))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<JsonGet> {
+        let count = command::read_count(src).await?;
+        if count < 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let mut paths = Vec::with_capacity(count as usize - 1);
+        for _ in 1..count {
+            paths.push(message::read_string(src).await?);
+        }
+        Ok(JsonGet { key, paths })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, 1 + self.paths.len()).await?;
+        message::write_string(buf, &self.key).await?;
+        for path in &self.paths {
+            message::write_string(buf, path).await?;
+        }
+        Ok(())
+    }
+}
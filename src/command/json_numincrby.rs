@@ -0,0 +1,87 @@
+use std::{io::Cursor, sync::Arc};
+
+use serde_json::{Number, Value as Json};
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::json::{self, Path},
+};
+
+#[derive(Debug)]
+pub struct JsonNumIncrBy {
+    pub key: String,
+    pub path: String,
+    pub number: String,
+}
+
+impl JsonNumIncrBy {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Ok(path) = Path::parse(&self.path) else {
+            return Ok(Message::Err("invalid path".to_string()));
+        };
+        let Ok(Json::Number(by)) = json::parse(&self.number) else {
+            return Ok(Message::Err("increment is not a number".to_string()));
+        };
+        db.update(self.key, |slot| {
+            let Some(val) = slot.get() else {
+                return Ok(Message::Null);
+            };
+            let Some(mut doc) = json::decode(val)? else {
+                return Ok(Message::Err("value is not json".to_string()));
+            };
+            let mut results = Vec::new();
+            let mut incremented = 0;
+            for location in path.locate(&doc) {
+                match json::resolve_mut(&mut doc, &location) {
+                    Some(Json::Number(number)) => match add(number, &by) {
+                        Some(sum) => {
+                            *number = sum.clone();
+                            incremented += 1;
+                            results.push(Json::Number(sum));
+                        }
+                        None => {
+                            return Ok(Message::Err("result is not a finite number".to_string()));
+                        }
+                    },
+                    _ => results.push(Json::Null),
+                }
+            }
+            if incremented > 0 {
+                slot.insert(json::encode(&doc));
+            }
+            Ok(Message::Text(Json::Array(results).to_string()))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<JsonNumIncrBy> {
+        let count = command::read_count(src).await?;
+        if count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let path = message::read_string(src).await?;
+        let number = message::read_string(src).await?;
+        Ok(JsonNumIncrBy { key, path, number })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(3).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.path).await?;
+        message::write_string(buf, &self.number).await?;
+        Ok(())
+    }
+}
+
+/// Adds two JSON numbers, staying integral when both are integers and the sum doesn't overflow.
+fn add(a: &Number, b: &Number) -> Option<Number> {
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64())
+        && let Some(sum) = a.checked_add(b)
+    {
+        return Some(sum.into());
+    }
+    Number::from_f64(a.as_f64()? + b.as_f64()?)
+}
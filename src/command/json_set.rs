@@ -0,0 +1,67 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::json::{self, Path},
+};
+
+#[derive(Debug)]
+pub struct JsonSet {
+    pub key: String,
+    pub path: String,
+    pub json: String,
+}
+
+impl JsonSet {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Ok(path) = Path::parse(&self.path) else {
+            return Ok(Message::Err("invalid path".to_string()));
+        };
+        let Ok(value) = json::parse(&self.json) else {
+            return Ok(Message::Err("invalid json".to_string()));
+        };
+        db.update(self.key, |slot| {
+            let Some(val) = slot.get() else {
+                if !path.is_root() {
+                    return Ok(Message::Err(
+                        "new documents must be created at the root path".to_string(),
+                    ));
+                }
+                slot.insert(json::encode(&value));
+                return Ok(Message::Ok);
+            };
+            let Some(mut doc) = json::decode(val)? else {
+                return Ok(Message::Err("value is not json".to_string()));
+            };
+            if path.set(&mut doc, value) {
+                slot.insert(json::encode(&doc));
+                Ok(Message::Ok)
+            } else {
+                Ok(Message::Null)
+            }
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<JsonSet> {
+        let count = command::read_count(src).await?;
+        if count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let path = message::read_string(src).await?;
+        let json = message::read_string(src).await?;
+        Ok(JsonSet { key, path, json })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(3).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.path).await?;
+        message::write_string(buf, &self.json).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::json::{self, Path},
+};
+
+#[derive(Debug)]
+pub struct JsonType {
+    pub key: String,
+    /// Defaults to the root path when `None`.
+    pub path: Option<String>,
+}
+
+impl JsonType {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let path = match Path::parse(self.path.as_deref().unwrap_or("$")) {
+            Ok(path) => path,
+            Err(_) => return Ok(Message::Err("invalid path".to_string())),
+        };
+        let Some(val) = db.get(&self.key) else {
+            return Ok(Message::Null);
+        };
+        let Some(doc) = json::decode(val.as_ref())? else {
+            return Ok(Message::Err("value is not json".to_string()));
+        };
+        let types = path
+            .query(&doc)
+            .into_iter()
+            .map(|node| Message::Text(json::type_name(node).to_string()))
+            .collect();
+        Ok(Message::Array(types))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<JsonType> {
+        let count = command::read_count(src).await?;
+        if !(1..=2).contains(&count) {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let path = if count == 2 {
+            Some(message::read_string(src).await?)
+        } else {
+            None
+        };
+        Ok(JsonType { key, path })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1 + self.path.is_some() as u8).await?;
+        message::write_string(buf, &self.key).await?;
+        if let Some(path) = &self.path {
+            message::write_string(buf, path).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+/// Moves a key, along with its expiry, from the connection's database to the one numbered
+/// `db`. Replies with 1 if it was moved, or 0 if it doesn't exist or the other database already
+/// has the key.
+#[derive(Debug)]
+pub struct Move {
+    pub key: String,
+    pub db: usize,
+}
+
+impl Move {
+    pub fn perform(self, source: &Arc<Db>, target: &Arc<Db>) -> crate::Result<Message> {
+        if Arc::ptr_eq(source, target) {
+            return Ok(Message::Err(
+                "source and destination databases are the same".to_string(),
+            ));
+        }
+        // Always lock the two in the same order, so two moves in opposite directions can't
+        // deadlock.
+        let (first, second) = if Arc::as_ptr(source) < Arc::as_ptr(target) {
+            (source, target)
+        } else {
+            (target, source)
+        };
+        let _first = first.exclusive();
+        let _second = second.exclusive();
+        let Some(metadata) = source.metadata(&self.key) else {
+            return Ok(Message::Int(0));
+        };
+        if target.metadata(&self.key).is_some() {
+            return Ok(Message::Int(0));
+        }
+        let Some(value) = source.remove(&self.key) else {
+            return Ok(Message::Int(0));
+        };
+        target.update(self.key, |slot| {
+            slot.insert(value);
+            slot.set_expiry(metadata.expires_at);
+        });
+        Ok(Message::Int(1))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Move> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let db = command::read_number(src).await?;
+        Ok(Move { key, db })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.db.to_string()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::Value,
+};
+
+#[derive(Debug)]
+pub struct Type {
+    pub key: String,
+}
+
+impl Type {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        match db.get(&self.key) {
+            Some(val) => Ok(Message::Text(Value::parse(&val)?.type_name().to_string())),
+            None => Ok(Message::Null),
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Type> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        Ok(Type { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
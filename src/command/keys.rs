@@ -0,0 +1,38 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    glob, message,
+};
+
+#[derive(Debug)]
+pub struct Keys {
+    pub pattern: String,
+}
+
+impl Keys {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let keys = db.keys(|key, _| glob::matches(&self.pattern, key));
+        Ok(Message::Array(
+            keys.into_iter().map(Message::Text).collect(),
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Keys> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let pattern = message::read_string(src).await?;
+        Ok(Keys { pattern })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.pattern).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+/// Grants a lease which runs out after `ttl` seconds unless kept alive, replying with its id.
+/// Keys are attached to it with `SET ... LEASE id`.
+#[derive(Debug)]
+pub struct LeaseGrant {
+    pub ttl: u64,
+}
+
+impl LeaseGrant {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let id = db.leases.grant(self.ttl, command::now_millis());
+        Ok(Message::Int(id as i64))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LeaseGrant> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let ttl: u64 = command::read_number(src).await?;
+        if ttl == 0 {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        Ok(LeaseGrant { ttl })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.ttl.to_string()).await?;
+        Ok(())
+    }
+}
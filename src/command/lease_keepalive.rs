@@ -0,0 +1,44 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+/// Restarts a lease's countdown, replying with its ttl in seconds.
+#[derive(Debug)]
+pub struct LeaseKeepAlive {
+    pub id: u64,
+}
+
+impl LeaseKeepAlive {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        match db.leases.keep_alive(self.id, command::now_millis()) {
+            Some(ttl) => Ok(Message::Int(ttl as i64)),
+            None => Ok(Message::Err("lease not found".to_string())),
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LeaseKeepAlive> {
+        let id = read_id(src).await?;
+        Ok(LeaseKeepAlive { id })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.id.to_string()).await?;
+        Ok(())
+    }
+}
+
+/// Reads a command's only argument, a lease id.
+pub(crate) async fn read_id(src: &mut Cursor<&[u8]>) -> crate::Result<u64> {
+    let count = command::read_count(src).await?;
+    if count != 1 {
+        return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+    }
+    command::read_number(src).await
+}
@@ -0,0 +1,31 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{Db, Message, command::lease_keepalive::read_id, message};
+
+/// Revokes a lease, removing every key attached to it, and replies with how many were removed.
+#[derive(Debug)]
+pub struct LeaseRevoke {
+    pub id: u64,
+}
+
+impl LeaseRevoke {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        match db.revoke_lease(self.id) {
+            Some(removed) => Ok(Message::Int(removed as i64)),
+            None => Ok(Message::Err("lease not found".to_string())),
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LeaseRevoke> {
+        let id = read_id(src).await?;
+        Ok(LeaseRevoke { id })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.id.to_string()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, lease_keepalive::read_id},
+    message,
+};
+
+/// Replies with how many seconds a lease has left, the ttl it was granted with, and the keys
+/// attached to it, as name and value pairs, or null if it doesn't exist.
+#[derive(Debug)]
+pub struct LeaseTtl {
+    pub id: u64,
+}
+
+impl LeaseTtl {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Some(info) = db.leases.info(self.id, command::now_millis()) else {
+            return Ok(Message::Null);
+        };
+        Ok(Message::Array(vec![
+            Message::Text("remaining".to_string()),
+            Message::Int(info.remaining.div_ceil(1000) as i64),
+            Message::Text("granted".to_string()),
+            Message::Int(info.ttl as i64),
+            Message::Text("keys".to_string()),
+            Message::Array(info.keys.into_iter().map(Message::Text).collect()),
+        ]))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LeaseTtl> {
+        let id = read_id(src).await?;
+        Ok(LeaseTtl { id })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.id.to_string()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{Db, Message, command::lpop, message, value::list};
+
+/// Replies with the length of a list, or zero if it doesn't exist.
+#[derive(Debug)]
+pub struct LLen {
+    pub key: String,
+}
+
+impl LLen {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Some(val) = db.get(&self.key) else {
+            return Ok(Message::Int(0));
+        };
+        match list::decode(&val)? {
+            Some(list) => Ok(Message::Int(list.len() as i64)),
+            None => Ok(Message::Err("value is not a list".to_string())),
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LLen> {
+        let key = lpop::read_key(src).await?;
+        Ok(LLen { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
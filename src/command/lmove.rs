@@ -0,0 +1,105 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, lpop, lpush},
+    message,
+    value::list,
+};
+
+/// One end of a list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    Left,
+    Right,
+}
+
+impl End {
+    pub fn parse(name: &str) -> Option<End> {
+        match name.to_ascii_uppercase().as_str() {
+            "LEFT" => Some(End::Left),
+            "RIGHT" => Some(End::Right),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            End::Left => "LEFT",
+            End::Right => "RIGHT",
+        }
+    }
+
+    pub(crate) async fn read(src: &mut Cursor<&[u8]>) -> crate::Result<End> {
+        match End::parse(&message::read_string(src).await?) {
+            Some(end) => Ok(end),
+            None => Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+        }
+    }
+}
+
+/// Pops an element from one end of a list and pushes it onto one end of another, which may be
+/// the same list, replying with the element or null if the source is empty.
+#[derive(Debug)]
+pub struct LMove {
+    pub source: String,
+    pub destination: String,
+    pub from: End,
+    pub to: End,
+}
+
+impl LMove {
+    /// Runs under the database's exclusive lock, so the element is never seen in neither list.
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        move_element(&db, &self.source, &self.destination, self.from, self.to)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LMove> {
+        let count = command::read_count(src).await?;
+        if count != 4 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let source = message::read_string(src).await?;
+        let destination = message::read_string(src).await?;
+        let from = End::read(src).await?;
+        let to = End::read(src).await?;
+        Ok(LMove {
+            source,
+            destination,
+            from,
+            to,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(4).await?;
+        message::write_string(buf, &self.source).await?;
+        message::write_string(buf, &self.destination).await?;
+        message::write_string(buf, self.from.name()).await?;
+        message::write_string(buf, self.to.name()).await?;
+        Ok(())
+    }
+}
+
+pub(crate) fn move_element(
+    db: &Db,
+    source: &str,
+    destination: &str,
+    from: End,
+    to: End,
+) -> crate::Result<Message> {
+    // Checked first, so the element isn't popped if it has nowhere to go.
+    if let Some(val) = db.get(destination)
+        && list::decode(&val)?.is_none()
+    {
+        return Ok(Message::Err("value is not a list".to_string()));
+    }
+    let element = match lpop::pop(db, source.to_string(), from)? {
+        Message::Text(element) => element,
+        message => return Ok(message),
+    };
+    lpush::push(db, destination.to_string(), vec![element.clone()], to)?;
+    Ok(Message::Text(element))
+}
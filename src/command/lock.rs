@@ -0,0 +1,89 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, blpop},
+    message,
+    value::Value,
+};
+
+/// Acquires the lock at `key` for `owner`, for a lease of `ttl` milliseconds, replying with a
+/// fencing token higher than any handed out before, or null if someone else holds it.
+///
+/// With a timeout, on a connection, waits for up to `timeout` seconds (or forever, if zero)
+/// for the lock to be released or its lease to run out. Inside a transaction it never waits.
+#[derive(Debug)]
+pub struct Lock {
+    pub key: String,
+    pub owner: String,
+    pub ttl: u64,
+    pub timeout: Option<f64>,
+}
+
+impl Lock {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(self.attempt(&db)?.unwrap_or(Message::Null))
+    }
+
+    /// Acquires the lock if it's free, without waiting.
+    pub(crate) fn attempt(&self, db: &Db) -> crate::Result<Option<Message>> {
+        let expires_at = command::now_millis().saturating_add(self.ttl);
+        db.update(self.key.clone(), |slot| {
+            if slot.is_occupied() {
+                return Ok(None);
+            }
+            slot.insert(Value::String(&self.owner).into_vec());
+            slot.set_expiry(Some(expires_at));
+            Ok(Some(Message::Int(db.fencing_token() as i64)))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Lock> {
+        let count = command::read_count(src).await?;
+        if count != 3 && count != 4 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let owner = message::read_string(src).await?;
+        let ttl = read_ttl(src).await?;
+        let timeout = if count == 4 {
+            Some(blpop::read_timeout(src).await?)
+        } else {
+            None
+        };
+        Ok(Lock {
+            key,
+            owner,
+            ttl,
+            timeout,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(if self.timeout.is_some() { 4 } else { 3 })
+            .await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.owner).await?;
+        message::write_string(buf, &self.ttl.to_string()).await?;
+        if let Some(timeout) = self.timeout {
+            message::write_string(buf, &timeout.to_string()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `owner` holds the lock stored in `val`.
+pub(crate) fn is_owner(val: &[u8], owner: &str) -> bool {
+    matches!(Value::parse(val), Ok(Value::String(held)) if held == owner)
+}
+
+/// Reads a lease length in milliseconds, which must be positive.
+pub(crate) async fn read_ttl(src: &mut Cursor<&[u8]>) -> crate::Result<u64> {
+    let ttl: u64 = command::read_number(src).await?;
+    if ttl == 0 {
+        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+    }
+    Ok(ttl)
+}
@@ -0,0 +1,68 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, lmove::End},
+    message,
+    pubsub::Event,
+    value::list,
+};
+
+/// Removes and replies with the head of a list, or null if it's empty.
+#[derive(Debug)]
+pub struct LPop {
+    pub key: String,
+}
+
+impl LPop {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        pop(&db, self.key, End::Left)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LPop> {
+        let key = read_key(src).await?;
+        Ok(LPop { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
+
+/// Removes an element from one end of the list at `key`, removing the key once it's empty.
+///
+/// Replies with the element as text, or null if there isn't one.
+pub(crate) fn pop(db: &Db, key: String, end: End) -> crate::Result<Message> {
+    db.update(key, |slot| {
+        let Some(val) = slot.get() else {
+            return Ok(Message::Null);
+        };
+        let Some(mut list) = list::decode(val)? else {
+            return Ok(Message::Err("value is not a list".to_string()));
+        };
+        let element = match end {
+            End::Left => list.pop_front(),
+            End::Right => list.pop_back(),
+        };
+        if list.is_empty() {
+            slot.remove();
+            slot.notify(Event::Del);
+        } else {
+            slot.insert(list::encode(&list));
+            slot.notify(Event::Set);
+        }
+        Ok(element.map_or(Message::Null, Message::Text))
+    })
+}
+
+pub(crate) async fn read_key(src: &mut Cursor<&[u8]>) -> crate::Result<String> {
+    let count = command::read_count(src).await?;
+    if count != 1 {
+        return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+    }
+    message::read_string(src).await
+}
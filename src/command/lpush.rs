@@ -0,0 +1,87 @@
+use std::{collections::VecDeque, io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, lmove::End},
+    message,
+    pubsub::Event,
+    value::list,
+};
+
+/// Pushes elements onto the head of a list, creating it if needed, and replies with its length.
+#[derive(Debug)]
+pub struct LPush {
+    pub key: String,
+    pub elements: Vec<String>,
+}
+
+impl LPush {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        push(&db, self.key, self.elements, End::Left)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LPush> {
+        let (key, elements) = read_elements(src).await?;
+        Ok(LPush { key, elements })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        write_elements(buf, &self.key, &self.elements).await
+    }
+}
+
+/// Pushes elements onto one end of the list at `key`, one at a time, waking anyone blocked
+/// waiting for it.
+pub(crate) fn push(
+    db: &Db,
+    key: String,
+    elements: Vec<String>,
+    end: End,
+) -> crate::Result<Message> {
+    db.update(key, |slot| {
+        let mut list = match slot.get() {
+            Some(val) => match list::decode(val)? {
+                Some(list) => list,
+                None => return Ok(Message::Err("value is not a list".to_string())),
+            },
+            None => VecDeque::new(),
+        };
+        for element in elements {
+            match end {
+                End::Left => list.push_front(element),
+                End::Right => list.push_back(element),
+            }
+        }
+        slot.insert(list::encode(&list));
+        slot.notify(Event::Set);
+        Ok(Message::Int(list.len() as i64))
+    })
+}
+
+pub(crate) async fn read_elements(src: &mut Cursor<&[u8]>) -> crate::Result<(String, Vec<String>)> {
+    let count = command::read_count(src).await?;
+    if count < 2 {
+        return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+    }
+    let key = message::read_string(src).await?;
+    let mut elements = Vec::with_capacity(count as usize - 1);
+    for _ in 1..count {
+        elements.push(message::read_string(src).await?);
+    }
+    Ok((key, elements))
+}
+
+pub(crate) async fn write_elements<W: AsyncWriteExt + Unpin>(
+    buf: &mut W,
+    key: &str,
+    elements: &[String],
+) -> crate::Result<()> {
+    command::write_count(buf, 1 + elements.len()).await?;
+    message::write_string(buf, key).await?;
+    for element in elements {
+        message::write_string(buf, element).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::list,
+};
+
+/// Replies with the elements of a list between two indexes, inclusive. Negative indexes count
+/// back from the tail.
+#[derive(Debug)]
+pub struct LRange {
+    pub key: String,
+    pub start: i64,
+    pub stop: i64,
+}
+
+impl LRange {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Some(val) = db.get(&self.key) else {
+            return Ok(Message::Array(Vec::new()));
+        };
+        let Some(list) = list::decode(&val)? else {
+            return Ok(Message::Err("value is not a list".to_string()));
+        };
+        let len = list.len() as i64;
+        let resolve = |index: i64| if index < 0 { len + index } else { index };
+        let start = resolve(self.start).max(0);
+        let stop = resolve(self.stop).min(len - 1);
+        if start > stop {
+            return Ok(Message::Array(Vec::new()));
+        }
+        Ok(Message::Array(
+            list.into_iter()
+                .skip(start as usize)
+                .take((stop - start + 1) as usize)
+                .map(Message::Text)
+                .collect(),
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<LRange> {
+        let count = command::read_count(src).await?;
+        if count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let start = command::read_number(src).await?;
+        let stop = command::read_number(src).await?;
+        Ok(LRange { key, start, stop })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(3).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.start.to_string()).await?;
+        message::write_string(buf, &self.stop.to_string()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, get},
+    message,
+};
+
+#[derive(Debug)]
+pub struct MGet {
+    pub keys: Vec<String>,
+}
+
+impl MGet {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let values = self
+            .keys
+            .iter()
+            .map(|key| match db.get(key) {
+                Some(val) => get::to_message(&val),
+                None => Ok(Message::Null),
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(Message::Array(values))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<MGet> {
+        let count = command::read_count(src).await?;
+        if count == 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut keys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            keys.push(message::read_string(src).await?);
+        }
+        Ok(MGet { keys })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.keys.len()).await?;
+        for key in &self.keys {
+            message::write_string(buf, key).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, set},
+    message,
+    pubsub::Event,
+};
+
+#[derive(Debug)]
+pub struct MSet {
+    pub pairs: Vec<(String, Vec<u8>)>,
+}
+
+impl MSet {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if let Some(err) = self
+            .pairs
+            .iter()
+            .find_map(|(_, value)| set::validate(value))
+        {
+            return Ok(err);
+        }
+        set_all(&db, self.pairs);
+        Ok(Message::Ok)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<MSet> {
+        Ok(MSet {
+            pairs: read_pairs(src).await?,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        write_pairs(buf, &self.pairs).await
+    }
+}
+
+/// Sets every key, clearing any expiry as `SET` does.
+pub(crate) fn set_all(db: &Db, pairs: Vec<(String, Vec<u8>)>) {
+    for (key, value) in pairs {
+        db.update(key, |slot| {
+            slot.insert(value);
+            slot.set_expiry(None);
+            slot.notify(Event::Set);
+        });
+    }
+}
+
+pub(crate) async fn read_pairs(src: &mut Cursor<&[u8]>) -> crate::Result<Vec<(String, Vec<u8>)>> {
+    let count = command::read_count(src).await?;
+    if count == 0 || count % 2 != 0 {
+        return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+    }
+    let mut pairs = Vec::with_capacity(count as usize / 2);
+    for _ in 0..count / 2 {
+        let key = message::read_string(src).await?;
+        let value = message::read_bytes(src).await?;
+        pairs.push((key, value));
+    }
+    Ok(pairs)
+}
+
+pub(crate) async fn write_pairs<W: AsyncWriteExt + Unpin>(
+    buf: &mut W,
+    pairs: &[(String, Vec<u8>)],
+) -> crate::Result<()> {
+    command::write_count(buf, 2 * pairs.len()).await?;
+    for (key, value) in pairs {
+        message::write_string(buf, key).await?;
+        message::write_bytes(buf, value).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{mset, set},
+};
+
+/// Sets every key, unless any of them already exist.
+#[derive(Debug)]
+pub struct MSetNx {
+    pub pairs: Vec<(String, Vec<u8>)>,
+}
+
+impl MSetNx {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if let Some(err) = self
+            .pairs
+            .iter()
+            .find_map(|(_, value)| set::validate(value))
+        {
+            return Ok(err);
+        }
+        // Runs under the exclusive lock, so nothing can create a key between checking and setting.
+        if self.pairs.iter().any(|(key, _)| db.contains_key(key)) {
+            return Ok(Message::Int(0));
+        }
+        mset::set_all(&db, self.pairs);
+        Ok(Message::Int(1))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<MSetNx> {
+        Ok(MSetNx {
+            pairs: mset::read_pairs(src).await?,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        mset::write_pairs(buf, &self.pairs).await
+    }
+}
@@ -0,0 +1,24 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::command::{self, Error};
+
+/// Starts queueing commands, to be run together by `EXEC`.
+#[derive(Debug)]
+pub struct Multi;
+
+impl Multi {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Multi> {
+        let count = command::read_count(src).await?;
+        if count != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(Multi)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(0).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, ack, enqueue},
+    message,
+};
+
+/// Gives up a received message, making it visible again, immediately or after a delay, rather
+/// than waiting for its visibility timeout. Replies like `ACK`.
+#[derive(Debug)]
+pub struct Nack {
+    pub key: String,
+    pub receipt: String,
+    /// How long the message stays invisible, in milliseconds.
+    pub delay: Option<u64>,
+}
+
+impl Nack {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let visible_at = command::now_millis().saturating_add(self.delay.unwrap_or(0));
+        ack::update(&db, self.key, |queue| queue.nack(&self.receipt, visible_at))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Nack> {
+        let count = command::read_count(src).await?;
+        if count != 2 && count != 4 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let receipt = message::read_string(src).await?;
+        let delay = if count == 4 {
+            Some(enqueue::read_delay(src).await?)
+        } else {
+            None
+        };
+        Ok(Nack {
+            key,
+            receipt,
+            delay,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(if self.delay.is_some() { 4 } else { 2 })
+            .await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.receipt).await?;
+        if let Some(delay) = self.delay {
+            enqueue::write_delay(buf, delay).await?;
+        }
+        Ok(())
+    }
+}
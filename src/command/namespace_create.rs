@@ -0,0 +1,129 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+    namespace::Eviction,
+};
+
+/// Creates a namespace, a keyspace of its own which connections use with `NAMESPACE.USE`, or by
+/// prefixing keys with `{ns:name}`.
+///
+/// Without a password anyone can read and write it. Otherwise the password grants read-write
+/// access, and the read password, if any, read-only access. The creating connection is granted
+/// read-write access.
+#[derive(Debug)]
+pub struct NamespaceCreate {
+    pub name: String,
+    /// The most bytes of keys and values it may hold.
+    pub max_memory: Option<usize>,
+    /// How long keys created without an expiry live, in seconds.
+    pub ttl: Option<u64>,
+    /// What happens once it holds more than `max_memory`, refusing writes by default.
+    pub eviction: Option<Eviction>,
+    pub password: Option<String>,
+    pub read_password: Option<String>,
+}
+
+impl NamespaceCreate {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<NamespaceCreate> {
+        let count = command::read_count(src).await?;
+        if count == 0 || !(count - 1).is_multiple_of(2) {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let name = message::read_string(src).await?;
+        if !is_valid_name(&name) {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        let mut create = NamespaceCreate {
+            name,
+            max_memory: None,
+            ttl: None,
+            eviction: None,
+            password: None,
+            read_password: None,
+        };
+        for _ in 0..(count - 1) / 2 {
+            let option = message::read_string(src).await?.to_ascii_uppercase();
+            // Each option may only be given once.
+            let duplicate = match option.as_str() {
+                "MAXMEMORY" => create
+                    .max_memory
+                    .replace(command::read_number(src).await?)
+                    .is_some(),
+                "TTL" => create
+                    .ttl
+                    .replace(command::read_number(src).await?)
+                    .is_some(),
+                "EVICTION" => {
+                    let Some(eviction) = Eviction::parse(&message::read_string(src).await?) else {
+                        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+                    };
+                    create.eviction.replace(eviction).is_some()
+                }
+                "PASSWORD" => create
+                    .password
+                    .replace(message::read_string(src).await?)
+                    .is_some(),
+                "READPASSWORD" => create
+                    .read_password
+                    .replace(message::read_string(src).await?)
+                    .is_some(),
+                _ => true,
+            };
+            if duplicate {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+        }
+        // Without a password everyone can already write, so a read password would mean nothing.
+        if create.ttl == Some(0)
+            || create.max_memory == Some(0)
+            || (create.read_password.is_some() && create.password.is_none())
+        {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        Ok(create)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        let mut options: Vec<String> = Vec::new();
+        if let Some(max_memory) = self.max_memory {
+            options.extend(["MAXMEMORY".to_string(), max_memory.to_string()]);
+        }
+        if let Some(ttl) = self.ttl {
+            options.extend(["TTL".to_string(), ttl.to_string()]);
+        }
+        if let Some(eviction) = self.eviction {
+            options.extend(["EVICTION".to_string(), eviction.name().to_string()]);
+        }
+        if let Some(password) = &self.password {
+            options.extend(["PASSWORD".to_string(), password.clone()]);
+        }
+        if let Some(read_password) = &self.read_password {
+            options.extend(["READPASSWORD".to_string(), read_password.clone()]);
+        }
+        command::write_count(buf, 1 + options.len()).await?;
+        message::write_string(buf, &self.name).await?;
+        for option in options {
+            message::write_string(buf, &option).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `name` can be used as a namespace's name, which must fit within a `{ns:name}` prefix.
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['{', '}'])
+}
+
+/// The namespace named by a `{ns:name}` prefix on `key`, if it has one.
+///
+/// The prefix is recognized whether or not the namespace exists, so creating one never changes
+/// where existing keys live, and it can't be mistaken for a `{tag}` hash tag.
+pub(crate) fn prefix(key: &str) -> Option<&str> {
+    let rest = key.strip_prefix("{ns:")?;
+    let (name, _) = rest.split_once('}')?;
+    Some(name).filter(|name| is_valid_name(name))
+}
@@ -0,0 +1,31 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+};
+
+/// Removes a namespace along with all of its keys. Needs read-write access to it.
+#[derive(Debug)]
+pub struct NamespaceDrop {
+    pub name: String,
+}
+
+impl NamespaceDrop {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<NamespaceDrop> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let name = message::read_string(src).await?;
+        Ok(NamespaceDrop { name })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.name).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+};
+
+/// Replies with how many keys a namespace holds, the bytes they take, and its settings, as name
+/// and value pairs. Needs access to it.
+#[derive(Debug)]
+pub struct NamespaceInfo {
+    pub name: String,
+}
+
+impl NamespaceInfo {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<NamespaceInfo> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let name = message::read_string(src).await?;
+        Ok(NamespaceInfo { name })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.name).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+};
+
+/// Switches the connection to a namespace, first gaining access to it with the password if
+/// one's given. `SELECT` switches back to a numbered database.
+#[derive(Debug)]
+pub struct NamespaceUse {
+    pub name: String,
+    pub password: Option<String>,
+}
+
+impl NamespaceUse {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<NamespaceUse> {
+        let count = command::read_count(src).await?;
+        if count != 1 && count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let name = message::read_string(src).await?;
+        let password = if count == 2 {
+            Some(message::read_string(src).await?)
+        } else {
+            None
+        };
+        Ok(NamespaceUse { name, password })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(if self.password.is_some() { 2 } else { 1 })
+            .await?;
+        message::write_string(buf, &self.name).await?;
+        if let Some(password) = &self.password {
+            message::write_string(buf, password).await?;
+        }
+        Ok(())
+    }
+}
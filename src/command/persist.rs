@@ -0,0 +1,44 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    pubsub::Event,
+};
+
+/// Removes a key's expiry.
+#[derive(Debug)]
+pub struct Persist {
+    pub key: String,
+}
+
+impl Persist {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        db.update(self.key, |slot| {
+            if slot.expires_at().is_none() {
+                return Ok(Message::Int(0));
+            }
+            slot.set_expiry(None);
+            slot.notify(Event::Persist);
+            Ok(Message::Int(1))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Persist> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        Ok(Persist { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
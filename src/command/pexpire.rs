@@ -0,0 +1,44 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, expire},
+    message,
+};
+
+/// Sets a key to expire after a number of millis.
+#[derive(Debug)]
+pub struct PExpire {
+    pub key: String,
+    pub millis: u64,
+}
+
+impl PExpire {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let now = command::now_millis();
+        Ok(expire::expire_at(
+            &db,
+            self.key,
+            now.saturating_add(self.millis),
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<PExpire> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let millis = command::read_number(src).await?;
+        Ok(PExpire { key, millis })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.millis.to_string()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, expire},
+    message,
+};
+
+/// Sets a key to expire at a unix time in milliseconds.
+#[derive(Debug)]
+pub struct PExpireAt {
+    pub key: String,
+    pub timestamp: u64,
+}
+
+impl PExpireAt {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(expire::expire_at(&db, self.key, self.timestamp))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<PExpireAt> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let timestamp = command::read_number(src).await?;
+        Ok(PExpireAt { key, timestamp })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.timestamp.to_string()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+};
+
+/// Like `SUBSCRIBE`, but for every channel matching the glob patterns.
+#[derive(Debug)]
+pub struct PSubscribe {
+    pub patterns: Vec<String>,
+}
+
+impl PSubscribe {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<PSubscribe> {
+        let count = command::read_count(src).await?;
+        if count == 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut patterns = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            patterns.push(message::read_string(src).await?);
+        }
+        Ok(PSubscribe { patterns })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.patterns.len()).await?;
+        for pattern in &self.patterns {
+            message::write_string(buf, pattern).await?;
+        }
+        Ok(())
+    }
+}
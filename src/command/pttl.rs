@@ -0,0 +1,38 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, ttl},
+    message,
+};
+
+/// Returns the milliseconds until a key expires, -1 if it never does, or -2 if it doesn't exist.
+#[derive(Debug)]
+pub struct PTtl {
+    pub key: String,
+}
+
+impl PTtl {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(Message::Int(
+            ttl::remaining(&db, &self.key).map_or_else(|ttl| ttl, |millis| millis as i64),
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<PTtl> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        Ok(PTtl { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+/// Sends a message to everyone subscribed to a channel, replying with how many received it.
+#[derive(Debug)]
+pub struct Publish {
+    pub channel: String,
+    pub message: String,
+}
+
+impl Publish {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(Message::Int(
+            db.channels.publish(&self.channel, &self.message) as i64,
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Publish> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let channel = message::read_string(src).await?;
+        let message = message::read_string(src).await?;
+        Ok(Publish { channel, message })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.channel).await?;
+        message::write_string(buf, &self.message).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{command, message};
+
+/// Stops receiving messages for the patterns, or for every pattern if none are given.
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    pub patterns: Vec<String>,
+}
+
+impl PUnsubscribe {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<PUnsubscribe> {
+        let count = command::read_count(src).await?;
+        let mut patterns = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            patterns.push(message::read_string(src).await?);
+        }
+        Ok(PUnsubscribe { patterns })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.patterns.len()).await?;
+        for pattern in &self.patterns {
+            message::write_string(buf, pattern).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::queue::{DEFAULT_VISIBILITY, Queue},
+};
+
+/// Creates a queue with its settings. `ENQUEUE` creates queues with the default settings, so
+/// this is only needed to change them.
+#[derive(Debug)]
+pub struct QCreate {
+    pub key: String,
+    /// How long a received message stays invisible, in milliseconds.
+    pub visibility: Option<u64>,
+    /// How many times a message can be received before it's moved to the dead-letter queue,
+    /// along with the dead-letter queue's key.
+    pub dead_letter: Option<(u32, String)>,
+}
+
+impl QCreate {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if self
+            .dead_letter
+            .as_ref()
+            .is_some_and(|(_, dead_letter)| *dead_letter == self.key)
+        {
+            return Ok(Message::Err(
+                "a queue can't be its own dead-letter queue".to_string(),
+            ));
+        }
+        db.update(self.key, |slot| {
+            if slot.is_occupied() {
+                return Ok(Message::Err("key already exists".to_string()));
+            }
+            let (max_receives, dead_letter) = match self.dead_letter {
+                Some((max_receives, dead_letter)) => (max_receives, Some(dead_letter)),
+                None => (0, None),
+            };
+            let visibility = self.visibility.unwrap_or(DEFAULT_VISIBILITY);
+            slot.insert(Queue::new(visibility, max_receives, dead_letter).encode());
+            Ok(Message::Ok)
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<QCreate> {
+        let count = command::read_count(src).await?;
+        if count == 0 || !(count - 1).is_multiple_of(2) {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let mut visibility = None;
+        let mut max_receives = None;
+        let mut dead_letter = None;
+        for _ in 0..(count - 1) / 2 {
+            let option = message::read_string(src).await?.to_ascii_uppercase();
+            // Each option may only be given once.
+            let duplicate = match option.as_str() {
+                "VISIBILITY" => visibility
+                    .replace(command::read_number::<u64>(src).await?)
+                    .is_some(),
+                "MAXRECEIVES" => {
+                    let n: u32 = command::read_number(src).await?;
+                    if n == 0 {
+                        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+                    }
+                    max_receives.replace(n).is_some()
+                }
+                "DEADLETTER" => dead_letter
+                    .replace(message::read_string(src).await?)
+                    .is_some(),
+                _ => true,
+            };
+            if duplicate {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+        }
+        // A message received too many times has to go somewhere, and a dead-letter queue
+        // is of no use without a limit.
+        let dead_letter = match (max_receives, dead_letter) {
+            (Some(max_receives), Some(dead_letter)) => Some((max_receives, dead_letter)),
+            (None, None) => None,
+            _ => return Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+        };
+        Ok(QCreate {
+            key,
+            visibility,
+            dead_letter,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        let mut options: Vec<String> = Vec::new();
+        if let Some(visibility) = self.visibility {
+            options.extend(["VISIBILITY".to_string(), visibility.to_string()]);
+        }
+        if let Some((max_receives, dead_letter)) = &self.dead_letter {
+            options.extend(["MAXRECEIVES".to_string(), max_receives.to_string()]);
+            options.extend(["DEADLETTER".to_string(), dead_letter.clone()]);
+        }
+        command::write_count(buf, 1 + options.len()).await?;
+        message::write_string(buf, &self.key).await?;
+        for option in options {
+            message::write_string(buf, &option).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, lpop},
+    message,
+    value::queue::Queue,
+};
+
+/// Replies with how many messages in a queue can be received now, how many are in flight
+/// and how many are delayed, as name and count pairs, or null if it doesn't exist.
+#[derive(Debug)]
+pub struct QStats {
+    pub key: String,
+}
+
+impl QStats {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Some(val) = db.get(&self.key) else {
+            return Ok(Message::Null);
+        };
+        let Some(queue) = Queue::decode(&val)? else {
+            return Ok(Message::Err("value is not a queue".to_string()));
+        };
+        let stats = queue.stats(command::now_millis());
+        Ok(Message::Array(vec![
+            Message::Text("depth".to_string()),
+            Message::Int(stats.ready as i64),
+            Message::Text("in-flight".to_string()),
+            Message::Int(stats.in_flight as i64),
+            Message::Text("delayed".to_string()),
+            Message::Int(stats.delayed as i64),
+        ]))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<QStats> {
+        let key = lpop::read_key(src).await?;
+        Ok(QStats { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
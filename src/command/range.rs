@@ -0,0 +1,190 @@
+use std::{io::Cursor, ops::Bound, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, get},
+    message,
+};
+
+// Bounds are written as:
+//
+// -        no lower bound
+// +        no upper bound
+// [key     up to or from the key itself
+// (key     up to or from the key, but not the key itself
+// key      the same as [key
+
+/// The number of entries returned when a range doesn't specify a limit.
+const DEFAULT_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyRange {
+    /// Keys between a lower and upper bound.
+    Between(Bound<String>, Bound<String>),
+    /// Keys starting with a prefix.
+    Prefix(String),
+}
+
+impl KeyRange {
+    /// Parses a range from its lower and upper bounds.
+    pub fn between(start: String, end: String) -> crate::Result<KeyRange> {
+        Ok(KeyRange::Between(
+            parse_bound(start, "-")?,
+            parse_bound(end, "+")?,
+        ))
+    }
+
+    fn bounds(&self) -> (Bound<String>, Bound<String>) {
+        match self {
+            KeyRange::Between(start, end) => (start.clone(), end.clone()),
+            KeyRange::Prefix(prefix) => (
+                Bound::Included(prefix.clone()),
+                prefix_end(prefix).map_or(Bound::Unbounded, Bound::Excluded),
+            ),
+        }
+    }
+}
+
+/// Returns the keys and values in a range, in key order.
+#[derive(Debug)]
+pub struct Range {
+    pub range: KeyRange,
+    pub limit: Option<usize>,
+}
+
+impl Range {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        perform(&db, &self.range, self.limit, false)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Range> {
+        let (range, limit) = read(src, false).await?;
+        Ok(Range { range, limit })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        write(buf, &self.range, self.limit, false).await
+    }
+}
+
+pub(crate) fn perform(
+    db: &Db,
+    range: &KeyRange,
+    limit: Option<usize>,
+    reverse: bool,
+) -> crate::Result<Message> {
+    let (start, end) = range.bounds();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let Some(entries) = db.range(bound_ref(&start), bound_ref(&end), reverse, limit) else {
+        return Ok(Message::Err("keyspace is not ordered".to_string()));
+    };
+    let entries = entries
+        .into_iter()
+        .map(|(key, value)| {
+            Ok(Message::Array(vec![
+                Message::Text(key),
+                get::to_message(&value)?,
+            ]))
+        })
+        .collect::<crate::Result<_>>()?;
+    Ok(Message::Array(entries))
+}
+
+/// Reads a range and optional limit. Reversed ranges give the upper bound first.
+pub(crate) async fn read(
+    src: &mut Cursor<&[u8]>,
+    reverse: bool,
+) -> crate::Result<(KeyRange, Option<usize>)> {
+    let count = command::read_count(src).await?;
+    if count != 2 && count != 4 {
+        return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+    }
+    let first = message::read_string(src).await?;
+    let second = message::read_string(src).await?;
+    let range = if first.eq_ignore_ascii_case("PREFIX") {
+        KeyRange::Prefix(second)
+    } else if reverse {
+        KeyRange::between(second, first)?
+    } else {
+        KeyRange::between(first, second)?
+    };
+    let limit = if count == 4 {
+        let option = message::read_string(src).await?;
+        if !option.eq_ignore_ascii_case("LIMIT") {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        Some(command::read_number(src).await?)
+    } else {
+        None
+    };
+    Ok((range, limit))
+}
+
+pub(crate) async fn write<W: AsyncWriteExt + Unpin>(
+    buf: &mut W,
+    range: &KeyRange,
+    limit: Option<usize>,
+    reverse: bool,
+) -> crate::Result<()> {
+    buf.write_u8(if limit.is_some() { 4 } else { 2 }).await?;
+    match range {
+        KeyRange::Prefix(prefix) => {
+            message::write_string(buf, "PREFIX").await?;
+            message::write_string(buf, prefix).await?;
+        }
+        KeyRange::Between(start, end) => {
+            let (start, end) = (write_bound(start, "-"), write_bound(end, "+"));
+            let (first, second) = if reverse { (end, start) } else { (start, end) };
+            message::write_string(buf, &first).await?;
+            message::write_string(buf, &second).await?;
+        }
+    }
+    if let Some(limit) = limit {
+        message::write_string(buf, "LIMIT").await?;
+        message::write_string(buf, &limit.to_string()).await?;
+    }
+    Ok(())
+}
+
+/// Parses a bound, where `unbounded` is the symbol for no bound at this end of the range.
+fn parse_bound(arg: String, unbounded: &str) -> crate::Result<Bound<String>> {
+    if arg == unbounded {
+        return Ok(Bound::Unbounded);
+    }
+    if arg == "-" || arg == "+" {
+        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+    }
+    Ok(match arg.strip_prefix('(') {
+        Some(key) => Bound::Excluded(key.to_string()),
+        None => Bound::Included(arg.strip_prefix('[').unwrap_or(&arg).to_string()),
+    })
+}
+
+fn write_bound(bound: &Bound<String>, unbounded: &str) -> String {
+    match bound {
+        Bound::Included(key) => format!("[{key}"),
+        Bound::Excluded(key) => format!("({key}"),
+        Bound::Unbounded => unbounded.to_string(),
+    }
+}
+
+fn bound_ref(bound: &Bound<String>) -> Bound<&str> {
+    bound.as_ref().map(String::as_str)
+}
+
+/// The smallest string greater than every string starting with `prefix`, if there is one.
+fn prefix_end(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(last) = chars.pop() {
+        let next = match last {
+            char::MAX => continue,
+            '\u{d7ff}' => '\u{e000}',
+            c => char::from_u32(c as u32 + 1).unwrap(),
+        };
+        chars.push(next);
+        return Some(chars.into_iter().collect());
+    }
+    None
+}
@@ -0,0 +1,65 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    pubsub::Event,
+};
+
+#[derive(Debug)]
+pub struct Rename {
+    pub key: String,
+    pub new_key: String,
+}
+
+impl Rename {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if !db.contains_key(&self.key) {
+            return Ok(Message::Err("no such key".to_string()));
+        }
+        if self.key != self.new_key {
+            move_value(&db, &self.key, self.new_key);
+        }
+        Ok(Message::Ok)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Rename> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let new_key = message::read_string(src).await?;
+        Ok(Rename { key, new_key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.new_key).await?;
+        Ok(())
+    }
+}
+
+/// Moves the value at `key` to `new_key` along with its expiry, replacing anything already
+/// there.
+///
+/// Only atomic while the caller holds the database's exclusive lock.
+pub(crate) fn move_value(db: &Db, key: &str, new_key: String) {
+    let moved = db.update(key.to_string(), |slot| {
+        let expires_at = slot.expires_at();
+        let value = slot.remove()?;
+        slot.notify(Event::Del);
+        Some((value, expires_at))
+    });
+    if let Some((value, expires_at)) = moved {
+        db.update(new_key, |slot| {
+            slot.insert(value);
+            slot.set_expiry(expires_at);
+            slot.notify(Event::Set);
+        });
+    }
+}
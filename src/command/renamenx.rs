@@ -0,0 +1,45 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, rename},
+    message,
+};
+
+#[derive(Debug)]
+pub struct RenameNx {
+    pub key: String,
+    pub new_key: String,
+}
+
+impl RenameNx {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if !db.contains_key(&self.key) {
+            return Ok(Message::Err("no such key".to_string()));
+        }
+        if db.contains_key(&self.new_key) {
+            return Ok(Message::Int(0));
+        }
+        rename::move_value(&db, &self.key, self.new_key);
+        Ok(Message::Int(1))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<RenameNx> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let new_key = message::read_string(src).await?;
+        Ok(RenameNx { key, new_key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.new_key).await?;
+        Ok(())
+    }
+}
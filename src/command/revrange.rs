@@ -0,0 +1,31 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::range::{self, KeyRange},
+};
+
+/// Returns the keys and values in a range, in reverse key order. The upper bound is given
+/// first.
+#[derive(Debug)]
+pub struct RevRange {
+    pub range: KeyRange,
+    pub limit: Option<usize>,
+}
+
+impl RevRange {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        range::perform(&db, &self.range, self.limit, true)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<RevRange> {
+        let (range, limit) = range::read(src, true).await?;
+        Ok(RevRange { range, limit })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        range::write(buf, &self.range, self.limit, true).await
+    }
+}
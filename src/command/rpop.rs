@@ -0,0 +1,32 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{lmove::End, lpop},
+    message,
+};
+
+/// Removes and replies with the tail of a list, or null if it's empty.
+#[derive(Debug)]
+pub struct RPop {
+    pub key: String,
+}
+
+impl RPop {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        lpop::pop(&db, self.key, End::Right)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<RPop> {
+        let key = lpop::read_key(src).await?;
+        Ok(RPop { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
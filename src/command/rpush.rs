@@ -0,0 +1,30 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{lmove::End, lpush},
+};
+
+/// Pushes elements onto the tail of a list, creating it if needed, and replies with its length.
+#[derive(Debug)]
+pub struct RPush {
+    pub key: String,
+    pub elements: Vec<String>,
+}
+
+impl RPush {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        lpush::push(&db, self.key, self.elements, End::Right)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<RPush> {
+        let (key, elements) = lpush::read_elements(src).await?;
+        Ok(RPush { key, elements })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        lpush::write_elements(buf, &self.key, &self.elements).await
+    }
+}
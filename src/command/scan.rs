@@ -0,0 +1,90 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    glob, message,
+    value::Value,
+};
+
+/// The number of keys returned when a scan doesn't specify a count.
+const DEFAULT_COUNT: usize = 10;
+
+/// Returns the next batch of keys from a cursor, starting from zero, along with the cursor for
+/// the batch after. The scan is complete once the returned cursor is zero.
+#[derive(Debug)]
+pub struct Scan {
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: Option<usize>,
+    /// Only return keys holding this type of value, as named by `TYPE`.
+    pub kind: Option<String>,
+}
+
+impl Scan {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let (cursor, keys) = db.scan(
+            self.cursor,
+            self.count.unwrap_or(DEFAULT_COUNT),
+            |key, value| {
+                self.pattern
+                    .as_ref()
+                    .is_none_or(|pattern| glob::matches(pattern, key))
+                    && self.kind.as_ref().is_none_or(|kind| {
+                        Value::parse(value).is_ok_and(|v| v.type_name().eq_ignore_ascii_case(kind))
+                    })
+            },
+        );
+        Ok(Message::Array(vec![
+            Message::Text(cursor.to_string()),
+            Message::Array(keys.into_iter().map(Message::Text).collect()),
+        ]))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Scan> {
+        let count = command::read_count(src).await?;
+        if count % 2 != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut scan = Scan {
+            cursor: command::read_number(src).await?,
+            pattern: None,
+            count: None,
+            kind: None,
+        };
+        for _ in 0..count / 2 {
+            let option = message::read_string(src).await?;
+            if option.eq_ignore_ascii_case("MATCH") {
+                scan.pattern = Some(message::read_string(src).await?);
+            } else if option.eq_ignore_ascii_case("COUNT") {
+                scan.count = Some(command::read_number(src).await?);
+            } else if option.eq_ignore_ascii_case("TYPE") {
+                scan.kind = Some(message::read_string(src).await?);
+            } else {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+        }
+        Ok(scan)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        let options = [
+            ("MATCH", self.pattern.clone()),
+            ("COUNT", self.count.map(|count| count.to_string())),
+            ("TYPE", self.kind.clone()),
+        ];
+        let options: Vec<_> = options
+            .into_iter()
+            .filter_map(|(name, arg)| Some((name, arg?)))
+            .collect();
+        command::write_count(buf, 1 + 2 * options.len()).await?;
+        message::write_string(buf, &self.cursor.to_string()).await?;
+        for (name, arg) in options {
+            message::write_string(buf, name).await?;
+            message::write_string(buf, &arg).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::list,
+};
+
+/// Schedules a payload to be pushed onto the end of the list at `key` once `at` has passed, for
+/// workers waiting on it with `BLPOP`. Replies with the job's id.
+#[derive(Debug)]
+pub struct Schedule {
+    pub key: String,
+    /// When the job is due, as a unix time in milliseconds.
+    pub at: u64,
+    pub payload: String,
+}
+
+impl Schedule {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if let Some(val) = db.get(&self.key)
+            && list::decode(&val)?.is_none()
+        {
+            return Ok(Message::Err("value is not a list".to_string()));
+        }
+        let id = db.schedule.add(self.key, self.at, self.payload);
+        Ok(Message::Int(id as i64))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Schedule> {
+        let count = command::read_count(src).await?;
+        if count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let at = command::read_number(src).await?;
+        let payload = message::read_string(src).await?;
+        Ok(Schedule { key, at, payload })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(3).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.at.to_string()).await?;
+        message::write_string(buf, &self.payload).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+};
+
+/// Switches the connection to the logical database numbered `index`.
+#[derive(Debug)]
+pub struct Select {
+    pub index: usize,
+}
+
+impl Select {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Select> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let index = command::read_number(src).await?;
+        Ok(Select { index })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.index.to_string()).await?;
+        Ok(())
+    }
+}
@@ -1,45 +1,221 @@
 use std::{io::Cursor, sync::Arc};
 
-use dashmap::DashMap;
 use tokio::io::AsyncWriteExt;
 
 use crate::{
-    Message, Result,
-    command::{self, Error},
+    Db, Message, Result,
+    command::{self, Error, get},
     message,
-    value::Value,
+    pubsub::Event,
+    value::{Value, json},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// Only set the key if it doesn't exist.
+    Nx,
+    /// Only set the key if it already exists.
+    Xx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// Expire after this many seconds.
+    Ex(u64),
+    /// Expire after this many milliseconds.
+    Px(u64),
+    /// Expire at this unix time, in seconds.
+    ExAt(u64),
+    /// Expire at this unix time, in milliseconds.
+    PxAt(u64),
+    /// Keep the key's current expiry.
+    KeepTtl,
+}
+
+impl Expiry {
+    /// When a key given this expiry at `now` expires, or `None` for `KeepTtl`.
+    pub fn deadline(&self, now: u64) -> Option<u64> {
+        match *self {
+            Expiry::Ex(secs) => Some(now.saturating_add(secs.saturating_mul(1000))),
+            Expiry::Px(millis) => Some(now.saturating_add(millis)),
+            Expiry::ExAt(secs) => Some(secs.saturating_mul(1000)),
+            Expiry::PxAt(millis) => Some(millis),
+            Expiry::KeepTtl => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Set {
     pub key: String,
     pub value: Vec<u8>,
+    pub condition: Option<Condition>,
+    /// Reply with the old value rather than `OK`.
+    pub get: bool,
+    /// When the key expires. Without one, any existing expiry is cleared.
+    pub expiry: Option<Expiry>,
+    /// The lease to attach the key to. Without one, the key is detached from any lease.
+    pub lease: Option<u64>,
 }
 
 impl Set {
-    pub fn perform(self, db: Arc<DashMap<String, Vec<u8>>>) -> crate::Result<Message> {
-        if Value::parse(&self.value).is_err() {
-            Ok(Message::Err("invalid value".to_string()))
-        } else {
-            db.insert(self.key, self.value);
-            Ok(Message::Ok)
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if let Some(err) = validate(&self.value) {
+            return Ok(err);
         }
+        let now = command::now_millis();
+        let key = self.key.clone();
+        db.update(self.key, |slot| {
+            let old = match slot.get() {
+                Some(val) if self.get => get::to_message(val)?,
+                _ => Message::Null,
+            };
+            if let Message::Err(_) = old {
+                return Ok(old);
+            }
+            let skip = match self.condition {
+                Some(Condition::Nx) => slot.is_occupied(),
+                Some(Condition::Xx) => !slot.is_occupied(),
+                None => false,
+            };
+            if skip {
+                return Ok(old);
+            }
+            let expires_at = match self.expiry {
+                Some(Expiry::KeepTtl) => slot.expires_at(),
+                Some(expiry) => expiry.deadline(now),
+                None => None,
+            };
+            if expires_at.is_some_and(|at| at <= now) {
+                slot.remove();
+            } else {
+                match self.lease {
+                    Some(lease) if !db.leases.attach(&key, lease, now) => {
+                        return Ok(Message::Err("lease not found".to_string()));
+                    }
+                    Some(_) => {}
+                    None => db.leases.detach(&key),
+                }
+                slot.insert(self.value);
+                slot.set_expiry(expires_at);
+                slot.notify(Event::Set);
+            }
+            Ok(if self.get { old } else { Message::Ok })
+        })
     }
 
     pub async fn parse(src: &mut Cursor<&[u8]>) -> Result<Set> {
         let count = command::read_count(src).await?;
-        if count != 2 {
+        if count < 2 {
             return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
         }
         let key = message::read_string(src).await?;
         let value = message::read_bytes(src).await?;
-        Ok(Set { key, value })
+        let mut set = Set {
+            key,
+            value,
+            condition: None,
+            get: false,
+            expiry: None,
+            lease: None,
+        };
+        let mut remaining = count - 2;
+        while remaining > 0 {
+            remaining -= 1;
+            let option = message::read_string(src).await?.to_ascii_uppercase();
+            let (condition, expiry) = match option.as_str() {
+                "NX" => (Some(Condition::Nx), None),
+                "XX" => (Some(Condition::Xx), None),
+                "GET" => {
+                    set.get = true;
+                    (None, None)
+                }
+                "KEEPTTL" => (None, Some(Expiry::KeepTtl)),
+                "EX" | "PX" | "EXAT" | "PXAT" => {
+                    if remaining == 0 {
+                        return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+                    }
+                    remaining -= 1;
+                    let time: u64 = command::read_number(src).await?;
+                    if time == 0 {
+                        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+                    }
+                    let expiry = match option.as_str() {
+                        "EX" => Expiry::Ex(time),
+                        "PX" => Expiry::Px(time),
+                        "EXAT" => Expiry::ExAt(time),
+                        _ => Expiry::PxAt(time),
+                    };
+                    (None, Some(expiry))
+                }
+                "LEASE" => {
+                    if remaining == 0 {
+                        return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+                    }
+                    if set.lease.is_some() {
+                        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+                    }
+                    remaining -= 1;
+                    set.lease = Some(command::read_number(src).await?);
+                    (None, None)
+                }
+                _ => return Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+            };
+            // Each kind of option may only be given once.
+            if condition.is_some() {
+                if set.condition.is_some() {
+                    return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+                }
+                set.condition = condition;
+            }
+            if expiry.is_some() {
+                if set.expiry.is_some() {
+                    return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+                }
+                set.expiry = expiry;
+            }
+        }
+        Ok(set)
     }
 
     pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
-        buf.write_u8(2).await?;
+        let mut options: Vec<String> = Vec::new();
+        match self.condition {
+            Some(Condition::Nx) => options.push("NX".to_string()),
+            Some(Condition::Xx) => options.push("XX".to_string()),
+            None => {}
+        }
+        if self.get {
+            options.push("GET".to_string());
+        }
+        match self.expiry {
+            Some(Expiry::Ex(secs)) => options.extend(["EX".to_string(), secs.to_string()]),
+            Some(Expiry::Px(millis)) => options.extend(["PX".to_string(), millis.to_string()]),
+            Some(Expiry::ExAt(secs)) => options.extend(["EXAT".to_string(), secs.to_string()]),
+            Some(Expiry::PxAt(millis)) => options.extend(["PXAT".to_string(), millis.to_string()]),
+            Some(Expiry::KeepTtl) => options.push("KEEPTTL".to_string()),
+            None => {}
+        }
+        if let Some(lease) = self.lease {
+            options.extend(["LEASE".to_string(), lease.to_string()]);
+        }
+        command::write_count(buf, 2 + options.len()).await?;
         message::write_string(buf, &self.key).await?;
         message::write_bytes(buf, &self.value).await?;
+        for option in options {
+            message::write_string(buf, &option).await?;
+        }
         Ok(())
     }
 }
+
+/// Returns an error message if `value` can't be stored.
+pub(crate) fn validate(value: &[u8]) -> Option<Message> {
+    match Value::parse(value) {
+        Err(_) => Some(Message::Err("invalid value".to_string())),
+        Ok(Value::Json(text)) if json::parse(text).is_err() => {
+            Some(Message::Err("invalid json".to_string()))
+        }
+        Ok(_) => None,
+    }
+}
@@ -0,0 +1,49 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, set},
+    message,
+    pubsub::Event,
+};
+
+#[derive(Debug)]
+pub struct SetNx {
+    pub key: String,
+    pub value: Vec<u8>,
+}
+
+impl SetNx {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if let Some(err) = set::validate(&self.value) {
+            return Ok(err);
+        }
+        db.update(self.key, |slot| {
+            if slot.is_occupied() {
+                return Ok(Message::Int(0));
+            }
+            slot.insert(self.value);
+            slot.notify(Event::Set);
+            Ok(Message::Int(1))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<SetNx> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let value = message::read_bytes(src).await?;
+        Ok(SetNx { key, value })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_bytes(buf, &self.value).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, append},
+    message,
+    pubsub::Event,
+    value::Value,
+};
+
+/// The longest string `SETRANGE` will create, in bytes.
+const MAX_LEN: usize = 512 * 1024 * 1024;
+
+/// Overwrites part of a string starting at a byte offset, padding it with zero bytes if it's
+/// too short.
+#[derive(Debug)]
+pub struct SetRange {
+    pub key: String,
+    pub offset: usize,
+    pub value: String,
+}
+
+impl SetRange {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let end = self.offset.saturating_add(self.value.len());
+        if end > MAX_LEN {
+            return Ok(Message::Err("string exceeds maximum size".to_string()));
+        }
+        db.update(self.key, |slot| {
+            if let Some(err) = append::ensure_string(slot)? {
+                return Ok(err);
+            }
+            let Some(val) = slot.get() else {
+                if self.value.is_empty() {
+                    return Ok(Message::Int(0));
+                }
+                let mut string = vec![0u8; self.offset];
+                string.extend_from_slice(self.value.as_bytes());
+                let string = String::from_utf8(string).unwrap();
+                slot.insert(Value::String(&string).into_vec());
+                slot.notify(Event::Set);
+                return Ok(Message::Int(end as i64));
+            };
+            // The offsets are in bytes, so make sure they don't split a character.
+            let Value::String(string) = Value::parse(val)? else {
+                unreachable!();
+            };
+            let boundary = |i: usize| i >= string.len() || string.is_char_boundary(i);
+            if !boundary(self.offset) || !boundary(end) {
+                return Ok(Message::Err("offset splits a character".to_string()));
+            }
+            if self.value.is_empty() {
+                return Ok(Message::Int(string.len() as i64));
+            }
+            let val = slot.get_mut().unwrap();
+            // The stored value starts with its type.
+            if val.len() < end + 1 {
+                val.resize(end + 1, 0);
+            }
+            val[self.offset + 1..end + 1].copy_from_slice(self.value.as_bytes());
+            let len = val.len() - 1;
+            slot.notify(Event::Set);
+            Ok(Message::Int(len as i64))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<SetRange> {
+        let count = command::read_count(src).await?;
+        if count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let offset = command::read_number(src).await?;
+        let value = message::read_string(src).await?;
+        Ok(SetRange { key, offset, value })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(3).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.offset.to_string()).await?;
+        message::write_string(buf, &self.value).await?;
+        Ok(())
+    }
+}
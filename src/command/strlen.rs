@@ -0,0 +1,43 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::Value,
+};
+
+#[derive(Debug)]
+pub struct StrLen {
+    pub key: String,
+}
+
+impl StrLen {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Some(val) = db.get(&self.key) else {
+            return Ok(Message::Int(0));
+        };
+        match Value::parse(&val)? {
+            Value::String(string) => Ok(Message::Int(string.len() as i64)),
+            Value::Int(int) => Ok(Message::Int(int.to_string().len() as i64)),
+            _ => Ok(Message::Err("value is not a string".to_string())),
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<StrLen> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        Ok(StrLen { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
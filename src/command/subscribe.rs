@@ -0,0 +1,36 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+};
+
+/// Switches the connection into push mode, and sends it every message published to the channels.
+#[derive(Debug)]
+pub struct Subscribe {
+    pub channels: Vec<String>,
+}
+
+impl Subscribe {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Subscribe> {
+        let count = command::read_count(src).await?;
+        if count == 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut channels = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            channels.push(message::read_string(src).await?);
+        }
+        Ok(Subscribe { channels })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.channels.len()).await?;
+        for channel in &self.channels {
+            message::write_string(buf, channel).await?;
+        }
+        Ok(())
+    }
+}
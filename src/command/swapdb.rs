@@ -0,0 +1,34 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+};
+
+/// Swaps two logical databases, so connections using one see the other's keys.
+#[derive(Debug)]
+pub struct SwapDb {
+    pub first: usize,
+    pub second: usize,
+}
+
+impl SwapDb {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<SwapDb> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let first = command::read_number(src).await?;
+        let second = command::read_number(src).await?;
+        Ok(SwapDb { first, second })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.first.to_string()).await?;
+        message::write_string(buf, &self.second.to_string()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::Value,
+};
+
+/// Rate limits actions on `key` with the generic cell rate algorithm, allowing `rate` actions
+/// per `period` seconds, with bursts of up to `max_burst` more, and counting this one as
+/// `quantity` actions.
+///
+/// Replies with whether the action is allowed (1 or 0), the limit (`max_burst + 1`), how many
+/// more actions would be allowed right now, how many milliseconds until this one would be
+/// allowed (or -1 if it was, or never could be), and how many milliseconds until the limit
+/// resets completely.
+///
+/// The key holds just the theoretical arrival time of the next action, in microseconds, and
+/// expires once the limit has reset.
+#[derive(Debug)]
+pub struct Throttle {
+    pub key: String,
+    pub max_burst: u64,
+    pub rate: u64,
+    pub period: u64,
+    pub quantity: u64,
+}
+
+impl Throttle {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let now = command::now_millis() as i128 * 1000;
+        let emission_interval = (self.period as i128 * 1_000_000 / self.rate as i128).max(1);
+        let limit = self.max_burst as i128 + 1;
+        let (Some(tolerance), Some(increment)) = (
+            emission_interval.checked_mul(limit),
+            emission_interval.checked_mul(self.quantity as i128),
+        ) else {
+            return Ok(Message::Err("throttle is too large".to_string()));
+        };
+        db.update(self.key, |slot| {
+            let tat = match slot.get().map(|val| Value::parse(val)) {
+                None => now,
+                Some(Ok(Value::String(tat))) => match tat.parse::<i128>() {
+                    Ok(tat) => tat.max(now),
+                    Err(_) => return Ok(Message::Err("value is not a throttle".to_string())),
+                },
+                Some(_) => return Ok(Message::Err("value is not a throttle".to_string())),
+            };
+            let Some(new_tat) = tat.checked_add(increment) else {
+                return Ok(Message::Err("throttle is too large".to_string()));
+            };
+            let allow_at = new_tat - tolerance;
+            let (allowed, retry_after, ttl) = if now < allow_at {
+                // Too many actions would be needed to ever fit within the burst.
+                let retry_after = if increment <= tolerance {
+                    allow_at - now
+                } else {
+                    -1
+                };
+                (false, retry_after, tat - now)
+            } else {
+                slot.insert(Value::String(&new_tat.to_string()).into_vec());
+                slot.set_expiry(Some(millis(new_tat)));
+                (true, -1, new_tat - now)
+            };
+            let remaining = ((tolerance - ttl) / emission_interval).max(0);
+            let retry_after = if retry_after < 0 {
+                -1
+            } else {
+                int(millis(retry_after).into())
+            };
+            Ok(Message::Array(vec![
+                Message::Int(allowed as i64),
+                Message::Int(int(limit)),
+                Message::Int(int(remaining)),
+                Message::Int(retry_after),
+                Message::Int(int(millis(ttl).into())),
+            ]))
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Throttle> {
+        let count = command::read_count(src).await?;
+        if count != 4 && count != 5 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let max_burst = command::read_number(src).await?;
+        let rate: u64 = command::read_number(src).await?;
+        let period: u64 = command::read_number(src).await?;
+        let quantity = if count == 5 {
+            command::read_number(src).await?
+        } else {
+            1
+        };
+        if rate == 0 || period == 0 {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        Ok(Throttle {
+            key,
+            max_burst,
+            rate,
+            period,
+            quantity,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        let with_quantity = self.quantity != 1;
+        buf.write_u8(if with_quantity { 5 } else { 4 }).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.max_burst.to_string()).await?;
+        message::write_string(buf, &self.rate.to_string()).await?;
+        message::write_string(buf, &self.period.to_string()).await?;
+        if with_quantity {
+            message::write_string(buf, &self.quantity.to_string()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts microseconds to milliseconds, rounding up.
+fn millis(micros: i128) -> u64 {
+    u64::try_from(micros.max(0))
+        .unwrap_or(u64::MAX)
+        .div_ceil(1000)
+}
+
+/// Clamps a number to fit in an INT reply.
+fn int(n: i128) -> i64 {
+    i64::try_from(n).unwrap_or(if n < 0 { i64::MIN } else { i64::MAX })
+}
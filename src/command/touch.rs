@@ -0,0 +1,41 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+#[derive(Debug)]
+pub struct Touch {
+    pub keys: Vec<String>,
+}
+
+impl Touch {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let existing = self.keys.iter().filter(|key| db.touch(key)).count();
+        Ok(Message::Int(existing as i64))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Touch> {
+        let count = command::read_count(src).await?;
+        if count == 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut keys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            keys.push(message::read_string(src).await?);
+        }
+        Ok(Touch { keys })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.keys.len()).await?;
+        for key in &self.keys {
+            message::write_string(buf, key).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::timeseries::{self, TimeSeries},
+};
+
+#[derive(Debug)]
+pub struct TsAdd {
+    pub key: String,
+    /// Uses the server's current time when `None`.
+    pub timestamp: Option<u64>,
+    pub value: f64,
+}
+
+impl TsAdd {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let timestamp = self.timestamp.unwrap_or_else(command::now_millis);
+        add_sample(&db, self.key, timestamp, self.value)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<TsAdd> {
+        let count = command::read_count(src).await?;
+        if count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let timestamp = read_timestamp(src).await?;
+        let value = command::read_number(src).await?;
+        Ok(TsAdd {
+            key,
+            timestamp,
+            value,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(3).await?;
+        message::write_string(buf, &self.key).await?;
+        write_timestamp(buf, self.timestamp).await?;
+        message::write_string(buf, &self.value.to_string()).await?;
+        Ok(())
+    }
+}
+
+/// Adds a sample to the series at `key`, creating it if needed, then feeds any closed buckets
+/// into the destinations of its compaction rules.
+///
+/// The caller must hold the exclusive lock if the series has rules, so the destinations can't
+/// be written in between.
+pub(crate) fn add_sample(
+    db: &Db,
+    key: String,
+    timestamp: u64,
+    value: f64,
+) -> crate::Result<Message> {
+    let mut compacted = Vec::new();
+    let reply = append(db, key, timestamp, value, true, &mut compacted)?;
+    // Destinations which have been deleted or replaced since the rule was created are skipped.
+    while let Some((dest, timestamp, value)) = compacted.pop() {
+        append(db, dest, timestamp, value, false, &mut compacted)?;
+    }
+    Ok(reply)
+}
+
+fn append(
+    db: &Db,
+    key: String,
+    timestamp: u64,
+    value: f64,
+    create: bool,
+    compacted: &mut Vec<(String, u64, f64)>,
+) -> crate::Result<Message> {
+    db.update(key, |slot| {
+        let mut series = match slot.get() {
+            Some(val) => match TimeSeries::decode(val)? {
+                Some(series) => series,
+                None => return Ok(Message::Err("value is not a time series".to_string())),
+            },
+            None if create => TimeSeries::default(),
+            None => return Ok(Message::Null),
+        };
+        match series.add(timestamp, value) {
+            Ok(samples) => compacted.extend(samples),
+            Err(timeseries::Error::OutOfOrder) => {
+                return Ok(Message::Err(
+                    "timestamp must be newer than the latest sample".to_string(),
+                ));
+            }
+        }
+        slot.insert(series.encode());
+        Ok(Message::Int(timestamp as i64))
+    })
+}
+
+/// Whether the series at `key` has compaction rules, so adding to it writes other keys too.
+pub(crate) fn has_rules(db: &Db, key: &str) -> bool {
+    db.get(key).is_some_and(|val| timeseries::has_rules(&val))
+}
+
+/// Reads a timestamp argument, where `*` means the server's current time. Timestamps must fit
+/// in an INT reply.
+pub(crate) async fn read_timestamp(src: &mut Cursor<&[u8]>) -> crate::Result<Option<u64>> {
+    let arg = message::read_string(src).await?;
+    if arg == "*" {
+        return Ok(None);
+    }
+    match arg.parse::<i64>() {
+        Ok(timestamp) if timestamp >= 0 => Ok(Some(timestamp as u64)),
+        _ => Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+    }
+}
+
+pub(crate) async fn write_timestamp<W: AsyncWriteExt + Unpin>(
+    buf: &mut W,
+    timestamp: Option<u64>,
+) -> crate::Result<()> {
+    match timestamp {
+        Some(timestamp) => message::write_string(buf, &timestamp.to_string()).await,
+        None => message::write_string(buf, "*").await,
+    }
+}
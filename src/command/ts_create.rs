@@ -0,0 +1,63 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::timeseries::TimeSeries,
+};
+
+#[derive(Debug)]
+pub struct TsCreate {
+    pub key: String,
+    /// Keeps samples forever when `None`.
+    pub retention: Option<u64>,
+}
+
+impl TsCreate {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        db.update(self.key, |slot| {
+            if slot.is_occupied() {
+                return Ok(Message::Err("key already exists".to_string()));
+            }
+            slot.insert(TimeSeries::new(self.retention.unwrap_or(0)).encode());
+            Ok(Message::Ok)
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<TsCreate> {
+        let count = command::read_count(src).await?;
+        if count != 1 && count != 3 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let retention = if count == 3 {
+            let option = message::read_string(src).await?;
+            if !option.eq_ignore_ascii_case("RETENTION") {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+            Some(command::read_number(src).await?)
+        } else {
+            None
+        };
+        Ok(TsCreate { key, retention })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        match self.retention {
+            Some(retention) => {
+                buf.write_u8(3).await?;
+                message::write_string(buf, &self.key).await?;
+                message::write_string(buf, "RETENTION").await?;
+                message::write_string(buf, &retention.to_string()).await?;
+            }
+            None => {
+                buf.write_u8(1).await?;
+                message::write_string(buf, &self.key).await?;
+            }
+        }
+        Ok(())
+    }
+}
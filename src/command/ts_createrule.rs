@@ -0,0 +1,86 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, ts_range},
+    message,
+    value::timeseries::{self, Aggregation, Rule, TimeSeries},
+};
+
+#[derive(Debug)]
+pub struct TsCreateRule {
+    pub source: String,
+    pub dest: String,
+    pub aggregation: Aggregation,
+    /// The width of each bucket in milliseconds.
+    pub bucket: u64,
+}
+
+impl TsCreateRule {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if self.source == self.dest {
+            return Ok(Message::Err(
+                "source and destination must differ".to_string(),
+            ));
+        }
+        match db.get(&self.dest) {
+            Some(val) if TimeSeries::decode(val.as_ref())?.is_some() => {}
+            _ => {
+                return Ok(Message::Err("destination is not a time series".to_string()));
+            }
+        }
+        db.update(self.source, |slot| {
+            let Some(val) = slot.get() else {
+                return Ok(Message::Null);
+            };
+            let Some(mut series) = TimeSeries::decode(val)? else {
+                return Ok(Message::Err("value is not a time series".to_string()));
+            };
+            if series.rules.iter().any(|rule| rule.dest == self.dest) {
+                return Ok(Message::Err(
+                    "a rule for this destination already exists".to_string(),
+                ));
+            }
+            if series.rules.len() == timeseries::MAX_RULES {
+                return Ok(Message::Err("too many rules".to_string()));
+            }
+            series
+                .rules
+                .push(Rule::new(self.dest, self.aggregation, self.bucket));
+            slot.insert(series.encode());
+            Ok(Message::Ok)
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<TsCreateRule> {
+        let count = command::read_count(src).await?;
+        if count != 5 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let source = message::read_string(src).await?;
+        let dest = message::read_string(src).await?;
+        let option = message::read_string(src).await?;
+        if !option.eq_ignore_ascii_case("AGGREGATION") {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        let (aggregation, bucket) = ts_range::read_aggregation(src).await?;
+        Ok(TsCreateRule {
+            source,
+            dest,
+            aggregation,
+            bucket,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(5).await?;
+        message::write_string(buf, &self.source).await?;
+        message::write_string(buf, &self.dest).await?;
+        message::write_string(buf, "AGGREGATION").await?;
+        message::write_string(buf, self.aggregation.name()).await?;
+        message::write_string(buf, &self.bucket.to_string()).await?;
+        Ok(())
+    }
+}
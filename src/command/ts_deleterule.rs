@@ -0,0 +1,53 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::timeseries::TimeSeries,
+};
+
+#[derive(Debug)]
+pub struct TsDeleteRule {
+    pub source: String,
+    pub dest: String,
+}
+
+impl TsDeleteRule {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        db.update(self.source, |slot| {
+            let Some(val) = slot.get() else {
+                return Ok(Message::Null);
+            };
+            let Some(mut series) = TimeSeries::decode(val)? else {
+                return Ok(Message::Err("value is not a time series".to_string()));
+            };
+            let len = series.rules.len();
+            series.rules.retain(|rule| rule.dest != self.dest);
+            if series.rules.len() == len {
+                return Ok(Message::Null);
+            }
+            slot.insert(series.encode());
+            Ok(Message::Ok)
+        })
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<TsDeleteRule> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let source = message::read_string(src).await?;
+        let dest = message::read_string(src).await?;
+        Ok(TsDeleteRule { source, dest })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.source).await?;
+        message::write_string(buf, &self.dest).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, ts_add},
+    message,
+};
+
+#[derive(Debug)]
+pub struct TsMAdd {
+    pub samples: Vec<TsSample>,
+}
+
+#[derive(Debug)]
+pub struct TsSample {
+    pub key: String,
+    /// Uses the server's current time when `None`.
+    pub timestamp: Option<u64>,
+    pub value: f64,
+}
+
+impl TsMAdd {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let now = command::now_millis();
+        let mut replies = Vec::with_capacity(self.samples.len());
+        for sample in self.samples {
+            let timestamp = sample.timestamp.unwrap_or(now);
+            replies.push(ts_add::add_sample(
+                &db,
+                sample.key,
+                timestamp,
+                sample.value,
+            )?);
+        }
+        Ok(Message::Array(replies))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<TsMAdd> {
+        let count = command::read_count(src).await?;
+        if count == 0 || count % 3 != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut samples = Vec::with_capacity(count as usize / 3);
+        for _ in 0..count / 3 {
+            let key = message::read_string(src).await?;
+            let timestamp = ts_add::read_timestamp(src).await?;
+            let value = command::read_number(src).await?;
+            samples.push(TsSample {
+                key,
+                timestamp,
+                value,
+            });
+        }
+        Ok(TsMAdd { samples })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, 3 * self.samples.len()).await?;
+        for sample in &self.samples {
+            message::write_string(buf, &sample.key).await?;
+            ts_add::write_timestamp(buf, sample.timestamp).await?;
+            message::write_string(buf, &sample.value.to_string()).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::timeseries::{self, Aggregation, TimeSeries},
+};
+
+#[derive(Debug)]
+pub struct TsRange {
+    pub key: String,
+    /// Starts from the oldest sample when `None`.
+    pub from: Option<u64>,
+    /// Ends at the newest sample when `None`.
+    pub to: Option<u64>,
+    /// Aggregates samples into buckets of the given width in milliseconds.
+    pub aggregation: Option<(Aggregation, u64)>,
+}
+
+impl TsRange {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Some(val) = db.get(&self.key) else {
+            return Ok(Message::Null);
+        };
+        let Some(series) = TimeSeries::decode(val.as_ref())? else {
+            return Ok(Message::Err("value is not a time series".to_string()));
+        };
+        drop(val);
+        let mut samples = series.range(self.from.unwrap_or(0), self.to.unwrap_or(u64::MAX));
+        if let Some((aggregation, bucket)) = self.aggregation {
+            samples = timeseries::aggregate(&samples, aggregation, bucket);
+        }
+        Ok(Message::Array(
+            samples
+                .into_iter()
+                .map(|(timestamp, value)| {
+                    Message::Array(vec![Message::Int(timestamp as i64), Message::Float(value)])
+                })
+                .collect(),
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<TsRange> {
+        let count = command::read_count(src).await?;
+        if count != 3 && count != 6 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let from = read_bound(src, "-").await?;
+        let to = read_bound(src, "+").await?;
+        let aggregation = if count == 6 {
+            let option = message::read_string(src).await?;
+            if !option.eq_ignore_ascii_case("AGGREGATION") {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+            Some(read_aggregation(src).await?)
+        } else {
+            None
+        };
+        Ok(TsRange {
+            key,
+            from,
+            to,
+            aggregation,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(if self.aggregation.is_some() { 6 } else { 3 })
+            .await?;
+        message::write_string(buf, &self.key).await?;
+        write_bound(buf, self.from, "-").await?;
+        write_bound(buf, self.to, "+").await?;
+        if let Some((aggregation, bucket)) = self.aggregation {
+            message::write_string(buf, "AGGREGATION").await?;
+            message::write_string(buf, aggregation.name()).await?;
+            message::write_string(buf, &bucket.to_string()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads an aggregation type followed by a non-zero bucket width.
+pub(crate) async fn read_aggregation(src: &mut Cursor<&[u8]>) -> crate::Result<(Aggregation, u64)> {
+    let name = message::read_string(src).await?;
+    let Some(aggregation) = Aggregation::parse(&name) else {
+        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+    };
+    let bucket = command::read_number(src).await?;
+    if bucket == 0 {
+        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+    }
+    Ok((aggregation, bucket))
+}
+
+async fn read_bound(src: &mut Cursor<&[u8]>, open: &str) -> crate::Result<Option<u64>> {
+    let arg = message::read_string(src).await?;
+    if arg == open {
+        return Ok(None);
+    }
+    match arg.parse() {
+        Ok(timestamp) => Ok(Some(timestamp)),
+        Err(_) => Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+    }
+}
+
+async fn write_bound<W: AsyncWriteExt + Unpin>(
+    buf: &mut W,
+    bound: Option<u64>,
+    open: &str,
+) -> crate::Result<()> {
+    match bound {
+        Some(timestamp) => message::write_string(buf, &timestamp.to_string()).await,
+        None => message::write_string(buf, open).await,
+    }
+}
@@ -0,0 +1,48 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+/// Returns the seconds until a key expires, -1 if it never does, or -2 if it doesn't exist.
+#[derive(Debug)]
+pub struct Ttl {
+    pub key: String,
+}
+
+impl Ttl {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(Message::Int(remaining(&db, &self.key).map_or_else(
+            |ttl| ttl,
+            |millis| (millis as i64 + 500) / 1000,
+        )))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Ttl> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        Ok(Ttl { key })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.key).await?;
+        Ok(())
+    }
+}
+
+/// The milliseconds until `key` expires, or the reply for keys without an expiry.
+pub(crate) fn remaining(db: &Db, key: &str) -> Result<u64, i64> {
+    match db.expires_at(key) {
+        Some(Some(deadline)) => Ok(deadline.saturating_sub(command::now_millis())),
+        Some(None) => Err(-1),
+        None => Err(-2),
+    }
+}
@@ -0,0 +1,220 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Command, Db, Message,
+    command::{self, Error},
+    message,
+    value::Value,
+};
+
+// TXN COMPARE-COUNT [TARGET KEY OP OPERAND]... THEN-COUNT [COMMAND]... ELSE-COUNT [COMMAND]...
+//
+// Targets are VALUE, VERSION (the number of writes since the key was created), CREATE (the
+// version the key was created at), MOD (the key's current version) and EXISTS (1 or 0).
+// Operators are =, !=, < and >. Each command is encoded as it would be in a message, without
+// the leading message variant, and can't itself be a TXN.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Value(String),
+    Version(u64),
+    Create(u64),
+    Mod(u64),
+    Exists(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+}
+
+impl Comparison {
+    pub fn parse(op: &str) -> Option<Comparison> {
+        match op {
+            "=" => Some(Comparison::Equal),
+            "!=" => Some(Comparison::NotEqual),
+            "<" => Some(Comparison::Less),
+            ">" => Some(Comparison::Greater),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::Greater => ">",
+        }
+    }
+
+    fn holds<T: Ord + ?Sized>(&self, a: &T, b: &T) -> bool {
+        match self {
+            Comparison::Equal => a == b,
+            Comparison::NotEqual => a != b,
+            Comparison::Less => a < b,
+            Comparison::Greater => a > b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compare {
+    pub key: String,
+    pub comparison: Comparison,
+    pub target: Target,
+}
+
+impl Compare {
+    fn holds(&self, db: &Db) -> crate::Result<bool> {
+        let metadata = db.metadata(&self.key);
+        let c = self.comparison;
+        Ok(match &self.target {
+            Target::Value(expected) => {
+                // Missing keys, and those holding something other than text, never compare.
+                let Some(val) = db.get(&self.key) else {
+                    return Ok(false);
+                };
+                match Value::parse(&val)? {
+                    Value::Int(int) => c.holds(int.to_string().as_str(), expected),
+                    Value::String(string) | Value::Json(string) => c.holds(string, expected),
+                    _ => false,
+                }
+            }
+            Target::Version(expected) => c.holds(&metadata.map_or(0, |m| m.writes), expected),
+            Target::Create(expected) => c.holds(&metadata.map_or(0, |m| m.created), expected),
+            Target::Mod(expected) => c.holds(&metadata.map_or(0, |m| m.version), expected),
+            Target::Exists(expected) => c.holds(&metadata.is_some(), expected),
+        })
+    }
+
+    async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Compare> {
+        let target = message::read_string(src).await?.to_ascii_uppercase();
+        let key = message::read_string(src).await?;
+        let Some(comparison) = Comparison::parse(&message::read_string(src).await?) else {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        };
+        let target = match target.as_str() {
+            "VALUE" => Target::Value(message::read_string(src).await?),
+            "VERSION" => Target::Version(command::read_number(src).await?),
+            "CREATE" => Target::Create(command::read_number(src).await?),
+            "MOD" => Target::Mod(command::read_number(src).await?),
+            "EXISTS" => match message::read_string(src).await?.as_str() {
+                "1" => Target::Exists(true),
+                "0" => Target::Exists(false),
+                _ => return Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+            },
+            _ => return Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+        };
+        Ok(Compare {
+            key,
+            comparison,
+            target,
+        })
+    }
+
+    async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        let (target, operand) = match &self.target {
+            Target::Value(value) => ("VALUE", value.clone()),
+            Target::Version(version) => ("VERSION", version.to_string()),
+            Target::Create(version) => ("CREATE", version.to_string()),
+            Target::Mod(version) => ("MOD", version.to_string()),
+            Target::Exists(exists) => ("EXISTS", (*exists as u8).to_string()),
+        };
+        message::write_string(buf, target).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, self.comparison.symbol()).await?;
+        message::write_string(buf, &operand).await?;
+        Ok(())
+    }
+}
+
+/// Runs one of two lists of commands, depending on whether every comparison holds.
+///
+/// Replies with the branch which ran, `then` or `else`, and the result of each of its commands.
+#[derive(Debug)]
+pub struct Txn {
+    pub compares: Vec<Compare>,
+    pub success: Vec<Command>,
+    pub failure: Vec<Command>,
+}
+
+impl Txn {
+    /// Runs under the database's exclusive lock, so the commands see exactly what was compared.
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let mut succeeded = true;
+        for compare in &self.compares {
+            if !compare.holds(&db)? {
+                succeeded = false;
+                break;
+            }
+        }
+        let (branch, commands) = if succeeded {
+            ("then", self.success)
+        } else {
+            ("else", self.failure)
+        };
+        let results = commands
+            .into_iter()
+            .map(|command| command.execute_in_batch(db.clone()))
+            .collect();
+        Ok(Message::Array(vec![
+            Message::Text(branch.to_string()),
+            Message::Array(results),
+        ]))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Txn> {
+        let count = command::read_count(src).await? as usize;
+        let mut compares = Vec::new();
+        for _ in 0..command::read_number::<usize>(src).await? {
+            compares.push(Compare::parse(src).await?);
+        }
+        let success = read_commands(src).await?;
+        let failure = read_commands(src).await?;
+        if count != 3 + 4 * compares.len() + success.len() + failure.len() {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(Txn {
+            compares,
+            success,
+            failure,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        let count = 3 + 4 * self.compares.len() + self.success.len() + self.failure.len();
+        command::write_count(buf, count).await?;
+        message::write_string(buf, &self.compares.len().to_string()).await?;
+        for compare in &self.compares {
+            compare.write(buf).await?;
+        }
+        for commands in [&self.success, &self.failure] {
+            message::write_string(buf, &commands.len().to_string()).await?;
+            for command in commands {
+                let mut bytes = Vec::new();
+                Box::pin(command.write(&mut bytes)).await?;
+                message::write_bytes(buf, &bytes).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn read_commands(src: &mut Cursor<&[u8]>) -> crate::Result<Vec<Command>> {
+    let mut commands = Vec::new();
+    for _ in 0..command::read_number::<usize>(src).await? {
+        let bytes = message::read_bytes(src).await?;
+        // Nested transactions would let a single message recurse thousands of times.
+        if bytes.first() == Some(&(command::Variant::Txn as u8)) {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        commands.push(Box::pin(Command::parse(&mut Cursor::new(&bytes[..]))).await?);
+    }
+    Ok(commands)
+}
@@ -0,0 +1,44 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+#[derive(Debug)]
+pub struct Unlink {
+    pub keys: Vec<String>,
+}
+
+impl Unlink {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        // The keys are gone as soon as this returns, but freeing their values can wait.
+        let removed: Vec<Vec<u8>> = self.keys.iter().filter_map(|key| db.remove(key)).collect();
+        let count = removed.len();
+        db.free_later(removed);
+        Ok(Message::Int(count as i64))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Unlink> {
+        let count = command::read_count(src).await?;
+        if count == 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut keys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            keys.push(message::read_string(src).await?);
+        }
+        Ok(Unlink { keys })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.keys.len()).await?;
+        for key in &self.keys {
+            message::write_string(buf, key).await?;
+        }
+        Ok(())
+    }
+}
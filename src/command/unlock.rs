@@ -0,0 +1,45 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, lock},
+    message,
+};
+
+/// Releases the lock at `key` if `owner` holds it, replying with 1 if it did and 0 otherwise.
+#[derive(Debug)]
+pub struct Unlock {
+    pub key: String,
+    pub owner: String,
+}
+
+impl Unlock {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        Ok(db.update(self.key, |slot| match slot.get() {
+            Some(val) if lock::is_owner(val, &self.owner) => {
+                slot.remove();
+                Message::Int(1)
+            }
+            _ => Message::Int(0),
+        }))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Unlock> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let owner = message::read_string(src).await?;
+        Ok(Unlock { key, owner })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &self.owner).await?;
+        Ok(())
+    }
+}
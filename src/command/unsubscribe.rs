@@ -0,0 +1,30 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{command, message};
+
+/// Stops receiving messages published to the channels, or to every channel if none are given.
+#[derive(Debug)]
+pub struct Unsubscribe {
+    pub channels: Vec<String>,
+}
+
+impl Unsubscribe {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Unsubscribe> {
+        let count = command::read_count(src).await?;
+        let mut channels = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            channels.push(message::read_string(src).await?);
+        }
+        Ok(Unsubscribe { channels })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.channels.len()).await?;
+        for channel in &self.channels {
+            message::write_string(buf, channel).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::command::{self, Error};
+
+/// Stops watching every key.
+#[derive(Debug)]
+pub struct Unwatch;
+
+impl Unwatch {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Unwatch> {
+        let count = command::read_count(src).await?;
+        if count != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(Unwatch)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(0).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::command::{self, Error};
+
+/// Closes every stream opened by `WATCHREV`.
+#[derive(Debug)]
+pub struct UnwatchRev;
+
+impl UnwatchRev {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<UnwatchRev> {
+        let count = command::read_count(src).await?;
+        if count != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(UnwatchRev)
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(0).await?;
+        Ok(())
+    }
+}
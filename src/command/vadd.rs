@@ -0,0 +1,57 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+    value::vector,
+};
+
+#[derive(Debug)]
+pub struct VAdd {
+    pub key: String,
+    pub vector: Vec<f32>,
+}
+
+impl VAdd {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if let Some(dim) = db
+            .vectors
+            .mismatched_dimension(&self.key, self.vector.len())
+        {
+            return Ok(Message::Err(format!(
+                "vector must have {dim} dimensions to be indexed"
+            )));
+        }
+        db.insert(self.key, vector::encode(&self.vector));
+        Ok(Message::Ok)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<VAdd> {
+        let count = command::read_count(src).await?;
+        if count != 2 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let key = message::read_string(src).await?;
+        let vector = read_vector(src).await?;
+        Ok(VAdd { key, vector })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(2).await?;
+        message::write_string(buf, &self.key).await?;
+        message::write_string(buf, &vector::to_string(&self.vector)).await?;
+        Ok(())
+    }
+}
+
+/// Reads a vector written as a comma separated list of components.
+pub(crate) async fn read_vector(src: &mut Cursor<&[u8]>) -> crate::Result<Vec<f32>> {
+    let arg = message::read_string(src).await?;
+    match vector::parse(&arg) {
+        Some(vector) => Ok(vector),
+        None => Err(crate::Error::ParseCommand(Error::InvalidArgument)),
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    io::Cursor,
+    sync::{Arc, PoisonError},
+};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    index::vector::{Algorithm, VectorIndex},
+    message,
+    value::vector::{self, Metric},
+};
+
+#[derive(Debug)]
+pub struct VCreate {
+    pub index: String,
+    /// Every vector stored under a key starting with this prefix is indexed.
+    pub prefix: String,
+    pub dim: usize,
+    pub metric: Metric,
+    pub algorithm: Algorithm,
+}
+
+impl VCreate {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let index = VectorIndex::new(self.prefix.clone(), self.dim, self.metric, self.algorithm);
+        let Some(index) = db.vectors.create(self.index, index) else {
+            return Ok(Message::Err("index already exists".to_string()));
+        };
+        db.backfill(&self.prefix, |key, val| {
+            if let Ok(Some(vector)) = vector::decode(val) {
+                index
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .upsert(key, vector);
+            }
+        });
+        Ok(Message::Ok)
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<VCreate> {
+        let count = command::read_count(src).await?;
+        if count < 4 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let index = message::read_string(src).await?;
+        let prefix = message::read_string(src).await?;
+        let dim = command::read_number(src).await?;
+        if dim == 0 {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        }
+        let Some(metric) = Metric::parse(&message::read_string(src).await?) else {
+            return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+        };
+        let mut algorithm = Algorithm::Flat;
+        let mut remaining = count - 4;
+        if remaining > 0 {
+            let name = message::read_string(src).await?;
+            remaining -= 1;
+            if name.eq_ignore_ascii_case("HNSW") {
+                let mut m = Algorithm::DEFAULT_M;
+                let mut ef_construction = Algorithm::DEFAULT_EF_CONSTRUCTION;
+                while remaining >= 2 {
+                    let option = message::read_string(src).await?;
+                    let value: usize = command::read_number(src).await?;
+                    remaining -= 2;
+                    if value == 0 {
+                        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+                    }
+                    if option.eq_ignore_ascii_case("M") {
+                        m = value;
+                    } else if option.eq_ignore_ascii_case("EF_CONSTRUCTION") {
+                        ef_construction = value;
+                    } else {
+                        return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+                    }
+                }
+                algorithm = Algorithm::Hnsw { m, ef_construction };
+            } else if !name.eq_ignore_ascii_case("FLAT") {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+        }
+        if remaining != 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        Ok(VCreate {
+            index,
+            prefix,
+            dim,
+            metric,
+            algorithm,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        let count = match self.algorithm {
+            Algorithm::Flat => 5,
+            Algorithm::Hnsw { .. } => 9,
+        };
+        buf.write_u8(count).await?;
+        message::write_string(buf, &self.index).await?;
+        message::write_string(buf, &self.prefix).await?;
+        message::write_string(buf, &self.dim.to_string()).await?;
+        message::write_string(buf, self.metric.name()).await?;
+        match self.algorithm {
+            Algorithm::Flat => message::write_string(buf, "FLAT").await?,
+            Algorithm::Hnsw { m, ef_construction } => {
+                message::write_string(buf, "HNSW").await?;
+                message::write_string(buf, "M").await?;
+                message::write_string(buf, &m.to_string()).await?;
+                message::write_string(buf, "EF_CONSTRUCTION").await?;
+                message::write_string(buf, &ef_construction.to_string()).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use std::{io::Cursor, sync::Arc};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error},
+    message,
+};
+
+#[derive(Debug)]
+pub struct VDrop {
+    pub index: String,
+}
+
+impl VDrop {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        if db.vectors.drop(&self.index) {
+            Ok(Message::Ok)
+        } else {
+            Ok(Message::Null)
+        }
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<VDrop> {
+        let count = command::read_count(src).await?;
+        if count != 1 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let index = message::read_string(src).await?;
+        Ok(VDrop { index })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(1).await?;
+        message::write_string(buf, &self.index).await?;
+        Ok(())
+    }
+}
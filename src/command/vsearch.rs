@@ -0,0 +1,83 @@
+use std::{
+    io::Cursor,
+    sync::{Arc, PoisonError},
+};
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    Db, Message,
+    command::{self, Error, vadd},
+    message,
+    value::vector,
+};
+
+#[derive(Debug)]
+pub struct VSearch {
+    pub index: String,
+    pub k: usize,
+    pub vector: Vec<f32>,
+    /// The candidate list size for HNSW indexes, ignored by flat indexes.
+    pub ef: Option<usize>,
+}
+
+impl VSearch {
+    pub fn perform(self, db: Arc<Db>) -> crate::Result<Message> {
+        let Some(index) = db.vectors.get(&self.index) else {
+            return Ok(Message::Err("no such index".to_string()));
+        };
+        let index = index.lock().unwrap_or_else(PoisonError::into_inner);
+        if index.dim != self.vector.len() {
+            return Ok(Message::Err(format!(
+                "query must have {} dimensions",
+                index.dim
+            )));
+        }
+        let nearest = index.search(&self.vector, self.k, self.ef);
+        Ok(Message::Array(
+            nearest
+                .into_iter()
+                .map(|(key, distance)| {
+                    Message::Array(vec![Message::Text(key), Message::Float(distance.into())])
+                })
+                .collect(),
+        ))
+    }
+
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<VSearch> {
+        let count = command::read_count(src).await?;
+        if count != 3 && count != 5 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let index = message::read_string(src).await?;
+        let k = command::read_number(src).await?;
+        let vector = vadd::read_vector(src).await?;
+        let ef = if count == 5 {
+            let option = message::read_string(src).await?;
+            if !option.eq_ignore_ascii_case("EF") {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+            Some(command::read_number(src).await?)
+        } else {
+            None
+        };
+        Ok(VSearch {
+            index,
+            k,
+            vector,
+            ef,
+        })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        buf.write_u8(if self.ef.is_some() { 5 } else { 3 }).await?;
+        message::write_string(buf, &self.index).await?;
+        message::write_string(buf, &self.k.to_string()).await?;
+        message::write_string(buf, &vector::to_string(&self.vector)).await?;
+        if let Some(ef) = self.ef {
+            message::write_string(buf, "EF").await?;
+            message::write_string(buf, &ef.to_string()).await?;
+        }
+        Ok(())
+    }
+}
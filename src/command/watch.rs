@@ -0,0 +1,36 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    message,
+};
+
+/// Makes the next `EXEC` fail if any of the keys change before it.
+#[derive(Debug)]
+pub struct Watch {
+    pub keys: Vec<String>,
+}
+
+impl Watch {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<Watch> {
+        let count = command::read_count(src).await?;
+        if count == 0 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        let mut keys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            keys.push(message::read_string(src).await?);
+        }
+        Ok(Watch { keys })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        command::write_count(buf, self.keys.len()).await?;
+        for key in &self.keys {
+            message::write_string(buf, key).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::Cursor;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    command::{self, Error},
+    history::Watched,
+    message,
+};
+
+/// Streams every change to a key, or to keys with a prefix, starting from a revision.
+///
+/// Without `FROM`, only changes made after the stream is opened are sent, so a client which
+/// reconnects should resume from one past the last revision it saw.
+#[derive(Debug)]
+pub struct WatchRev {
+    pub watched: Watched,
+    pub from: Option<u64>,
+}
+
+impl WatchRev {
+    pub async fn parse(src: &mut Cursor<&[u8]>) -> crate::Result<WatchRev> {
+        let count = command::read_count(src).await?;
+        if count == 0 || count > 4 {
+            return Err(crate::Error::ParseCommand(Error::WrongNumberArguments));
+        }
+        // Watching a prefix takes one more argument than watching a key.
+        let watched = if count.is_multiple_of(2) {
+            if !message::read_string(src)
+                .await?
+                .eq_ignore_ascii_case("PREFIX")
+            {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+            Watched::Prefix(message::read_string(src).await?)
+        } else {
+            Watched::Key(message::read_string(src).await?)
+        };
+        let from = if count > 2 {
+            if !message::read_string(src)
+                .await?
+                .eq_ignore_ascii_case("FROM")
+            {
+                return Err(crate::Error::ParseCommand(Error::InvalidArgument));
+            }
+            Some(command::read_number(src).await?)
+        } else {
+            None
+        };
+        Ok(WatchRev { watched, from })
+    }
+
+    pub async fn write<W: AsyncWriteExt + Unpin>(&self, buf: &mut W) -> crate::Result<()> {
+        let count = match &self.watched {
+            Watched::Key(_) => 1,
+            Watched::Prefix(_) => 2,
+        } + if self.from.is_some() { 2 } else { 0 };
+        buf.write_u8(count).await?;
+        match &self.watched {
+            Watched::Key(key) => message::write_string(buf, key).await?,
+            Watched::Prefix(prefix) => {
+                message::write_string(buf, "PREFIX").await?;
+                message::write_string(buf, prefix).await?;
+            }
+        }
+        if let Some(from) = self.from {
+            message::write_string(buf, "FROM").await?;
+            message::write_string(buf, &from.to_string()).await?;
+        }
+        Ok(())
+    }
+}
@@ -46,15 +46,23 @@ impl Connection {
         }
     }
 
+    /// Waits for the peer to close the connection, keeping anything it sends in the meantime to
+    /// be read later.
+    pub async fn closed(&mut self) -> Result<()> {
+        while 0 != self.stream.read_buf(&mut self.buffer).await? {}
+        Ok(())
+    }
+
     async fn parse_message(&mut self) -> Result<Option<Message>> {
         let mut buf = Cursor::new(&self.buffer[..]);
 
         if message::is_complete(&mut buf) {
             let len = buf.position() as usize;
             buf.set_position(0);
-            let message = Message::parse(&mut buf).await?;
+            let message = Message::parse(&mut buf).await;
+            // Skip past invalid messages too, so the next one can still be read.
             self.buffer.advance(len);
-            Ok(Some(message))
+            Ok(Some(message?))
         } else {
             Ok(None)
         }
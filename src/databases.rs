@@ -0,0 +1,107 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard},
+};
+
+use crate::{Db, namespace::Namespace};
+
+/// How many logical databases a server has by default.
+pub const DEFAULT_DATABASES: usize = 16;
+
+/// Makes each database, numbered or namespaced, so they're all set up alike.
+type Make = Box<dyn Fn() -> Arc<Db> + Send + Sync>;
+
+/// A server's numbered logical databases, and the namespaces created at runtime, each a
+/// separate keyspace with its own history, channels and everything else a [`Db`] holds.
+///
+/// Connections use database 0 until they `SELECT` another or use a namespace.
+pub struct Databases {
+    dbs: RwLock<Vec<Arc<Db>>>,
+    namespaces: RwLock<HashMap<String, Arc<Namespace>>>,
+    make: Make,
+}
+
+impl Databases {
+    /// Creates `count` databases, each made by `make`, which also makes namespaces' databases.
+    pub fn new(count: usize, make: impl Fn() -> Arc<Db> + Send + Sync + 'static) -> Databases {
+        Databases {
+            dbs: RwLock::new((0..count.max(1)).map(|_| make()).collect()),
+            namespaces: RwLock::default(),
+            make: Box::new(make),
+        }
+    }
+
+    /// The database numbered `index`, if there is one.
+    pub fn get(&self, index: usize) -> Option<Arc<Db>> {
+        self.read().get(index).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Every database, in order.
+    pub fn all(&self) -> Vec<Arc<Db>> {
+        self.read().clone()
+    }
+
+    /// Swaps the databases numbered `a` and `b`, so connections using one now see the other's
+    /// keys. Returns false if either doesn't exist.
+    pub fn swap(&self, a: usize, b: usize) -> bool {
+        let mut dbs = self.dbs.write().unwrap_or_else(PoisonError::into_inner);
+        if a >= dbs.len() || b >= dbs.len() {
+            return false;
+        }
+        dbs.swap(a, b);
+        true
+    }
+
+    /// Creates an empty namespace, returning `None` if one already has the name.
+    pub fn create_namespace(
+        &self,
+        name: String,
+        password: Option<String>,
+        read_password: Option<String>,
+    ) -> Option<Arc<Namespace>> {
+        let mut namespaces = self
+            .namespaces
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if namespaces.contains_key(&name) {
+            return None;
+        }
+        let namespace = Arc::new(Namespace {
+            db: (self.make)(),
+            password,
+            read_password,
+        });
+        namespaces.insert(name, namespace.clone());
+        Some(namespace)
+    }
+
+    pub fn namespace(&self, name: &str) -> Option<Arc<Namespace>> {
+        self.namespaces
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned()
+    }
+
+    /// Removes a namespace, whose keys are freed once no connection is using them. Returns
+    /// false if it doesn't exist.
+    pub fn drop_namespace(&self, name: &str) -> bool {
+        self.namespaces
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name)
+            .is_some()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Vec<Arc<Db>>> {
+        self.dbs.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
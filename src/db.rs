@@ -0,0 +1,651 @@
+use std::{
+    hash::{BuildHasher, BuildHasherDefault, DefaultHasher},
+    ops::Bound,
+    sync::{
+        PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+use crossbeam_skiplist::SkipSet;
+use dashmap::{DashMap, mapref::entry::Entry, mapref::one::MappedRef};
+
+use crate::{
+    blocking::Blocked,
+    command::{self, End, now_millis},
+    history::History,
+    index::{fulltext::TextIndexes, vector::VectorIndexes},
+    lease::Leases,
+    namespace::{Eviction, Limits},
+    pubsub::{Channels, Event},
+    schedule::Schedule,
+};
+
+/// Removed values smaller than this, in bytes, aren't worth handing to another thread to free.
+const LAZY_FREE_THRESHOLD: usize = 64 * 1024;
+
+/// Orders keys for [`Db::scan`], independently of where they live in the map.
+fn scan_hash(key: &str) -> u64 {
+    BuildHasherDefault::<DefaultHasher>::default().hash_one(key)
+}
+
+/// The keyspace, along with the secondary indexes kept in sync with it.
+///
+/// All writes go through [`Db::update`] (or the helpers built on it), so that every index sees
+/// every change.
+#[derive(Default)]
+pub struct Db {
+    entries: DashMap<String, Record>,
+    /// Held shared by commands touching a single key, and exclusively by those touching
+    /// several, so that the latter appear atomic to everyone else.
+    lock: RwLock<()>,
+    /// Every key in order, if the keyspace was created ordered.
+    order: Option<SkipSet<String>>,
+    /// Every key, ordered by [`scan_hash`] for [`Db::scan`].
+    scan_order: SkipSet<(u64, String)>,
+    /// Every change, which gives each write its version.
+    pub history: History,
+    /// Every key with an expiry, ordered by when it expires.
+    deadlines: SkipSet<(u64, String)>,
+    pub vectors: VectorIndexes,
+    pub text: TextIndexes,
+    pub channels: Channels,
+    pub blocked: Blocked,
+    pub schedule: Schedule,
+    pub leases: Leases,
+    pub limits: Limits,
+    /// The bytes taken by every key and value, including expired ones not yet removed.
+    used: AtomicUsize,
+    /// The latest fencing token handed out by `LOCK`.
+    fencing: AtomicU64,
+}
+
+/// A value along with its metadata.
+pub struct Record {
+    value: Vec<u8>,
+    /// When the key expires, in milliseconds since the unix epoch.
+    expires_at: Option<u64>,
+    /// Changes whenever the value or its expiry does.
+    version: u64,
+    /// The version the key had when it was created.
+    created: u64,
+    /// The number of times the key has been written since it was created.
+    writes: u64,
+    /// When the key was last read or written, in milliseconds since the unix epoch.
+    accessed: AtomicU64,
+}
+
+/// What's known about a key besides its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    pub version: u64,
+    pub created: u64,
+    pub writes: u64,
+    pub expires_at: Option<u64>,
+}
+
+impl Record {
+    fn is_live(&self, now: u64) -> bool {
+        self.expires_at.is_none_or(|at| at > now)
+    }
+}
+
+/// The value stored at a key, as seen from within [`Db::update`].
+pub struct Slot {
+    value: Option<Vec<u8>>,
+    expires_at: Option<u64>,
+    version: u64,
+    changed: bool,
+    event: Option<Event>,
+}
+
+impl Slot {
+    pub fn get(&self) -> Option<&Vec<u8>> {
+        self.value.as_ref()
+    }
+
+    /// Borrows the value for in-place modification, which counts as a write.
+    pub fn get_mut(&mut self) -> Option<&mut Vec<u8>> {
+        self.changed = true;
+        self.value.as_mut()
+    }
+
+    /// Replaces the value, keeping any expiry.
+    pub fn insert(&mut self, value: Vec<u8>) -> Option<Vec<u8>> {
+        self.changed = true;
+        self.value.replace(value)
+    }
+
+    pub fn remove(&mut self) -> Option<Vec<u8>> {
+        self.changed = true;
+        self.expires_at = None;
+        self.value.take()
+    }
+
+    /// The key's version before this update, or zero if it didn't exist.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// When the key expires, in milliseconds since the unix epoch.
+    pub fn expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
+
+    /// Sets when the key expires, which has no effect unless the slot is occupied.
+    pub fn set_expiry(&mut self, expires_at: Option<u64>) {
+        self.expires_at = expires_at;
+    }
+
+    pub fn is_occupied(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// Publishes a keyspace event for the key, along with its new value, once the update is done.
+    pub fn notify(&mut self, event: Event) {
+        self.event = Some(event);
+    }
+}
+
+impl Db {
+    pub fn new() -> Db {
+        Db::default()
+    }
+
+    /// Creates a database which also keeps its keys in order, so they can be queried by range.
+    pub fn ordered() -> Db {
+        Db {
+            order: Some(SkipSet::new()),
+            ..Db::default()
+        }
+    }
+
+    pub fn is_ordered(&self) -> bool {
+        self.order.is_some()
+    }
+
+    pub fn shared(&self) -> RwLockReadGuard<'_, ()> {
+        self.lock.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn exclusive(&self) -> RwLockWriteGuard<'_, ()> {
+        self.lock.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reads the value at `key`, which counts as accessing it.
+    pub fn get(&self, key: &str) -> Option<MappedRef<'_, String, Record, Vec<u8>>> {
+        let now = now_millis();
+        self.entries.get(key).filter(|r| r.is_live(now)).map(|r| {
+            r.accessed.store(now, Ordering::Relaxed);
+            r.map(|r| &r.value)
+        })
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        let now = now_millis();
+        self.entries.get(key).is_some_and(|r| r.is_live(now))
+    }
+
+    /// Marks `key` as accessed now, returning whether it exists.
+    pub fn touch(&self, key: &str) -> bool {
+        let now = now_millis();
+        self.entries
+            .get(key)
+            .filter(|r| r.is_live(now))
+            .map(|r| r.accessed.store(now, Ordering::Relaxed))
+            .is_some()
+    }
+
+    /// When `key` expires, or `None` if it doesn't exist.
+    pub fn expires_at(&self, key: &str) -> Option<Option<u64>> {
+        let now = now_millis();
+        self.entries
+            .get(key)
+            .filter(|r| r.is_live(now))
+            .map(|r| r.expires_at)
+    }
+
+    /// The version of `key`, or zero if it doesn't exist.
+    ///
+    /// Versions are taken from a counter shared by every key, so a key never gets the same
+    /// version twice, even if it's removed and written again.
+    pub fn version(&self, key: &str) -> u64 {
+        let now = now_millis();
+        self.entries
+            .get(key)
+            .filter(|r| r.is_live(now))
+            .map_or(0, |r| r.version)
+    }
+
+    pub fn metadata(&self, key: &str) -> Option<Metadata> {
+        let now = now_millis();
+        self.entries
+            .get(key)
+            .filter(|r| r.is_live(now))
+            .map(|r| Metadata {
+                version: r.version,
+                created: r.created,
+                writes: r.writes,
+                expires_at: r.expires_at,
+            })
+    }
+
+    pub fn insert(&self, key: String, value: Vec<u8>) -> Option<Vec<u8>> {
+        self.update(key, |slot| slot.insert(value))
+    }
+
+    /// Removes a key, publishing a `del` event if it existed.
+    pub fn remove(&self, key: &str) -> Option<Vec<u8>> {
+        self.update(key.to_string(), |slot| {
+            let removed = slot.remove();
+            if removed.is_some() {
+                slot.notify(Event::Del);
+            }
+            removed
+        })
+    }
+
+    /// Atomically reads and optionally modifies the value at `key`. No other writer can touch
+    /// the key until `f` returns.
+    ///
+    /// Expired keys are seen as vacant, and removed unless `f` replaces them. Anyone blocked on
+    /// the key is woken when it's written, removed or found to have expired. Keys created
+    /// without an expiry are given the default ttl, if there is one.
+    pub fn update<T>(&self, key: String, f: impl FnOnce(&mut Slot) -> T) -> T {
+        match self.entries.entry(key) {
+            Entry::Occupied(mut e) => {
+                let old_size = e.key().len() + e.get().value.len();
+                let record = e.get_mut();
+                let old_deadline = record.expires_at;
+                let now = now_millis();
+                let live = record.is_live(now);
+                let mut slot = if live {
+                    Slot {
+                        value: Some(std::mem::take(&mut record.value)),
+                        expires_at: record.expires_at,
+                        version: record.version,
+                        changed: false,
+                        event: None,
+                    }
+                } else {
+                    Slot {
+                        value: None,
+                        expires_at: None,
+                        version: 0,
+                        changed: false,
+                        event: None,
+                    }
+                };
+                if !live {
+                    self.history.record(e.key(), None);
+                    self.channels.notify(Event::Expired, e.key(), None);
+                    self.blocked.wake(e.key());
+                    self.leases.detach(e.key());
+                }
+                let result = f(&mut slot);
+                if !live && slot.value.is_some() && slot.expires_at.is_none() {
+                    slot.expires_at = self.default_expiry(now);
+                }
+                let new_deadline = slot.value.as_ref().and(slot.expires_at);
+                self.reschedule(e.key(), old_deadline, new_deadline);
+                let modified = slot.changed || !live || old_deadline != slot.expires_at;
+                match slot.value {
+                    Some(value) => {
+                        self.resize(old_size, e.key().len() + value.len());
+                        let record = e.get();
+                        let (version, created, writes) = match (live, modified) {
+                            (true, false) => (record.version, record.created, record.writes),
+                            (true, true) => {
+                                let version = self.history.record(e.key(), Some(&value));
+                                (version, record.created, record.writes + 1)
+                            }
+                            (false, _) => {
+                                let version = self.history.record(e.key(), Some(&value));
+                                (version, version, 1)
+                            }
+                        };
+                        *e.get_mut() = Record {
+                            value,
+                            expires_at: slot.expires_at,
+                            version,
+                            created,
+                            writes,
+                            accessed: AtomicU64::new(now),
+                        };
+                        if slot.changed {
+                            self.written(e.key(), &e.get().value);
+                            self.blocked.wake(e.key());
+                        }
+                        if let Some(event) = slot.event {
+                            self.channels.notify(event, e.key(), Some(&e.get().value));
+                        }
+                    }
+                    None => {
+                        self.resize(old_size, 0);
+                        if live {
+                            self.history.record(e.key(), None);
+                            self.blocked.wake(e.key());
+                        }
+                        self.removed(e.key());
+                        if let Some(event) = slot.event {
+                            self.channels.notify(event, e.key(), None);
+                        }
+                        e.remove();
+                    }
+                }
+                result
+            }
+            Entry::Vacant(e) => {
+                let mut slot = Slot {
+                    value: None,
+                    expires_at: None,
+                    version: 0,
+                    changed: false,
+                    event: None,
+                };
+                let result = f(&mut slot);
+                if let Some(value) = slot.value {
+                    let now = now_millis();
+                    if slot.expires_at.is_none() {
+                        slot.expires_at = self.default_expiry(now);
+                    }
+                    self.resize(0, e.key().len() + value.len());
+                    self.reschedule(e.key(), None, slot.expires_at);
+                    let version = self.history.record(e.key(), Some(&value));
+                    let e = e.insert_entry(Record {
+                        value,
+                        expires_at: slot.expires_at,
+                        version,
+                        created: version,
+                        writes: 1,
+                        accessed: AtomicU64::new(now),
+                    });
+                    self.written(e.key(), &e.get().value);
+                    self.blocked.wake(e.key());
+                    if let Some(event) = slot.event {
+                        self.channels.notify(event, e.key(), Some(&e.get().value));
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// How many live keys there are.
+    pub fn len(&self) -> usize {
+        let now = now_millis();
+        self.entries.iter().filter(|e| e.is_live(now)).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the keys starting with `prefix`, in no particular order.
+    pub fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let now = now_millis();
+        self.entries
+            .iter()
+            .filter(|e| e.key().starts_with(prefix) && e.is_live(now))
+            .map(|e| e.key().clone())
+            .collect()
+    }
+
+    /// Calls `f` with each key starting with `prefix` and its value, to fill a newly created
+    /// index.
+    ///
+    /// The index is already receiving writes, so only values stored before it was created need
+    /// adding. Each is passed to `f` while its key is held, so a concurrent write can't be
+    /// overwritten in the index with a stale value.
+    pub fn backfill(&self, prefix: &str, mut f: impl FnMut(&str, &[u8])) {
+        for key in self.keys_with_prefix(prefix) {
+            if let Some(val) = self.get(&key) {
+                f(&key, &val);
+            }
+        }
+    }
+
+    /// Returns the keys accepted by `filter`, in no particular order.
+    pub fn keys(&self, filter: impl Fn(&str, &[u8]) -> bool) -> Vec<String> {
+        let now = now_millis();
+        self.entries
+            .iter()
+            .filter(|e| e.is_live(now) && filter(e.key(), &e.value))
+            .map(|e| e.key().clone())
+            .collect()
+    }
+
+    /// Returns up to `count` keys accepted by `filter`, starting from `cursor`, along with the
+    /// cursor to continue from, which is zero once every key has been visited.
+    ///
+    /// Keys are visited in the order of a fixed hash rather than the map's layout, so a key
+    /// present for the whole scan is returned at least once however the map is resized or
+    /// written in between calls. Keys sharing a hash are returned together, so a batch may hold
+    /// a few more than `count`.
+    pub fn scan(
+        &self,
+        cursor: u64,
+        count: usize,
+        filter: impl Fn(&str, &[u8]) -> bool,
+    ) -> (u64, Vec<String>) {
+        let count = count.max(1);
+        let now = now_millis();
+        let mut keys = Vec::new();
+        let mut last = None;
+        for e in self.scan_order.range((cursor, String::new())..) {
+            let (hash, key) = e.value();
+            if keys.len() >= count && last != Some(*hash) {
+                return (*hash, keys);
+            }
+            // A key may be removed from the map just before it's removed from the scan order.
+            let Some(record) = self.entries.get(key) else {
+                continue;
+            };
+            if record.is_live(now) && filter(key, &record.value) {
+                keys.push(key.clone());
+                last = Some(*hash);
+            }
+        }
+        (0, keys)
+    }
+
+    /// Returns up to `limit` keys and values with keys between `start` and `end`, in order (or
+    /// reverse order), or `None` if the keyspace isn't ordered.
+    pub fn range(
+        &self,
+        start: Bound<&str>,
+        end: Bound<&str>,
+        reverse: bool,
+        limit: usize,
+    ) -> Option<Vec<(String, Vec<u8>)>> {
+        let order = self.order.as_ref()?;
+        let empty = match (start, end) {
+            (Bound::Included(s), Bound::Included(e)) => s > e,
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) => {
+                s >= e
+            }
+            _ => false,
+        };
+        if empty {
+            return Some(Vec::new());
+        }
+        let keys = order.range::<str, _>((start, end));
+        let keys: Box<dyn Iterator<Item = _>> = if reverse {
+            Box::new(keys.rev())
+        } else {
+            Box::new(keys)
+        };
+        Some(
+            keys
+                // A key may be removed from the map just before it's removed from the order.
+                .filter_map(|key| {
+                    let value = self.get(key.value())?.clone();
+                    Some((key.value().clone(), value))
+                })
+                .take(limit)
+                .collect(),
+        )
+    }
+
+    /// Removes up to `limit` keys which have expired, returning how many were removed.
+    ///
+    /// Expired keys are already invisible to readers, but hold on to their memory (and their
+    /// place in any index) until they're removed, either here or by the next write to them.
+    pub fn remove_expired(&self, limit: usize) -> usize {
+        let now = now_millis();
+        let due: Vec<String> = self
+            .deadlines
+            .iter()
+            .take_while(|e| e.value().0 <= now)
+            .take(limit)
+            .map(|e| e.value().1.clone())
+            .collect();
+        let count = due.len();
+        for key in due {
+            // Seeing the key as vacant and leaving it that way removes it, unless it has been
+            // given a later expiry since.
+            self.update(key, |_| ());
+        }
+        count
+    }
+
+    /// Pushes up to `limit` scheduled jobs which are due onto the end of their ready lists,
+    /// returning how many were due.
+    ///
+    /// A job whose key holds something other than a list by the time it's due is dropped.
+    pub fn release_jobs(&self, limit: usize) -> usize {
+        let due = self.schedule.take_due(now_millis(), limit);
+        let count = due.len();
+        for job in due {
+            // An error means the stored list is corrupt, which the job can do nothing about.
+            let _ = command::push(self, job.key, vec![job.payload], End::Right);
+        }
+        count
+    }
+
+    /// Returns a fencing token higher than any handed out before.
+    pub fn fencing_token(&self) -> u64 {
+        self.fencing.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Revokes the lease, removing the keys attached to it and returning how many there were,
+    /// or `None` if the lease doesn't exist.
+    ///
+    /// The caller must hold the exclusive lock, so the keys are removed all at once.
+    pub fn revoke_lease(&self, id: u64) -> Option<usize> {
+        let keys = self.leases.revoke(id)?;
+        Some(keys.iter().filter(|key| self.remove(key).is_some()).count())
+    }
+
+    /// Revokes every lease which has run out, returning how many keys were removed.
+    ///
+    /// The caller must hold the exclusive lock.
+    pub fn revoke_expired_leases(&self) -> usize {
+        let keys = self.leases.revoke_expired(now_millis());
+        keys.iter().filter(|key| self.remove(key).is_some()).count()
+    }
+
+    /// The bytes taken by every key and value.
+    pub fn used_memory(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Evicts keys by the eviction policy until the memory limit is no longer exceeded,
+    /// returning how many were evicted.
+    pub fn evict(&self) -> usize {
+        if !self.limits.exceeded(self.used_memory()) {
+            return 0;
+        }
+        let candidates: Vec<String> = match self.limits.eviction() {
+            Eviction::NoEviction => return 0,
+            Eviction::VolatileTtl => self.deadlines.iter().map(|e| e.value().1.clone()).collect(),
+            Eviction::AllKeysOldest => self.keys_by(|record| record.version),
+            Eviction::AllKeysLru => self.keys_by(|record| record.accessed.load(Ordering::Relaxed)),
+        };
+        let mut evicted = 0;
+        for key in candidates {
+            if !self.limits.exceeded(self.used_memory()) {
+                break;
+            }
+            self.update(key, |slot| {
+                if slot.remove().is_some() {
+                    slot.notify(Event::Evicted);
+                    evicted += 1;
+                }
+            });
+        }
+        evicted
+    }
+
+    /// Every key, ordered by `f` of its record.
+    fn keys_by(&self, f: impl Fn(&Record) -> u64) -> Vec<String> {
+        let mut keys: Vec<(u64, String)> = self
+            .entries
+            .iter()
+            .map(|e| (f(e.value()), e.key().clone()))
+            .collect();
+        keys.sort_unstable();
+        keys.into_iter().map(|(_, key)| key).collect()
+    }
+
+    /// Drops removed values, freeing large ones on a blocking thread rather than the caller's.
+    pub fn free_later(&self, values: Vec<Vec<u8>>) {
+        let size: usize = values.iter().map(Vec::len).sum();
+        if size < LAZY_FREE_THRESHOLD {
+            return;
+        }
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn_blocking(move || drop(values));
+        }
+    }
+
+    fn default_expiry(&self, now: u64) -> Option<u64> {
+        self.limits.default_ttl().map(|ttl| now.saturating_add(ttl))
+    }
+
+    fn resize(&self, old: usize, new: usize) {
+        if new > old {
+            self.used.fetch_add(new - old, Ordering::Relaxed);
+        } else {
+            self.used.fetch_sub(old - new, Ordering::Relaxed);
+        }
+    }
+
+    // Called with the key's shard locked, like the hooks below.
+    fn reschedule(&self, key: &str, old: Option<u64>, new: Option<u64>) {
+        if old == new {
+            return;
+        }
+        if let Some(old) = old {
+            self.deadlines.remove(&(old, key.to_string()));
+        }
+        if let Some(new) = new {
+            self.deadlines.insert((new, key.to_string()));
+        }
+    }
+
+    // Called with the key's shard locked, so index updates are ordered the same way as writes.
+    fn written(&self, key: &str, value: &[u8]) {
+        if let Some(order) = &self.order
+            && !order.contains(key)
+        {
+            order.insert(key.to_string());
+        }
+        let scanned = (scan_hash(key), key.to_string());
+        if !self.scan_order.contains(&scanned) {
+            self.scan_order.insert(scanned);
+        }
+        self.vectors.written(key, value);
+        self.text.written(key, value);
+    }
+
+    fn removed(&self, key: &str) {
+        if let Some(order) = &self.order {
+            order.remove(key);
+        }
+        self.scan_order.remove(&(scan_hash(key), key.to_string()));
+        self.vectors.removed(key);
+        self.text.removed(key);
+        self.leases.detach(key);
+    }
+}
@@ -0,0 +1,81 @@
+// Glob patterns, as used to match keys and channels:
+//
+// *        any run of characters, including none
+// ?        any single character
+// [abc]    any one of the listed characters
+// [^abc]   any character not listed
+// [a-z]    any character in the range
+// \x       the character x, even if it's special
+
+/// Whether the whole of `text` matches `pattern`.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the last `*` if the rest of the pattern fails to match.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => class(&pattern[p..], text[t]),
+            Some('\\') if p + 1 < pattern.len() => (pattern[p + 1] == text[t]).then_some(2),
+            Some(&c) => (c == text[t]).then_some(1),
+            None => None,
+        };
+        match (step, backtrack) {
+            (Some(len), _) => {
+                p += len;
+                t += 1;
+            }
+            (None, Some((star, start))) => {
+                // Let the `*` swallow one more character and try again.
+                p = star + 1;
+                t = start + 1;
+                backtrack = Some((star, start + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches `c` against the character class at the start of `pattern`, returning the length of
+/// the class if it matches.
+fn class(pattern: &[char], c: char) -> Option<usize> {
+    let mut i = 1;
+    let negated = pattern.get(i) == Some(&'^');
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        match pattern.get(i) {
+            // An unterminated class matches a literal `[`.
+            None => return (c == '[').then_some(1),
+            Some(']') if !first => break,
+            Some(&'\\') if i + 1 < pattern.len() => {
+                matched |= pattern[i + 1] == c;
+                i += 2;
+            }
+            Some(&low) => {
+                if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&h| h != ']')
+                {
+                    let high = pattern[i + 2];
+                    matched |= low <= c && c <= high;
+                    i += 3;
+                } else {
+                    matched |= low == c;
+                    i += 1;
+                }
+            }
+        }
+        first = false;
+    }
+    (matched != negated).then_some(i + 1)
+}
@@ -0,0 +1,163 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use tokio::sync::watch;
+
+/// How many changes are kept by default, for watchers resuming from an earlier revision.
+pub const DEFAULT_HISTORY: usize = 10_000;
+
+/// Every change made to the keyspace, each with a revision one higher than the last, of which
+/// the most recent are kept so watchers can catch up.
+pub struct History {
+    log: Mutex<Log>,
+    capacity: AtomicUsize,
+    /// Sent the latest revision whenever a change is recorded.
+    latest: watch::Sender<u64>,
+}
+
+#[derive(Default)]
+struct Log {
+    changes: VecDeque<Change>,
+    /// The latest revision given to a change.
+    revision: u64,
+    /// The latest revision no longer kept, or zero if nothing has been trimmed.
+    compacted: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub revision: u64,
+    pub key: String,
+    /// The key's new value, or `None` if it was removed.
+    pub value: Option<Vec<u8>>,
+}
+
+/// The keys a watcher is interested in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Watched {
+    Key(String),
+    Prefix(String),
+}
+
+impl Watched {
+    /// The key, or the prefix.
+    pub fn name(&self) -> &str {
+        match self {
+            Watched::Key(key) | Watched::Prefix(key) => key,
+        }
+    }
+
+    pub fn matches(&self, key: &str) -> bool {
+        match self {
+            Watched::Key(watched) => watched == key,
+            Watched::Prefix(prefix) => key.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+/// What a watcher sees next, after a given revision.
+#[derive(Debug)]
+pub enum Next {
+    /// The first change it's interested in.
+    Change(Change),
+    /// Nothing yet, so it should wait for revisions from this one.
+    Pending(u64),
+    /// Changes it hasn't seen were trimmed, the latest of them having this revision.
+    Compacted(u64),
+}
+
+impl Default for History {
+    fn default() -> History {
+        History {
+            log: Mutex::default(),
+            capacity: AtomicUsize::new(DEFAULT_HISTORY),
+            latest: watch::Sender::new(0),
+        }
+    }
+}
+
+impl History {
+    /// Gives a change the next revision, returning it.
+    ///
+    /// Called with the key's shard locked, so each key's changes are recorded in the order
+    /// they're made.
+    pub fn record(&self, key: &str, value: Option<&[u8]>) -> u64 {
+        let capacity = self.capacity();
+        let mut log = self.lock();
+        log.revision += 1;
+        let revision = log.revision;
+        log.changes.push_back(Change {
+            revision,
+            key: key.to_string(),
+            value: value.map(<[u8]>::to_vec),
+        });
+        log.trim(capacity);
+        drop(log);
+        self.latest.send_replace(revision);
+        revision
+    }
+
+    /// The revision given to the latest change.
+    pub fn revision(&self) -> u64 {
+        self.lock().revision
+    }
+
+    /// The latest revision which has been trimmed, so can no longer be watched from.
+    pub fn compacted(&self) -> u64 {
+        self.lock().compacted
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Changes how many changes are kept, trimming the oldest if there are now too many.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        self.lock().trim(capacity);
+    }
+
+    /// Finds the first change to the watched keys with a revision of at least `from`.
+    pub fn next(&self, watched: &Watched, from: u64) -> Next {
+        let log = self.lock();
+        if from <= log.compacted {
+            return Next::Compacted(log.compacted);
+        }
+        let start = log.changes.partition_point(|change| change.revision < from);
+        match log
+            .changes
+            .range(start..)
+            .find(|change| watched.matches(&change.key))
+        {
+            Some(change) => Next::Change(change.clone()),
+            None => Next::Pending(from.max(log.revision + 1)),
+        }
+    }
+
+    /// Returns a receiver which is told whenever a change is recorded.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.latest.subscribe()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Log> {
+        self.log.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Log {
+    fn trim(&mut self, capacity: usize) {
+        while self.changes.len() > capacity {
+            if let Some(change) = self.changes.pop_front() {
+                self.compacted = change.revision;
+            }
+        }
+        if capacity == 0 {
+            self.compacted = self.revision;
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod fulltext;
+pub mod vector;
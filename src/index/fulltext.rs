@@ -0,0 +1,403 @@
+// Queries are made up of terms, all of which must match:
+//
+// word             documents containing the word
+// "some words"     documents containing the words next to each other, in order
+// a | b            documents matching either side
+// (a | b) c        groups part of a query
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, PoisonError, RwLock},
+};
+
+use crate::value::Value;
+
+/// BM25 term frequency saturation.
+const K1: f64 = 1.2;
+/// BM25 document length normalisation.
+const B: f64 = 0.75;
+/// How deeply groups may be nested in a query, which bounds the recursion needed to parse it.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidQuery,
+}
+
+#[derive(Debug)]
+pub enum Query {
+    Term(String),
+    Phrase(Vec<String>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Query, Error> {
+        let mut parser = Parser {
+            chars: query.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        let query = parser.or()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.chars.len() {
+            return Err(Error::InvalidQuery);
+        }
+        Ok(query)
+    }
+
+    /// Every term which contributes to a document's score.
+    fn terms(&self, out: &mut Vec<String>) {
+        match self {
+            Query::Term(term) => out.push(term.clone()),
+            Query::Phrase(terms) => out.extend(terms.iter().cloned()),
+            Query::And(queries) | Query::Or(queries) => {
+                for query in queries {
+                    query.terms(out);
+                }
+            }
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// How many groups the parser is inside.
+    depth: usize,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn or(&mut self) -> Result<Query, Error> {
+        let mut queries = vec![self.and()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            queries.push(self.and()?);
+        }
+        Ok(if queries.len() == 1 {
+            queries.pop().unwrap()
+        } else {
+            Query::Or(queries)
+        })
+    }
+
+    fn and(&mut self) -> Result<Query, Error> {
+        let mut queries = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            queries.push(self.atom()?);
+        }
+        match queries.len() {
+            0 => Err(Error::InvalidQuery),
+            1 => Ok(queries.pop().unwrap()),
+            _ => Ok(Query::And(queries)),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Query, Error> {
+        match self.peek() {
+            Some('(') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(Error::InvalidQuery);
+                }
+                self.pos += 1;
+                self.depth += 1;
+                let query = self.or()?;
+                if self.peek() != Some(')') {
+                    return Err(Error::InvalidQuery);
+                }
+                self.pos += 1;
+                self.depth -= 1;
+                Ok(query)
+            }
+            Some('"') => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.chars.get(self.pos).is_some_and(|&c| c != '"') {
+                    self.pos += 1;
+                }
+                if self.pos == self.chars.len() {
+                    return Err(Error::InvalidQuery);
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                self.pos += 1;
+                let mut terms: Vec<String> = tokenize(&text).map(|(_, term)| term).collect();
+                match terms.len() {
+                    0 => Err(Error::InvalidQuery),
+                    1 => Ok(Query::Term(terms.pop().unwrap())),
+                    _ => Ok(Query::Phrase(terms)),
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while self
+                    .chars
+                    .get(self.pos)
+                    .is_some_and(|&c| !c.is_whitespace() && !matches!(c, '|' | '(' | ')' | '"'))
+                {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                let mut terms: Vec<Query> = tokenize(&text).map(|(_, t)| Query::Term(t)).collect();
+                match terms.len() {
+                    0 => Err(Error::InvalidQuery),
+                    1 => Ok(terms.pop().unwrap()),
+                    _ => Ok(Query::And(terms)),
+                }
+            }
+        }
+    }
+}
+
+/// Splits text into lowercase alphanumeric terms, along with their position.
+pub fn tokenize(text: &str) -> impl Iterator<Item = (u32, String)> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .enumerate()
+        .map(|(position, term)| (position as u32, term.to_lowercase()))
+}
+
+#[derive(Debug)]
+struct Document {
+    key: String,
+    /// The number of terms in the document.
+    len: u32,
+    terms: Vec<String>,
+}
+
+/// An inverted index over the strings stored under a key prefix.
+#[derive(Debug)]
+pub struct TextIndex {
+    pub prefix: String,
+    documents: Vec<Option<Document>>,
+    free: Vec<usize>,
+    ids: HashMap<String, usize>,
+    /// The positions of each term within each document containing it.
+    postings: HashMap<String, HashMap<usize, Vec<u32>>>,
+    total_len: u64,
+}
+
+impl TextIndex {
+    pub fn new(prefix: String) -> TextIndex {
+        TextIndex {
+            prefix,
+            documents: Vec::new(),
+            free: Vec::new(),
+            ids: HashMap::new(),
+            postings: HashMap::new(),
+            total_len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn upsert(&mut self, key: &str, text: &str) {
+        self.remove(key);
+        let mut positions: HashMap<String, Vec<u32>> = HashMap::new();
+        let mut len = 0;
+        for (position, term) in tokenize(text) {
+            positions.entry(term).or_default().push(position);
+            len = position + 1;
+        }
+        let id = match self.free.pop() {
+            Some(id) => id,
+            None => {
+                self.documents.push(None);
+                self.documents.len() - 1
+            }
+        };
+        let terms = positions.keys().cloned().collect();
+        for (term, positions) in positions {
+            self.postings.entry(term).or_default().insert(id, positions);
+        }
+        self.documents[id] = Some(Document {
+            key: key.to_string(),
+            len,
+            terms,
+        });
+        self.ids.insert(key.to_string(), id);
+        self.total_len += len as u64;
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        let Some(id) = self.ids.remove(key) else {
+            return;
+        };
+        let Some(document) = self.documents[id].take() else {
+            return;
+        };
+        for term in document.terms {
+            if let Some(postings) = self.postings.get_mut(&term) {
+                postings.remove(&id);
+                if postings.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        self.total_len -= document.len as u64;
+        self.free.push(id);
+    }
+
+    /// Returns up to `limit` keys matching `query`, ranked by BM25 score.
+    pub fn search(&self, query: &Query, limit: usize) -> Vec<(String, f64)> {
+        let matches = self.matches(query);
+        let mut terms = Vec::new();
+        query.terms(&mut terms);
+        terms.sort_unstable();
+        terms.dedup();
+
+        let count = self.ids.len() as f64;
+        let average_len = self.total_len as f64 / count.max(1.0);
+        let mut results: Vec<(String, f64)> = matches
+            .into_iter()
+            .filter_map(|id| {
+                let document = self.documents[id].as_ref()?;
+                let norm = K1 * (1.0 - B + B * document.len as f64 / average_len.max(1.0));
+                let score = terms
+                    .iter()
+                    .filter_map(|term| {
+                        let postings = self.postings.get(term)?;
+                        let frequency = postings.get(&id)?.len() as f64;
+                        let df = postings.len() as f64;
+                        let idf = ((count - df + 0.5) / (df + 0.5) + 1.0).ln();
+                        Some(idf * frequency * (K1 + 1.0) / (frequency + norm))
+                    })
+                    .sum();
+                Some((document.key.clone(), score))
+            })
+            .collect();
+        results.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        results.truncate(limit);
+        results
+    }
+
+    fn matches(&self, query: &Query) -> HashSet<usize> {
+        match query {
+            Query::Term(term) => self
+                .postings
+                .get(term)
+                .map(|postings| postings.keys().copied().collect())
+                .unwrap_or_default(),
+            Query::Phrase(terms) => {
+                let mut candidates = self.matches(&Query::Term(terms[0].clone()));
+                for term in &terms[1..] {
+                    let other = self.matches(&Query::Term(term.clone()));
+                    candidates.retain(|id| other.contains(id));
+                }
+                candidates.retain(|&id| self.contains_phrase(id, terms));
+                candidates
+            }
+            Query::And(queries) => {
+                let mut queries = queries.iter();
+                let mut candidates = match queries.next() {
+                    Some(query) => self.matches(query),
+                    None => return HashSet::new(),
+                };
+                for query in queries {
+                    if candidates.is_empty() {
+                        break;
+                    }
+                    let other = self.matches(query);
+                    candidates.retain(|id| other.contains(id));
+                }
+                candidates
+            }
+            Query::Or(queries) => queries.iter().flat_map(|q| self.matches(q)).collect(),
+        }
+    }
+
+    fn contains_phrase(&self, id: usize, terms: &[String]) -> bool {
+        let positions: Vec<&Vec<u32>> = terms
+            .iter()
+            .filter_map(|term| self.postings.get(term)?.get(&id))
+            .collect();
+        if positions.len() != terms.len() {
+            return false;
+        }
+        positions[0].iter().any(|&start| {
+            positions[1..]
+                .iter()
+                .enumerate()
+                .all(|(offset, p)| p.binary_search(&(start + offset as u32 + 1)).is_ok())
+        })
+    }
+}
+
+/// Every full-text index in a database, by name.
+#[derive(Default)]
+pub struct TextIndexes {
+    indexes: RwLock<HashMap<String, Arc<Mutex<TextIndex>>>>,
+}
+
+impl TextIndexes {
+    /// Registers a new, empty index, returning it unless an index with that name exists.
+    pub fn create(&self, name: String, index: TextIndex) -> Option<Arc<Mutex<TextIndex>>> {
+        let mut indexes = self.indexes.write().unwrap_or_else(PoisonError::into_inner);
+        if indexes.contains_key(&name) {
+            return None;
+        }
+        let index = Arc::new(Mutex::new(index));
+        indexes.insert(name, index.clone());
+        Some(index)
+    }
+
+    pub fn drop(&self, name: &str) -> bool {
+        self.indexes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name)
+            .is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Mutex<TextIndex>>> {
+        self.indexes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned()
+    }
+
+    pub(crate) fn written(&self, key: &str, value: &[u8]) {
+        let indexes = self.indexes.read().unwrap_or_else(PoisonError::into_inner);
+        for index in indexes.values() {
+            let mut index = index.lock().unwrap_or_else(PoisonError::into_inner);
+            if !key.starts_with(&index.prefix) {
+                continue;
+            }
+            match Value::parse(value) {
+                Ok(Value::String(text)) => index.upsert(key, text),
+                _ => index.remove(key),
+            }
+        }
+    }
+
+    pub(crate) fn removed(&self, key: &str) {
+        let indexes = self.indexes.read().unwrap_or_else(PoisonError::into_inner);
+        for index in indexes.values() {
+            let mut index = index.lock().unwrap_or_else(PoisonError::into_inner);
+            if key.starts_with(&index.prefix) {
+                index.remove(key);
+            }
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet},
+    sync::{Arc, Mutex, PoisonError, RwLock},
+};
+
+use crate::value::vector::{self, Metric};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Compares the query against every vector, always returning the exact nearest neighbours.
+    Flat,
+    /// Navigates a hierarchical navigable small world graph, trading exactness for speed.
+    Hnsw {
+        /// The number of neighbours each node links to on the upper layers (twice this on the
+        /// bottom layer).
+        m: usize,
+        /// The size of the candidate list used while inserting.
+        ef_construction: usize,
+    },
+}
+
+impl Algorithm {
+    pub const DEFAULT_M: usize = 16;
+    pub const DEFAULT_EF_CONSTRUCTION: usize = 200;
+}
+
+/// The size of the candidate list used while searching an HNSW graph, unless the query asks
+/// for more.
+const DEFAULT_EF_SEARCH: usize = 64;
+
+/// Indexes the vectors stored under a key prefix.
+#[derive(Debug)]
+pub struct VectorIndex {
+    pub prefix: String,
+    pub dim: usize,
+    pub metric: Metric,
+    pub algorithm: Algorithm,
+    nodes: Vec<Node>,
+    ids: HashMap<String, usize>,
+    /// The number of nodes which have been removed but are still part of the graph.
+    deleted: usize,
+    entry: Option<usize>,
+    rng: u64,
+}
+
+#[derive(Debug)]
+struct Node {
+    key: String,
+    vector: Vec<f32>,
+    /// Neighbours on each layer this node is part of, starting from the bottom.
+    neighbors: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    id: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.id.cmp(&other.id))
+    }
+}
+
+impl VectorIndex {
+    pub fn new(prefix: String, dim: usize, metric: Metric, algorithm: Algorithm) -> VectorIndex {
+        VectorIndex {
+            prefix,
+            dim,
+            metric,
+            algorithm,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            deleted: 0,
+            entry: None,
+            rng: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn upsert(&mut self, key: &str, vector: Vec<f32>) {
+        if vector.len() != self.dim {
+            self.remove(key);
+            return;
+        }
+        if let Some(&id) = self.ids.get(key) {
+            if self.nodes[id].vector == vector {
+                return;
+            }
+            self.remove(key);
+        }
+        self.insert(key.to_string(), vector);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        let Some(id) = self.ids.remove(key) else {
+            return;
+        };
+        match self.algorithm {
+            Algorithm::Flat => {
+                // Nothing links to nodes without a graph, so they can be swapped out directly.
+                self.nodes.swap_remove(id);
+                if let Some(moved) = self.nodes.get(id) {
+                    self.ids.insert(moved.key.clone(), id);
+                }
+            }
+            Algorithm::Hnsw { .. } => {
+                // Removed nodes are still used to navigate the graph until it's rebuilt.
+                self.nodes[id].deleted = true;
+                self.deleted += 1;
+                if self.deleted > 64 && self.deleted > self.ids.len() {
+                    self.rebuild();
+                }
+            }
+        }
+    }
+
+    /// Returns the keys of the `k` vectors nearest to `query`, along with their distance.
+    pub fn search(&self, query: &[f32], k: usize, ef: Option<usize>) -> Vec<(String, f32)> {
+        // There are never more results than vectors, however many are asked for.
+        let k = k.min(self.nodes.len());
+        if query.len() != self.dim || k == 0 {
+            return Vec::new();
+        }
+        let nearest = match self.algorithm {
+            Algorithm::Flat => {
+                let mut candidates: Vec<Candidate> = self
+                    .nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(id, node)| Candidate {
+                        distance: self.metric.distance(query, &node.vector),
+                        id,
+                    })
+                    .collect();
+                if candidates.len() > k {
+                    candidates.select_nth_unstable(k - 1);
+                    candidates.truncate(k);
+                }
+                candidates.sort_unstable();
+                candidates
+            }
+            Algorithm::Hnsw { .. } => {
+                let Some(mut entry) = self.entry else {
+                    return Vec::new();
+                };
+                for layer in (1..self.nodes[entry].neighbors.len()).rev() {
+                    entry = self.closest(query, entry, layer);
+                }
+                // Removed nodes take up room in the candidate list, so widen it to compensate.
+                let ef = ef
+                    .unwrap_or(DEFAULT_EF_SEARCH)
+                    .max(k)
+                    .saturating_add(self.deleted.min(k));
+                let mut candidates = self.search_layer(query, &[entry], ef, 0);
+                candidates.retain(|c| !self.nodes[c.id].deleted);
+                candidates.truncate(k);
+                candidates
+            }
+        };
+        nearest
+            .into_iter()
+            .map(|c| (self.nodes[c.id].key.clone(), c.distance))
+            .collect()
+    }
+
+    fn insert(&mut self, key: String, vector: Vec<f32>) {
+        let id = self.nodes.len();
+        self.ids.insert(key.clone(), id);
+        let Algorithm::Hnsw { m, ef_construction } = self.algorithm else {
+            self.nodes.push(Node {
+                key,
+                vector,
+                neighbors: Vec::new(),
+                deleted: false,
+            });
+            return;
+        };
+
+        let level = self.random_level(m);
+        self.nodes.push(Node {
+            key,
+            vector,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        let Some(mut entry) = self.entry else {
+            self.entry = Some(id);
+            return;
+        };
+
+        let query = self.nodes[id].vector.clone();
+        let top = self.nodes[entry].neighbors.len() - 1;
+        for layer in (level + 1..=top).rev() {
+            entry = self.closest(&query, entry, layer);
+        }
+        for layer in (0..=level.min(top)).rev() {
+            let max = if layer == 0 { 2 * m } else { m };
+            let candidates = self.search_layer(&query, &[entry], ef_construction, layer);
+            let neighbors: Vec<usize> = candidates.iter().take(max).map(|c| c.id).collect();
+            for &neighbor in &neighbors {
+                self.nodes[neighbor].neighbors[layer].push(id);
+                if self.nodes[neighbor].neighbors[layer].len() > max {
+                    self.prune(neighbor, layer, max);
+                }
+            }
+            self.nodes[id].neighbors[layer] = neighbors;
+            entry = candidates[0].id;
+        }
+        if level > top {
+            self.entry = Some(id);
+        }
+    }
+
+    /// Keeps only the `max` closest neighbours of `id` on `layer`.
+    fn prune(&mut self, id: usize, layer: usize, max: usize) {
+        let node = &self.nodes[id];
+        let mut neighbors: Vec<Candidate> = node.neighbors[layer]
+            .iter()
+            .map(|&neighbor| Candidate {
+                distance: self
+                    .metric
+                    .distance(&node.vector, &self.nodes[neighbor].vector),
+                id: neighbor,
+            })
+            .collect();
+        neighbors.sort_unstable();
+        self.nodes[id].neighbors[layer] = neighbors.into_iter().take(max).map(|c| c.id).collect();
+    }
+
+    /// Greedily walks `layer` towards `query`, returning the closest node found.
+    fn closest(&self, query: &[f32], mut entry: usize, layer: usize) -> usize {
+        let mut best = self.metric.distance(query, &self.nodes[entry].vector);
+        loop {
+            let mut improved = false;
+            for &neighbor in &self.nodes[entry].neighbors[layer] {
+                let distance = self.metric.distance(query, &self.nodes[neighbor].vector);
+                if distance < best {
+                    best = distance;
+                    entry = neighbor;
+                    improved = true;
+                }
+            }
+            if !improved {
+                return entry;
+            }
+        }
+    }
+
+    /// Returns up to `ef` nodes on `layer` closest to `query`, nearest first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[usize],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+        for &id in entries {
+            let c = Candidate {
+                distance: self.metric.distance(query, &self.nodes[id].vector),
+                id,
+            };
+            candidates.push(Reverse(c));
+            results.push(c);
+        }
+        while let Some(Reverse(current)) = candidates.pop() {
+            let furthest = results.peek().map_or(f32::INFINITY, |c| c.distance);
+            if current.distance > furthest && results.len() >= ef {
+                break;
+            }
+            for &neighbor in &self.nodes[current.id].neighbors[layer] {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let c = Candidate {
+                    distance: self.metric.distance(query, &self.nodes[neighbor].vector),
+                    id: neighbor,
+                };
+                let furthest = results.peek().map_or(f32::INFINITY, |c| c.distance);
+                if results.len() < ef || c.distance < furthest {
+                    candidates.push(Reverse(c));
+                    results.push(c);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    fn random_level(&mut self, m: usize) -> usize {
+        // xorshift64
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let uniform = ((self.rng >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        (-uniform.ln() / (m.max(2) as f64).ln()) as usize
+    }
+
+    fn rebuild(&mut self) {
+        let nodes = std::mem::take(&mut self.nodes);
+        self.ids.clear();
+        self.deleted = 0;
+        self.entry = None;
+        for node in nodes.into_iter().filter(|node| !node.deleted) {
+            self.insert(node.key, node.vector);
+        }
+    }
+}
+
+/// Every vector index in a database, by name.
+#[derive(Default)]
+pub struct VectorIndexes {
+    indexes: RwLock<HashMap<String, Arc<Mutex<VectorIndex>>>>,
+}
+
+impl VectorIndexes {
+    /// Registers a new, empty index, returning it unless an index with that name exists.
+    pub fn create(&self, name: String, index: VectorIndex) -> Option<Arc<Mutex<VectorIndex>>> {
+        let mut indexes = self.indexes.write().unwrap_or_else(PoisonError::into_inner);
+        if indexes.contains_key(&name) {
+            return None;
+        }
+        let index = Arc::new(Mutex::new(index));
+        indexes.insert(name, index.clone());
+        Some(index)
+    }
+
+    pub fn drop(&self, name: &str) -> bool {
+        self.indexes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name)
+            .is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Mutex<VectorIndex>>> {
+        self.indexes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned()
+    }
+
+    /// Returns the dimension required by an index covering `key` which `dim` doesn't match.
+    pub fn mismatched_dimension(&self, key: &str, dim: usize) -> Option<usize> {
+        self.indexes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .find_map(|index| {
+                let index = index.lock().unwrap_or_else(PoisonError::into_inner);
+                (key.starts_with(&index.prefix) && index.dim != dim).then_some(index.dim)
+            })
+    }
+
+    pub(crate) fn written(&self, key: &str, value: &[u8]) {
+        let indexes = self.indexes.read().unwrap_or_else(PoisonError::into_inner);
+        for index in indexes.values() {
+            let mut index = index.lock().unwrap_or_else(PoisonError::into_inner);
+            if !key.starts_with(&index.prefix) {
+                continue;
+            }
+            match vector::decode(value) {
+                Ok(Some(vector)) => index.upsert(key, vector),
+                _ => index.remove(key),
+            }
+        }
+    }
+
+    pub(crate) fn removed(&self, key: &str) {
+        let indexes = self.indexes.read().unwrap_or_else(PoisonError::into_inner);
+        for index in indexes.values() {
+            let mut index = index.lock().unwrap_or_else(PoisonError::into_inner);
+            if key.starts_with(&index.prefix) {
+                index.remove(key);
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Leases granted with `LEASE.GRANT`, and the keys attached to them with `SET ... LEASE`.
+///
+/// The keys attached to a lease are removed when it's revoked or runs out without being kept
+/// alive. A key is detached when it's removed, or set again without the lease.
+#[derive(Default)]
+pub struct Leases {
+    next_id: AtomicU64,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    leases: HashMap<u64, Lease>,
+    /// The lease each attached key is attached to.
+    attached: HashMap<String, u64>,
+}
+
+struct Lease {
+    /// How long the lease lasts each time it's kept alive, in seconds.
+    ttl: u64,
+    /// When the lease runs out, in milliseconds since the unix epoch.
+    expires_at: u64,
+    keys: HashSet<String>,
+}
+
+/// What `LEASE.TTL` reports about a lease.
+pub struct LeaseInfo {
+    /// How long until the lease runs out, in milliseconds.
+    pub remaining: u64,
+    /// How long the lease lasts each time it's kept alive, in seconds.
+    pub ttl: u64,
+    pub keys: Vec<String>,
+}
+
+impl Leases {
+    /// Grants a lease of `ttl` seconds from `now`, returning its id.
+    pub fn grant(&self, ttl: u64, now: u64) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.lock().leases.insert(
+            id,
+            Lease {
+                ttl,
+                expires_at: expires_at(now, ttl),
+                keys: HashSet::new(),
+            },
+        );
+        id
+    }
+
+    /// Restarts the lease's countdown from `now`, returning its ttl, or `None` if it doesn't
+    /// exist.
+    pub fn keep_alive(&self, id: u64, now: u64) -> Option<u64> {
+        let mut state = self.lock();
+        let lease = state.leases.get_mut(&id).filter(|l| l.expires_at > now)?;
+        lease.expires_at = expires_at(now, lease.ttl);
+        Some(lease.ttl)
+    }
+
+    pub fn info(&self, id: u64, now: u64) -> Option<LeaseInfo> {
+        let state = self.lock();
+        let lease = state.leases.get(&id).filter(|l| l.expires_at > now)?;
+        let mut keys: Vec<String> = lease.keys.iter().cloned().collect();
+        keys.sort();
+        Some(LeaseInfo {
+            remaining: lease.expires_at - now,
+            ttl: lease.ttl,
+            keys,
+        })
+    }
+
+    /// Attaches the key to the lease, detaching it from any other, returning false if the lease
+    /// doesn't exist.
+    ///
+    /// Called with the key's shard locked.
+    pub fn attach(&self, key: &str, id: u64, now: u64) -> bool {
+        let state = &mut *self.lock();
+        let Some(lease) = state.leases.get_mut(&id).filter(|l| l.expires_at > now) else {
+            return false;
+        };
+        lease.keys.insert(key.to_string());
+        if let Some(old) = state.attached.insert(key.to_string(), id)
+            && old != id
+            && let Some(lease) = state.leases.get_mut(&old)
+        {
+            lease.keys.remove(key);
+        }
+        true
+    }
+
+    /// Detaches the key from its lease, if it has one.
+    ///
+    /// Called with the key's shard locked.
+    pub fn detach(&self, key: &str) {
+        let state = &mut *self.lock();
+        if let Some(id) = state.attached.remove(key)
+            && let Some(lease) = state.leases.get_mut(&id)
+        {
+            lease.keys.remove(key);
+        }
+    }
+
+    /// Removes the lease, returning the keys attached to it, or `None` if it doesn't exist.
+    pub fn revoke(&self, id: u64) -> Option<Vec<String>> {
+        let state = &mut *self.lock();
+        let lease = state.leases.remove(&id)?;
+        Some(
+            lease
+                .keys
+                .into_iter()
+                .inspect(|key| {
+                    state.attached.remove(key);
+                })
+                .collect(),
+        )
+    }
+
+    /// Removes every lease which has run out by `now`, returning the keys attached to them.
+    pub fn revoke_expired(&self, now: u64) -> Vec<String> {
+        let expired: Vec<u64> = self
+            .lock()
+            .leases
+            .iter()
+            .filter(|(_, lease)| lease.expires_at <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|id| self.revoke(id))
+            .flatten()
+            .collect()
+    }
+
+    /// Whether any lease has run out by `now`.
+    pub fn any_expired(&self, now: u64) -> bool {
+        self.lock()
+            .leases
+            .values()
+            .any(|lease| lease.expires_at <= now)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn expires_at(now: u64, ttl: u64) -> u64 {
+    now.saturating_add(ttl.saturating_mul(1000))
+}
@@ -1,13 +1,25 @@
 use thiserror::Error;
 use tokio::io::{self};
 
+pub mod blocking;
 pub mod command;
 pub mod connection;
+pub mod databases;
+pub mod db;
+pub mod glob;
+pub mod history;
+pub mod index;
+pub mod lease;
 pub mod message;
+pub mod namespace;
+pub mod pubsub;
+pub mod schedule;
+pub mod session;
 pub mod value;
 
 pub use command::Command;
 pub use connection::Connection;
+pub use db::Db;
 pub use message::Message;
 
 pub const DEFAULT_PORT: u16 = 7676;
@@ -22,6 +34,8 @@ pub enum Error {
     ConnectionReset,
     #[error("failed to parse message: {0:?}")]
     ParseMessage(message::Error),
+    #[error("message too large to send: {0:?}")]
+    WriteMessage(message::Error),
     #[error("failed to parse command: {0:?}")]
     ParseCommand(command::Error),
     #[error("failed to parse value: {0:?}")]
@@ -8,14 +8,20 @@
 // INT = 3
 // TEXT = 4
 // ERR = 5
+// ARRAY = 7
+// FLOAT = 8
+// PUSH = 9
 
 // Message
 // COMMAND = CMD(8) COUNT(8) [LENGTH(16) BYTES]...
 // OK = _
 // NULL = _
-// INT = INT(32)
+// INT = INT(64)
 // TEXT = LENGTH(16) BYTES
 // ERR = LENGTH(16) BYTES
+// ARRAY = COUNT(16) [MESSAGE]...
+// FLOAT = FLOAT(64)
+// PUSH = COUNT(16) [MESSAGE]...
 
 use bytes::Buf;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -23,6 +29,10 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use crate::command::Command;
 use std::io::Cursor;
 
+/// How deeply arrays and pushes may be nested in a message, which bounds the recursion needed to
+/// parse one.
+const MAX_DEPTH: usize = 32;
+
 #[repr(u8)]
 pub enum Variant {
     Ping = 0,
@@ -32,6 +42,9 @@ pub enum Variant {
     Err = 4,
     Int = 5,
     Text = 6,
+    Array = 7,
+    Float = 8,
+    Push = 9,
 }
 
 #[derive(Debug)]
@@ -39,6 +52,9 @@ pub enum Error {
     Incomplete,
     UnknownMessageType(u8),
     StringTooLarge,
+    TooManyMessages,
+    TooManyArguments,
+    TooDeep,
 }
 
 impl TryFrom<u8> for Variant {
@@ -53,6 +69,9 @@ impl TryFrom<u8> for Variant {
             4 => Ok(Variant::Err),
             5 => Ok(Variant::Int),
             6 => Ok(Variant::Text),
+            7 => Ok(Variant::Array),
+            8 => Ok(Variant::Float),
+            9 => Ok(Variant::Push),
             _ => Err(Error::UnknownMessageType(value)),
         }
     }
@@ -65,8 +84,13 @@ pub enum Message {
     Ok,
     Null,
     Err(String),
-    Int(i32),
+    Int(i64),
     Text(String),
+    Array(Vec<Message>),
+    Float(f64),
+    /// Sent to a subscribed connection when something it subscribed to happens, rather than in
+    /// reply to a command.
+    Push(Vec<Message>),
 }
 
 pub async fn read_string(src: &mut Cursor<&[u8]>) -> crate::Result<String> {
@@ -89,14 +113,20 @@ pub async fn write_string<W: AsyncWriteExt + Unpin>(buf: &mut W, value: &str) ->
 }
 
 pub async fn write_bytes<W: AsyncWriteExt + Unpin>(buf: &mut W, value: &[u8]) -> crate::Result<()> {
-    // TODO: Validate length
-    buf.write_u16(value.len() as u16).await?;
+    let Ok(len) = u16::try_from(value.len()) else {
+        return Err(crate::Error::WriteMessage(Error::StringTooLarge));
+    };
+    buf.write_u16(len).await?;
     buf.write_all(value).await?;
     Ok(())
 }
 
-pub async fn read_int(src: &mut Cursor<&[u8]>) -> crate::Result<i32> {
-    Ok(src.read_i32().await?)
+pub async fn read_int(src: &mut Cursor<&[u8]>) -> crate::Result<i64> {
+    Ok(src.read_i64().await?)
+}
+
+pub async fn read_float(src: &mut Cursor<&[u8]>) -> crate::Result<f64> {
+    Ok(src.read_f64().await?)
 }
 
 impl Message {
@@ -105,6 +135,11 @@ impl Message {
             Ok(l) => l,
             Err(e) => return Err(crate::Error::ParseMessage(e)),
         });
+        Message::parse_body(&mut line, 0).await
+    }
+
+    /// Parses a message nested inside `depth` arrays or pushes.
+    async fn parse_body(line: &mut Cursor<&[u8]>, depth: usize) -> crate::Result<Message> {
         let variant_byte = line.read_u8().await?;
         let variant = match Variant::try_from(variant_byte) {
             Ok(v) => v,
@@ -112,18 +147,34 @@ impl Message {
         };
         match variant {
             Variant::Ping => Ok(Message::Ping),
-            Variant::Command => Command::parse(&mut line).await.map(Message::Command),
+            Variant::Command => Command::parse(line).await.map(Message::Command),
             Variant::Ok => Ok(Message::Ok),
             Variant::Null => Ok(Message::Null),
-            Variant::Err => read_string(&mut line).await.map(Message::Err),
-            Variant::Int => read_int(&mut line).await.map(Message::Int),
-            Variant::Text => read_string(&mut line).await.map(Message::Text),
+            Variant::Err => read_string(line).await.map(Message::Err),
+            Variant::Int => read_int(line).await.map(Message::Int),
+            Variant::Text => read_string(line).await.map(Message::Text),
+            Variant::Array => read_messages(line, depth).await.map(Message::Array),
+            Variant::Float => read_float(line).await.map(Message::Float),
+            Variant::Push => read_messages(line, depth).await.map(Message::Push),
         }
     }
 
+    /// Writes the message, or nothing at all if it doesn't fit the format, so the peer never
+    /// sees half a message.
     pub async fn write<W: tokio::io::AsyncWriteExt + std::marker::Unpin>(
         &self,
         buf: &mut W,
+    ) -> crate::Result<()> {
+        let mut body = Vec::new();
+        self.write_body(&mut body).await?;
+        buf.write_all(&body).await?;
+        buf.write_all(b"\r\n").await?;
+        Ok(())
+    }
+
+    async fn write_body<W: tokio::io::AsyncWriteExt + std::marker::Unpin>(
+        &self,
+        buf: &mut W,
     ) -> crate::Result<()> {
         match self {
             Message::Ping => {
@@ -145,18 +196,55 @@ impl Message {
             }
             Message::Int(int) => {
                 buf.write_u8(Variant::Int as u8).await?;
-                buf.write_i32(*int).await?;
+                buf.write_i64(*int).await?;
             }
             Message::Text(text) => {
                 buf.write_u8(Variant::Text as u8).await?;
                 write_string(buf, text).await?;
             }
+            Message::Array(messages) => {
+                buf.write_u8(Variant::Array as u8).await?;
+                write_messages(buf, messages).await?;
+            }
+            Message::Float(float) => {
+                buf.write_u8(Variant::Float as u8).await?;
+                buf.write_f64(*float).await?;
+            }
+            Message::Push(messages) => {
+                buf.write_u8(Variant::Push as u8).await?;
+                write_messages(buf, messages).await?;
+            }
         }
-        buf.write_all(b"\r\n").await?;
         Ok(())
     }
 }
 
+async fn read_messages(src: &mut Cursor<&[u8]>, depth: usize) -> crate::Result<Vec<Message>> {
+    if depth >= MAX_DEPTH {
+        return Err(crate::Error::ParseMessage(Error::TooDeep));
+    }
+    let count = src.read_u16().await?;
+    let mut messages = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        messages.push(Box::pin(Message::parse_body(src, depth + 1)).await?);
+    }
+    Ok(messages)
+}
+
+async fn write_messages<W: AsyncWriteExt + Unpin>(
+    buf: &mut W,
+    messages: &[Message],
+) -> crate::Result<()> {
+    let Ok(count) = u16::try_from(messages.len()) else {
+        return Err(crate::Error::WriteMessage(Error::TooManyMessages));
+    };
+    buf.write_u16(count).await?;
+    for message in messages {
+        Box::pin(message.write_body(buf)).await?;
+    }
+    Ok(())
+}
+
 pub fn is_complete(src: &mut Cursor<&[u8]>) -> bool {
     read_line(src).is_ok()
 }
@@ -0,0 +1,128 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU8, AtomicU64, AtomicUsize, Ordering},
+};
+
+use crate::Db;
+
+/// A keyspace created at runtime with `NAMESPACE.CREATE`, along with who may use it.
+pub struct Namespace {
+    pub db: Arc<Db>,
+    /// Grants read-write access, if the namespace isn't open to everyone.
+    pub password: Option<String>,
+    /// Grants read-only access.
+    pub read_password: Option<String>,
+}
+
+/// What a connection may do in a namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    ReadOnly,
+    ReadWrite,
+}
+
+impl Namespace {
+    /// The access granted by `password`, or `None` if it's wrong.
+    pub fn access(&self, password: Option<&str>) -> Option<Access> {
+        match (&self.password, password) {
+            (None, _) => Some(Access::ReadWrite),
+            (Some(expected), Some(given)) if expected == given => Some(Access::ReadWrite),
+            (_, Some(given)) if self.read_password.as_deref() == Some(given) => {
+                Some(Access::ReadOnly)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How keys are chosen for eviction once a keyspace uses more than its memory limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eviction {
+    /// Nothing is evicted, and writes are refused instead.
+    NoEviction,
+    /// Keys with an expiry are evicted, soonest to expire first.
+    VolatileTtl,
+    /// Any key is evicted, least recently written first.
+    AllKeysOldest,
+    /// Any key is evicted, least recently read or written first.
+    AllKeysLru,
+}
+
+impl Eviction {
+    pub fn parse(name: &str) -> Option<Eviction> {
+        match name.to_ascii_lowercase().as_str() {
+            "noeviction" => Some(Eviction::NoEviction),
+            "volatile-ttl" => Some(Eviction::VolatileTtl),
+            "allkeys-oldest" => Some(Eviction::AllKeysOldest),
+            "allkeys-lru" => Some(Eviction::AllKeysLru),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Eviction::NoEviction => "noeviction",
+            Eviction::VolatileTtl => "volatile-ttl",
+            Eviction::AllKeysOldest => "allkeys-oldest",
+            Eviction::AllKeysLru => "allkeys-lru",
+        }
+    }
+}
+
+/// A keyspace's memory limit, default ttl and eviction policy, none of which apply by default.
+#[derive(Default)]
+pub struct Limits {
+    /// The most bytes of keys and values to hold, or zero for no limit.
+    max_memory: AtomicUsize,
+    /// How long keys created without an expiry live, in milliseconds, or zero for forever.
+    default_ttl: AtomicU64,
+    eviction: AtomicU8,
+}
+
+impl Limits {
+    pub fn max_memory(&self) -> Option<usize> {
+        Some(self.max_memory.load(Ordering::Relaxed)).filter(|&max| max > 0)
+    }
+
+    pub fn set_max_memory(&self, max: Option<usize>) {
+        self.max_memory.store(max.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn default_ttl(&self) -> Option<u64> {
+        Some(self.default_ttl.load(Ordering::Relaxed)).filter(|&ttl| ttl > 0)
+    }
+
+    pub fn set_default_ttl(&self, ttl: Option<u64>) {
+        self.default_ttl.store(ttl.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn eviction(&self) -> Eviction {
+        match self.eviction.load(Ordering::Relaxed) {
+            1 => Eviction::VolatileTtl,
+            2 => Eviction::AllKeysOldest,
+            3 => Eviction::AllKeysLru,
+            _ => Eviction::NoEviction,
+        }
+    }
+
+    pub fn set_eviction(&self, eviction: Eviction) {
+        let value = match eviction {
+            Eviction::NoEviction => 0,
+            Eviction::VolatileTtl => 1,
+            Eviction::AllKeysOldest => 2,
+            Eviction::AllKeysLru => 3,
+        };
+        self.eviction.store(value, Ordering::Relaxed);
+    }
+
+    /// Whether `used` bytes is over the limit.
+    pub fn exceeded(&self, used: usize) -> bool {
+        self.max_memory().is_some_and(|max| used > max)
+    }
+
+    /// Whether writes are refused, which they are once `used` bytes is over the limit and
+    /// nothing can be evicted.
+    pub fn refuses_writes(&self, used: usize) -> bool {
+        self.eviction() == Eviction::NoEviction && self.exceeded(used)
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        PoisonError, RwLock, RwLockWriteGuard,
+        atomic::{AtomicU8, AtomicU64, Ordering},
+    },
+};
+
+use tokio::sync::mpsc::{self, Receiver, Sender, error::TrySendError};
+
+use crate::{Message, command, glob};
+
+/// The most messages waiting to be sent to a subscriber before it's disconnected for not keeping
+/// up.
+pub const SUBSCRIBER_BUFFER: usize = 1024;
+
+/// Who is subscribed to which channels and patterns, for delivering published messages.
+#[derive(Default)]
+pub struct Channels {
+    next_id: AtomicU64,
+    registry: RwLock<Registry>,
+    /// Which keyspace events are published, as a set of [`Notify`] flags.
+    notify: AtomicU8,
+}
+
+/// A change to a key, which is published for anyone subscribed to the key or the event, if
+/// events of its class are enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Set,
+    Del,
+    Incr,
+    Expire,
+    Persist,
+    Expired,
+    Evicted,
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Set => "set",
+            Event::Del => "del",
+            Event::Incr => "incr",
+            Event::Expire => "expire",
+            Event::Persist => "persist",
+            Event::Expired => "expired",
+            Event::Evicted => "evicted",
+        }
+    }
+
+    fn class(&self) -> u8 {
+        match self {
+            Event::Del | Event::Expire | Event::Persist => Notify::GENERIC,
+            Event::Set | Event::Incr => Notify::STRING,
+            Event::Expired => Notify::EXPIRED,
+            Event::Evicted => Notify::EVICTED,
+        }
+    }
+}
+
+/// The flags making up the `notify-keyspace-events` setting, each written as one letter.
+pub struct Notify;
+
+impl Notify {
+    /// Publish to `__keyspace__:<key>`, with the event as the message.
+    pub const KEYSPACE: u8 = 1 << 0;
+    /// Publish to `__keyevent__:<event>`, with the key as the message.
+    pub const KEYEVENT: u8 = 1 << 1;
+    /// Keys being deleted, or given or cleared of an expiry.
+    pub const GENERIC: u8 = 1 << 2;
+    /// Strings and numbers being written.
+    pub const STRING: u8 = 1 << 3;
+    /// Keys expiring.
+    pub const EXPIRED: u8 = 1 << 4;
+    /// Keys being evicted to free memory.
+    pub const EVICTED: u8 = 1 << 5;
+    /// Include the key's new value, if it has one, after the message.
+    pub const VALUES: u8 = 1 << 6;
+
+    const LETTERS: [(char, u8); 7] = [
+        ('K', Notify::KEYSPACE),
+        ('E', Notify::KEYEVENT),
+        ('g', Notify::GENERIC),
+        ('$', Notify::STRING),
+        ('x', Notify::EXPIRED),
+        ('e', Notify::EVICTED),
+        ('v', Notify::VALUES),
+    ];
+    const ALL: u8 = Notify::GENERIC | Notify::STRING | Notify::EXPIRED | Notify::EVICTED;
+
+    /// Parses a setting such as `KEA`, where `A` stands for every class of event.
+    pub fn parse(setting: &str) -> Option<u8> {
+        setting.chars().try_fold(0, |flags, letter| {
+            if letter == 'A' {
+                return Some(flags | Notify::ALL);
+            }
+            let (_, flag) = Notify::LETTERS.iter().find(|(l, _)| *l == letter)?;
+            Some(flags | flag)
+        })
+    }
+
+    pub fn format(flags: u8) -> String {
+        Notify::LETTERS
+            .iter()
+            .filter(|(_, flag)| flags & flag != 0)
+            .map(|(letter, _)| letter)
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    subscribers: HashMap<u64, Sender<Message>>,
+    channels: HashMap<String, HashSet<u64>>,
+    patterns: HashMap<String, HashSet<u64>>,
+}
+
+impl Registry {
+    fn unsubscribe(map: &mut HashMap<String, HashSet<u64>>, name: &str, id: u64) {
+        if let Some(ids) = map.get_mut(name) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                map.remove(name);
+            }
+        }
+    }
+}
+
+impl Channels {
+    /// Registers a new subscriber, which receives the messages published to it through the
+    /// returned receiver.
+    ///
+    /// The receiver yields `None` once the subscriber has been dropped for falling too far
+    /// behind.
+    pub fn register(&self) -> (u64, Receiver<Message>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel(SUBSCRIBER_BUFFER);
+        self.write().subscribers.insert(id, sender);
+        (id, receiver)
+    }
+
+    /// Removes a subscriber, along with all of its subscriptions.
+    pub fn unregister(&self, id: u64) {
+        let registry = &mut *self.write();
+        if registry.subscribers.remove(&id).is_none() {
+            return;
+        }
+        for map in [&mut registry.channels, &mut registry.patterns] {
+            map.retain(|_, ids| {
+                ids.remove(&id);
+                !ids.is_empty()
+            });
+        }
+    }
+
+    pub fn subscribe(&self, id: u64, channel: &str) {
+        let mut registry = self.write();
+        if registry.subscribers.contains_key(&id) {
+            registry
+                .channels
+                .entry(channel.to_string())
+                .or_default()
+                .insert(id);
+        }
+    }
+
+    pub fn unsubscribe(&self, id: u64, channel: &str) {
+        Registry::unsubscribe(&mut self.write().channels, channel, id);
+    }
+
+    pub fn psubscribe(&self, id: u64, pattern: &str) {
+        let mut registry = self.write();
+        if registry.subscribers.contains_key(&id) {
+            registry
+                .patterns
+                .entry(pattern.to_string())
+                .or_default()
+                .insert(id);
+        }
+    }
+
+    pub fn punsubscribe(&self, id: u64, pattern: &str) {
+        Registry::unsubscribe(&mut self.write().patterns, pattern, id);
+    }
+
+    /// Sends a message to everyone subscribed to the channel, directly or by pattern, returning
+    /// how many subscribers it was sent to.
+    pub fn publish(&self, channel: &str, message: &str) -> usize {
+        self.deliver(channel, || vec![Message::Text(message.to_string())])
+    }
+
+    pub fn notify_flags(&self) -> u8 {
+        self.notify.load(Ordering::Relaxed)
+    }
+
+    pub fn set_notify_flags(&self, flags: u8) {
+        self.notify.store(flags, Ordering::Relaxed);
+    }
+
+    /// Publishes a change to a key, if events of its class are enabled.
+    pub fn notify(&self, event: Event, key: &str, value: Option<&[u8]>) {
+        let flags = self.notify_flags();
+        if flags & event.class() == 0 {
+            return;
+        }
+        // Values which can't be read back are sent as null, rather than failing the write.
+        let value = || match value {
+            Some(value) if flags & Notify::VALUES != 0 => {
+                Some(command::to_message(value).unwrap_or(Message::Null))
+            }
+            _ => None,
+        };
+        if flags & Notify::KEYSPACE != 0 {
+            self.deliver(&format!("__keyspace__:{key}"), || {
+                let mut message = vec![Message::Text(event.name().to_string())];
+                message.extend(value());
+                message
+            });
+        }
+        if flags & Notify::KEYEVENT != 0 {
+            self.deliver(&format!("__keyevent__:{}", event.name()), || {
+                let mut message = vec![Message::Text(key.to_string())];
+                message.extend(value());
+                message
+            });
+        }
+    }
+
+    /// Pushes what `payload` returns to everyone subscribed to the channel, after the channel and
+    /// any pattern it matched.
+    ///
+    /// Subscribers whose buffers are full are unregistered rather than waited for, so one slow
+    /// reader can't hold up the publisher or everyone else.
+    fn deliver(&self, channel: &str, payload: impl Fn() -> Vec<Message>) -> usize {
+        let mut sent = 0;
+        let mut dropped = Vec::new();
+        {
+            let registry = self.registry.read().unwrap_or_else(PoisonError::into_inner);
+            let mut send = |id: u64, mut push: Vec<Message>| {
+                let Some(sender) = registry.subscribers.get(&id) else {
+                    return;
+                };
+                push.extend(payload());
+                match sender.try_send(Message::Push(push)) {
+                    Ok(()) => sent += 1,
+                    Err(TrySendError::Full(_) | TrySendError::Closed(_)) => dropped.push(id),
+                }
+            };
+            for &id in registry.channels.get(channel).into_iter().flatten() {
+                send(
+                    id,
+                    vec![
+                        Message::Text("message".to_string()),
+                        Message::Text(channel.to_string()),
+                    ],
+                );
+            }
+            for (pattern, ids) in &registry.patterns {
+                if !glob::matches(pattern, channel) {
+                    continue;
+                }
+                for &id in ids {
+                    send(
+                        id,
+                        vec![
+                            Message::Text("pmessage".to_string()),
+                            Message::Text(pattern.clone()),
+                            Message::Text(channel.to_string()),
+                        ],
+                    );
+                }
+            }
+        }
+        for id in dropped {
+            self.unregister(id);
+        }
+        sent
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Registry> {
+        self.registry
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
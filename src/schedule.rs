@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crossbeam_skiplist::SkipSet;
+use tokio::sync::Notify;
+
+/// Jobs waiting to be pushed onto their ready lists, ordered by when they're due.
+///
+/// Jobs live only in memory, so any still waiting are lost when the server stops.
+#[derive(Default)]
+pub struct Schedule {
+    next_id: AtomicU64,
+    jobs: SkipSet<Job>,
+    /// Told whenever a job is added, so the timer can wake for it if it's due sooner.
+    added: Notify,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Job {
+    /// When the job is due, in milliseconds since the unix epoch.
+    pub due: u64,
+    /// Orders jobs due at the same time by when they were scheduled.
+    pub id: u64,
+    /// The list the job is pushed onto when it's due.
+    pub key: String,
+    pub payload: String,
+}
+
+impl Schedule {
+    /// Adds a job, returning its id.
+    pub fn add(&self, key: String, due: u64, payload: String) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.jobs.insert(Job {
+            due,
+            id,
+            key,
+            payload,
+        });
+        self.added.notify_one();
+        id
+    }
+
+    /// Removes and returns up to `limit` jobs which are due by `now`, earliest first.
+    pub fn take_due(&self, now: u64, limit: usize) -> Vec<Job> {
+        let mut due = Vec::new();
+        while due.len() < limit {
+            match self.jobs.front() {
+                Some(entry) if entry.value().due <= now => {
+                    // Another taker may have beaten us to it.
+                    if entry.remove() {
+                        due.push(entry.value().clone());
+                    }
+                }
+                _ => break,
+            }
+        }
+        due
+    }
+
+    /// When the earliest job is due, if there are any.
+    pub fn next_due(&self) -> Option<u64> {
+        self.jobs.front().map(|entry| entry.value().due)
+    }
+
+    /// How many jobs are waiting.
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Waits for a job to be added, or returns immediately if one has been since this was last
+    /// waited on.
+    pub async fn added(&self) {
+        self.added.notified().await;
+    }
+}
@@ -0,0 +1,686 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    future, mem, ptr,
+    sync::{Arc, Weak},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{mpsc::Receiver, watch};
+
+use crate::{
+    Command, Connection, Db, Message, Result, command,
+    databases::Databases,
+    history::{Change, Next, Watched},
+    namespace::{Access, Namespace},
+};
+
+const READ_ONLY: &str = "NOPERM this connection can only read the namespace";
+
+/// The state kept for a connection between its commands.
+#[derive(Default)]
+pub struct Session {
+    /// The number of the logical database the connection is using, unless it's using a
+    /// namespace.
+    db: usize,
+    /// The namespace the connection is using, if any.
+    namespace: Option<String>,
+    /// The access the connection has gained to namespaces with a password. Each grant only
+    /// applies to the namespace it was gained for, not to any created later with the same name.
+    granted: HashMap<String, (Weak<Namespace>, Access)>,
+    /// The commands queued since `MULTI`, if a transaction is open.
+    queued: Option<Vec<Command>>,
+    /// Whether a command sent since `MULTI` was invalid, so the transaction can't be run.
+    aborted: bool,
+    /// Each watched key, with the keyspace it was watched in and its version at the time.
+    watched: Vec<(Arc<Db>, String, u64)>,
+    /// The channels and patterns subscribed to, if any, in which case the connection is in push
+    /// mode.
+    subscription: Option<Subscription>,
+    /// The streams opened by `WATCHREV`, if any, which also put the connection in push mode.
+    streams: Option<Streams>,
+}
+
+struct Subscription {
+    db: Arc<Db>,
+    id: u64,
+    receiver: Receiver<Message>,
+    channels: BTreeSet<String>,
+    patterns: BTreeSet<String>,
+}
+
+impl Subscription {
+    fn count(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+}
+
+struct Streams {
+    db: Arc<Db>,
+    /// Told whenever a change is recorded, so the streams can look for new changes.
+    changes: watch::Receiver<u64>,
+    /// What each stream is watching, and the next revision it would send.
+    watching: Vec<(Watched, u64)>,
+}
+
+impl Streams {
+    /// Finds the earliest change, with a revision no stream has sent, which a stream is
+    /// watching for. Every stream moves past it, so a change several streams match is only
+    /// sent once.
+    ///
+    /// Streams which fell behind the history are closed, after saying so.
+    fn next(&mut self) -> Option<Message> {
+        self.changes.borrow_and_update();
+        let mut earliest: Option<Change> = None;
+        for i in 0..self.watching.len() {
+            let (watched, from) = &mut self.watching[i];
+            match self.db.history.next(watched, *from) {
+                Next::Change(change) => {
+                    if earliest
+                        .as_ref()
+                        .is_none_or(|e| change.revision < e.revision)
+                    {
+                        earliest = Some(change);
+                    }
+                }
+                Next::Pending(next) => *from = next,
+                Next::Compacted(compacted) => {
+                    let (watched, _) = self.watching.remove(i);
+                    return Some(Message::Push(vec![
+                        Message::Text("compacted".to_string()),
+                        Message::Text(watched.name().to_string()),
+                        Message::Int(compacted as i64),
+                    ]));
+                }
+            }
+        }
+        let change = earliest?;
+        for (_, from) in &mut self.watching {
+            *from = (*from).max(change.revision + 1);
+        }
+        let mut push = vec![
+            Message::Text(
+                if change.value.is_some() {
+                    "put"
+                } else {
+                    "delete"
+                }
+                .to_string(),
+            ),
+            Message::Int(change.revision as i64),
+            Message::Text(change.key),
+        ];
+        if let Some(value) = change.value {
+            push.push(command::to_message(&value).unwrap_or(Message::Null));
+        }
+        Some(Message::Push(push))
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.db.channels.unregister(self.id);
+    }
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Performs a command sent on this connection, or queues it if a transaction is open.
+    ///
+    /// Blocking commands wait here, so this returns `None` if the connection is closed while
+    /// one is waiting.
+    pub async fn perform(
+        &mut self,
+        command: Command,
+        databases: &Databases,
+        connection: &mut Connection,
+    ) -> Result<Option<Message>> {
+        let mut command = command;
+        let (db, access) = match self.route(&mut command, databases) {
+            Ok(routed) => routed,
+            Err(err) => return Ok(Some(err)),
+        };
+        if access == Access::ReadOnly && !command.is_read_only() {
+            return Ok(Some(Message::Err(READ_ONLY.to_string())));
+        }
+        let db = &db;
+        let idle = self.queued.is_none() && self.subscription.is_none() && self.streams.is_none();
+        let blocks = matches!(
+            command,
+            Command::BLPop(_) | Command::BRPop(_) | Command::BLMove(_)
+        ) || matches!(&command, Command::Lock(lock) if lock.timeout.is_some());
+        if idle
+            && blocks
+            && let Some(refused) = command.refused(db)
+        {
+            return Ok(Some(refused));
+        }
+        let command = match command {
+            Command::BLPop(blpop) if idle => {
+                let keys = blpop.keys.clone();
+                return block(db, connection, keys, blpop.timeout, |db| {
+                    let _lock = db.shared();
+                    blpop.attempt(db)
+                })
+                .await;
+            }
+            Command::BRPop(brpop) if idle => {
+                let keys = brpop.keys.clone();
+                return block(db, connection, keys, brpop.timeout, |db| {
+                    let _lock = db.shared();
+                    brpop.attempt(db)
+                })
+                .await;
+            }
+            Command::BLMove(blmove) if idle => {
+                let keys = vec![blmove.source.clone()];
+                return block(db, connection, keys, blmove.timeout, |db| {
+                    let _lock = db.exclusive();
+                    blmove.attempt(db)
+                })
+                .await;
+            }
+            Command::Lock(lock) if idle && lock.timeout.is_some() => {
+                let keys = vec![lock.key.clone()];
+                let timeout = lock.timeout.unwrap_or_default();
+                return block(db, connection, keys, timeout, |db| {
+                    let _lock = db.shared();
+                    lock.attempt(db)
+                })
+                .await;
+            }
+            command => command,
+        };
+        self.respond(command, db, access, databases).map(Some)
+    }
+
+    fn respond(
+        &mut self,
+        command: Command,
+        db: &Arc<Db>,
+        access: Access,
+        databases: &Databases,
+    ) -> Result<Message> {
+        let is_push = matches!(
+            command,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::WatchRev(_)
+                | Command::UnwatchRev(_)
+        );
+        if is_push && self.queued.is_some() {
+            return Ok(Message::Err(
+                "subscribing inside MULTI is not allowed".to_string(),
+            ));
+        }
+        if !is_push && (self.subscription.is_some() || self.streams.is_some()) {
+            return Ok(Message::Err(
+                "only (P)SUBSCRIBE / (P)UNSUBSCRIBE / WATCHREV / UNWATCHREV are allowed while \
+                 subscribed"
+                    .to_string(),
+            ));
+        }
+        let switches_db = matches!(
+            command,
+            Command::Select(_) | Command::SwapDb(_) | Command::Move(_)
+        );
+        if switches_db && self.queued.is_some() {
+            return Ok(Message::Err(
+                "SELECT, SWAPDB and MOVE inside MULTI are not allowed".to_string(),
+            ));
+        }
+        let is_namespace = matches!(
+            command,
+            Command::NamespaceCreate(_)
+                | Command::NamespaceDrop(_)
+                | Command::NamespaceUse(_)
+                | Command::NamespaceInfo(_)
+        );
+        if is_namespace && self.queued.is_some() {
+            return Ok(Message::Err(
+                "NAMESPACE commands inside MULTI are not allowed".to_string(),
+            ));
+        }
+        match command {
+            Command::Select(select) => {
+                if select.index >= databases.len() {
+                    return Ok(Message::Err("DB index is out of range".to_string()));
+                }
+                self.db = select.index;
+                self.namespace = None;
+                Ok(Message::Ok)
+            }
+            Command::NamespaceCreate(create) => {
+                let Some(namespace) = databases.create_namespace(
+                    create.name.clone(),
+                    create.password,
+                    create.read_password,
+                ) else {
+                    return Ok(Message::Err("namespace already exists".to_string()));
+                };
+                let limits = &namespace.db.limits;
+                limits.set_max_memory(create.max_memory);
+                limits.set_default_ttl(create.ttl.map(|ttl| ttl.saturating_mul(1000)));
+                if let Some(eviction) = create.eviction {
+                    limits.set_eviction(eviction);
+                }
+                self.granted
+                    .insert(create.name, (Arc::downgrade(&namespace), Access::ReadWrite));
+                Ok(Message::Ok)
+            }
+            Command::NamespaceUse(namespace_use) => {
+                let Some(namespace) = databases.namespace(&namespace_use.name) else {
+                    return Ok(Message::Err("no such namespace".to_string()));
+                };
+                if let Some(password) = namespace_use.password {
+                    let Some(access) = namespace.access(Some(&password)) else {
+                        return Ok(Message::Err("invalid password".to_string()));
+                    };
+                    self.granted.insert(
+                        namespace_use.name.clone(),
+                        (Arc::downgrade(&namespace), access),
+                    );
+                }
+                if self.access(&namespace_use.name, &namespace).is_none() {
+                    return Ok(Message::Err(no_access(&namespace_use.name)));
+                }
+                self.namespace = Some(namespace_use.name);
+                Ok(Message::Ok)
+            }
+            Command::NamespaceDrop(drop) => {
+                let Some(namespace) = databases.namespace(&drop.name) else {
+                    return Ok(Message::Err("no such namespace".to_string()));
+                };
+                if self.access(&drop.name, &namespace) != Some(Access::ReadWrite) {
+                    return Ok(Message::Err(no_access(&drop.name)));
+                }
+                databases.drop_namespace(&drop.name);
+                Ok(Message::Ok)
+            }
+            Command::NamespaceInfo(info) => {
+                let Some(namespace) = databases.namespace(&info.name) else {
+                    return Ok(Message::Null);
+                };
+                if self.access(&info.name, &namespace).is_none() {
+                    return Ok(Message::Err(no_access(&info.name)));
+                }
+                let db = &namespace.db;
+                let setting =
+                    |value: Option<u64>| value.map_or(Message::Null, |v| Message::Int(v as i64));
+                Ok(Message::Array(vec![
+                    Message::Text("keys".to_string()),
+                    Message::Int(db.len() as i64),
+                    Message::Text("used-memory".to_string()),
+                    Message::Int(db.used_memory() as i64),
+                    Message::Text("max-memory".to_string()),
+                    setting(db.limits.max_memory().map(|max| max as u64)),
+                    Message::Text("ttl".to_string()),
+                    setting(db.limits.default_ttl().map(|ttl| ttl / 1000)),
+                    Message::Text("eviction".to_string()),
+                    Message::Text(db.limits.eviction().name().to_string()),
+                ]))
+            }
+            Command::SwapDb(swapdb) => {
+                if !databases.swap(swapdb.first, swapdb.second) {
+                    return Ok(Message::Err("DB index is out of range".to_string()));
+                }
+                Ok(Message::Ok)
+            }
+            Command::Move(key_move) => {
+                let Some(target) = databases.get(key_move.db) else {
+                    return Ok(Message::Err("DB index is out of range".to_string()));
+                };
+                key_move.perform(db, &target)
+            }
+            Command::Multi(_) => {
+                if self.queued.is_some() {
+                    return Ok(Message::Err("MULTI calls can't be nested".to_string()));
+                }
+                self.queued = Some(Vec::new());
+                Ok(Message::Ok)
+            }
+            Command::Exec(_) => self.exec(db, access),
+            Command::Discard(_) => {
+                if self.queued.take().is_none() {
+                    return Ok(Message::Err("DISCARD without MULTI".to_string()));
+                }
+                self.aborted = false;
+                self.watched.clear();
+                Ok(Message::Ok)
+            }
+            Command::Watch(watch) => {
+                if self.queued.is_some() {
+                    return Ok(Message::Err(
+                        "WATCH inside MULTI is not allowed".to_string(),
+                    ));
+                }
+                for key in watch.keys {
+                    let version = db.version(&key);
+                    self.watched.push((db.clone(), key, version));
+                }
+                Ok(Message::Ok)
+            }
+            Command::Unwatch(_) => {
+                self.watched.clear();
+                Ok(Message::Ok)
+            }
+            Command::Subscribe(subscribe) => {
+                Ok(self.subscribe(db, "subscribe", subscribe.channels, false))
+            }
+            Command::PSubscribe(psubscribe) => {
+                Ok(self.subscribe(db, "psubscribe", psubscribe.patterns, true))
+            }
+            Command::Unsubscribe(unsubscribe) => {
+                Ok(self.unsubscribe("unsubscribe", unsubscribe.channels, false))
+            }
+            Command::PUnsubscribe(punsubscribe) => {
+                Ok(self.unsubscribe("punsubscribe", punsubscribe.patterns, true))
+            }
+            Command::WatchRev(watchrev) => {
+                let from = match watchrev.from {
+                    Some(from) => from,
+                    None => db.history.revision() + 1,
+                };
+                let compacted = db.history.compacted();
+                if from <= compacted {
+                    return Ok(Message::Err(format!(
+                        "compacted: the earliest revision which can be watched is {}",
+                        compacted + 1
+                    )));
+                }
+                let streams = self.streams.get_or_insert_with(|| Streams {
+                    db: db.clone(),
+                    changes: db.history.subscribe(),
+                    watching: Vec::new(),
+                });
+                let name = watchrev.watched.name().to_string();
+                streams.watching.push((watchrev.watched, from));
+                Ok(Message::Push(vec![
+                    Message::Text("watchrev".to_string()),
+                    Message::Text(name),
+                    Message::Int(from as i64),
+                    Message::Int(streams.watching.len() as i64),
+                ]))
+            }
+            Command::UnwatchRev(_) => {
+                self.streams = None;
+                Ok(Message::Push(vec![
+                    Message::Text("unwatchrev".to_string()),
+                    Message::Int(0),
+                ]))
+            }
+            command => match &mut self.queued {
+                Some(queued) => {
+                    queued.push(command);
+                    Ok(Message::Text("QUEUED".to_string()))
+                }
+                None => command.perform(db.clone()),
+            },
+        }
+    }
+
+    /// Finds the keyspace the command should run in, and the access the connection has to it.
+    ///
+    /// If every key the command names has a `{ns:name}` prefix naming the same namespace, the
+    /// prefixes are removed and the command runs in that namespace. Otherwise it runs in
+    /// whichever keyspace the connection is using.
+    fn route(
+        &self,
+        command: &mut Command,
+        databases: &Databases,
+    ) -> std::result::Result<(Arc<Db>, Access), Message> {
+        let mut keys = command.keys_mut();
+        let names: Vec<Option<&str>> = keys
+            .iter()
+            .map(|key| command::namespace_prefix(key))
+            .collect();
+        if let Some(Some(name)) = names.first()
+            && names.iter().all(|n| n == &Some(*name))
+        {
+            let name = name.to_string();
+            for key in keys.iter_mut() {
+                key.drain(..name.len() + "{ns:}".len());
+            }
+            if self.queued.is_some() {
+                return Err(Message::Err(
+                    "namespaced keys can't be used inside MULTI".to_string(),
+                ));
+            }
+            let Some(namespace) = databases.namespace(&name) else {
+                return Err(Message::Err("no such namespace".to_string()));
+            };
+            return match self.access(&name, &namespace) {
+                Some(access) => Ok((namespace.db.clone(), access)),
+                None => Err(Message::Err(no_access(&name))),
+            };
+        }
+        if names.iter().any(Option::is_some) {
+            return Err(Message::Err(
+                "keys must all be in the same namespace".to_string(),
+            ));
+        }
+        // Switching keyspace, or managing namespaces, doesn't need access to the current one,
+        // which may have been dropped since.
+        let switches = matches!(
+            command,
+            Command::Select(_)
+                | Command::NamespaceCreate(_)
+                | Command::NamespaceDrop(_)
+                | Command::NamespaceUse(_)
+                | Command::NamespaceInfo(_)
+        );
+        match &self.namespace {
+            Some(name) if !switches => {
+                let Some(namespace) = databases.namespace(name) else {
+                    return Err(Message::Err("namespace no longer exists".to_string()));
+                };
+                match self.access(name, &namespace) {
+                    Some(access) => Ok((namespace.db.clone(), access)),
+                    None => Err(Message::Err(no_access(name))),
+                }
+            }
+            _ => match databases.get(self.db) {
+                Some(db) => Ok((db, Access::ReadWrite)),
+                None => Err(Message::Err("DB index is out of range".to_string())),
+            },
+        }
+    }
+
+    /// The access the connection has to a namespace, if any.
+    fn access(&self, name: &str, namespace: &Arc<Namespace>) -> Option<Access> {
+        self.granted
+            .get(name)
+            .filter(|(granted, _)| ptr::eq(granted.as_ptr(), Arc::as_ptr(namespace)))
+            .map(|(_, access)| *access)
+            .or_else(|| namespace.access(None))
+    }
+
+    /// Records that an invalid command was sent, which aborts any open transaction.
+    pub fn failed(&mut self) {
+        if self.queued.is_some() {
+            self.aborted = true;
+        }
+    }
+
+    /// Waits for the next message published to something this connection subscribed to, or
+    /// change to something it's watching.
+    ///
+    /// Yields `None` if the connection fell too far behind and was unsubscribed, and never
+    /// completes if nothing is subscribed or watched.
+    pub async fn pushed(&mut self) -> Option<Message> {
+        loop {
+            if let Some(streams) = &mut self.streams
+                && let Some(message) = streams.next()
+            {
+                if streams.watching.is_empty() {
+                    self.streams = None;
+                }
+                return Some(message);
+            }
+            let published = async {
+                match &mut self.subscription {
+                    Some(subscription) => subscription.receiver.recv().await,
+                    None => future::pending().await,
+                }
+            };
+            let changed = async {
+                match &mut self.streams {
+                    // The history outlives every session, so this can't fail.
+                    Some(streams) => _ = streams.changes.changed().await,
+                    None => future::pending().await,
+                }
+            };
+            tokio::select! {
+                message = published => return message,
+                () = changed => {}
+            }
+        }
+    }
+
+    fn subscribe(
+        &mut self,
+        db: &Arc<Db>,
+        kind: &str,
+        names: Vec<String>,
+        patterns: bool,
+    ) -> Message {
+        let subscription = self.subscription.get_or_insert_with(|| {
+            let (id, receiver) = db.channels.register();
+            Subscription {
+                db: db.clone(),
+                id,
+                receiver,
+                channels: BTreeSet::new(),
+                patterns: BTreeSet::new(),
+            }
+        });
+        for name in &names {
+            if patterns {
+                db.channels.psubscribe(subscription.id, name);
+                subscription.patterns.insert(name.clone());
+            } else {
+                db.channels.subscribe(subscription.id, name);
+                subscription.channels.insert(name.clone());
+            }
+        }
+        subscribed(kind, names, subscription.count())
+    }
+
+    /// Unsubscribes from the given channels or patterns, or all of them if none are given,
+    /// leaving push mode once nothing is subscribed.
+    fn unsubscribe(&mut self, kind: &str, names: Vec<String>, patterns: bool) -> Message {
+        let Some(subscription) = &mut self.subscription else {
+            return subscribed(kind, names, 0);
+        };
+        let subscribed_to = if patterns {
+            &mut subscription.patterns
+        } else {
+            &mut subscription.channels
+        };
+        let names = if names.is_empty() {
+            mem::take(subscribed_to).into_iter().collect()
+        } else {
+            names
+        };
+        for name in &names {
+            subscribed_to.remove(name);
+            if patterns {
+                subscription.db.channels.punsubscribe(subscription.id, name);
+            } else {
+                subscription.db.channels.unsubscribe(subscription.id, name);
+            }
+        }
+        let count = subscription.count();
+        if count == 0 {
+            self.subscription = None;
+        }
+        subscribed(kind, names, count)
+    }
+
+    /// Runs the queued commands in `db`, to which the connection has `access`.
+    fn exec(&mut self, db: &Arc<Db>, access: Access) -> Result<Message> {
+        let Some(queued) = self.queued.take() else {
+            return Ok(Message::Err("EXEC without MULTI".to_string()));
+        };
+        let watched = mem::take(&mut self.watched);
+        if mem::take(&mut self.aborted) {
+            return Ok(Message::Err(
+                "transaction discarded because of previous errors".to_string(),
+            ));
+        }
+        if access == Access::ReadOnly && !queued.iter().all(Command::is_read_only) {
+            return Ok(Message::Err(READ_ONLY.to_string()));
+        }
+        let _lock = db.exclusive();
+        if watched
+            .iter()
+            .any(|(watched_db, key, version)| watched_db.version(key) != *version)
+        {
+            return Ok(Message::Null);
+        }
+        let results = queued
+            .into_iter()
+            .map(|command| command.execute_in_batch(db.clone()))
+            .collect();
+        Ok(Message::Array(results))
+    }
+}
+
+/// Runs `attempt` until it produces a reply, each time one of the keys is written, for up to
+/// `timeout` seconds (or forever, if zero).
+///
+/// Joins the keys' queues before the first attempt, so no push in between is missed. Keys are
+/// evicted after the attempt which produces a reply, as [`Command::perform`] does.
+async fn block(
+    db: &Arc<Db>,
+    connection: &mut Connection,
+    keys: Vec<String>,
+    timeout: f64,
+    attempt: impl Fn(&Arc<Db>) -> Result<Option<Message>>,
+) -> Result<Option<Message>> {
+    let waiter = db.blocked.wait(keys);
+    // Timeouts too long to represent are as good as waiting forever.
+    let deadline = Duration::try_from_secs_f64(timeout)
+        .ok()
+        .filter(|timeout| !timeout.is_zero())
+        .and_then(|timeout| Instant::now().checked_add(timeout));
+    let expired = async {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+            None => future::pending().await,
+        }
+    };
+    tokio::pin!(expired);
+    loop {
+        if let Some(message) = attempt(db)? {
+            let _lock = db.shared();
+            db.evict();
+            return Ok(Some(message));
+        }
+        tokio::select! {
+            () = waiter.woken() => {}
+            () = &mut expired => return Ok(Some(Message::Null)),
+            closed = connection.closed() => {
+                closed?;
+                return Ok(None);
+            }
+        }
+    }
+}
+
+fn no_access(name: &str) -> String {
+    format!("NOPERM this connection has no access to namespace '{name}'")
+}
+
+/// The reply to a change in subscriptions, which includes how many remain.
+fn subscribed(kind: &str, names: Vec<String>, count: usize) -> Message {
+    Message::Push(vec![
+        Message::Text(kind.to_string()),
+        Message::Array(names.into_iter().map(Message::Text).collect()),
+        Message::Int(count as i64),
+    ])
+}
@@ -1,12 +1,28 @@
+pub mod json;
+pub mod list;
+pub mod queue;
+pub mod timeseries;
+pub mod vector;
+
 pub enum Value<'a> {
     Int(i32),
     String(&'a str),
+    Json(&'a str),
+    TimeSeries(&'a [u8]),
+    Vector(&'a [u8]),
+    List(&'a [u8]),
+    Queue(&'a [u8]),
 }
 
 #[repr(u8)]
 enum Variant {
     Int = 0,
     String = 1,
+    Json = 2,
+    TimeSeries = 3,
+    Vector = 4,
+    List = 5,
+    Queue = 6,
 }
 
 #[derive(Debug)]
@@ -22,6 +38,11 @@ impl TryFrom<u8> for Variant {
         match value {
             0 => Ok(Self::Int),
             1 => Ok(Self::String),
+            2 => Ok(Self::Json),
+            3 => Ok(Self::TimeSeries),
+            4 => Ok(Self::Vector),
+            5 => Ok(Self::List),
+            6 => Ok(Self::Queue),
             _ => Err(Error::UnknownValueType(value)),
         }
     }
@@ -39,6 +60,24 @@ impl<'a> Value<'a> {
         match variant {
             Variant::Int => read_int(buf).map(Value::Int),
             Variant::String => read_string(buf).map(Value::String),
+            Variant::Json => read_string(buf).map(Value::Json),
+            Variant::TimeSeries => Ok(Value::TimeSeries(&buf[1..])),
+            Variant::Vector => Ok(Value::Vector(&buf[1..])),
+            Variant::List => Ok(Value::List(&buf[1..])),
+            Variant::Queue => Ok(Value::Queue(&buf[1..])),
+        }
+    }
+
+    /// The name of the value's type, as reported by `TYPE`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "int",
+            Value::String(_) => "string",
+            Value::Json(_) => "json",
+            Value::TimeSeries(_) => "timeseries",
+            Value::Vector(_) => "vector",
+            Value::List(_) => "list",
+            Value::Queue(_) => "queue",
         }
     }
 
@@ -49,25 +88,23 @@ impl<'a> Value<'a> {
                 buf[0] = Variant::Int as u8;
                 buf[1..5].copy_from_slice(&i32::to_be_bytes(*int));
             }
-            Value::String(string) => {
-                let string_bytes = string.as_bytes();
-                buf.resize(1 + string_bytes.len(), 0u8);
-                buf[0] = Variant::String as u8;
-                unsafe {
-                    core::ptr::copy_nonoverlapping(
-                        string_bytes.as_ptr(),
-                        buf[1..=string_bytes.len()].as_mut_ptr(),
-                        string_bytes.len(),
-                    );
-                }
-            }
+            Value::String(string) => write_bytes(buf, Variant::String, string.as_bytes()),
+            Value::Json(json) => write_bytes(buf, Variant::Json, json.as_bytes()),
+            Value::TimeSeries(bytes) => write_bytes(buf, Variant::TimeSeries, bytes),
+            Value::Vector(bytes) => write_bytes(buf, Variant::Vector, bytes),
+            Value::List(bytes) => write_bytes(buf, Variant::List, bytes),
+            Value::Queue(bytes) => write_bytes(buf, Variant::Queue, bytes),
         }
     }
 
     pub fn into_vec(&self) -> Vec<u8> {
         let len = match self {
             Value::Int(_) => 5,
-            Value::String(string) => 1 + string.len(),
+            Value::String(string) | Value::Json(string) => 1 + string.len(),
+            Value::TimeSeries(bytes)
+            | Value::Vector(bytes)
+            | Value::List(bytes)
+            | Value::Queue(bytes) => 1 + bytes.len(),
         };
         let mut buf = vec![0u8; len];
         self.write(&mut buf);
@@ -75,6 +112,18 @@ impl<'a> Value<'a> {
     }
 }
 
+fn write_bytes(buf: &mut Vec<u8>, variant: Variant, bytes: &[u8]) {
+    buf.resize(1 + bytes.len(), 0u8);
+    buf[0] = variant as u8;
+    unsafe {
+        core::ptr::copy_nonoverlapping(
+            bytes.as_ptr(),
+            buf[1..=bytes.len()].as_mut_ptr(),
+            bytes.len(),
+        );
+    }
+}
+
 fn read_int(buf: &[u8]) -> crate::Result<i32> {
     if buf.len() < 5 {
         return Err(crate::Error::ParseValue(Error::Invalid));
@@ -82,7 +131,7 @@ fn read_int(buf: &[u8]) -> crate::Result<i32> {
     Ok(i32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]))
 }
 
-fn read_string<'a>(buf: &'a [u8]) -> crate::Result<&'a str> {
+fn read_string(buf: &[u8]) -> crate::Result<&str> {
     match str::from_utf8(&buf[1..]) {
         Ok(s) => Ok(s),
         Err(_) => Err(crate::Error::InvalidUtf8),
@@ -0,0 +1,346 @@
+// A subset of JSONPath, used to address fragments of JSON documents.
+//
+// $            the root of the document
+// .name        an object member
+// ['name']     an object member (may contain any character)
+// [index]      an array element, negative indices count from the end
+// .* / [*]     every member of an object or element of an array
+// ..selector   any of the above, applied at every depth below the current node
+
+use serde_json::Value as Json;
+
+use crate::value::{self, Value};
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidPath,
+    InvalidJson,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Step {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    selector: Selector,
+    recursive: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    pub fn parse(path: &str) -> Result<Path, Error> {
+        let mut chars = path.chars().peekable();
+        if chars.next() != Some('$') {
+            return Err(Error::InvalidPath);
+        }
+        let mut segments = Vec::new();
+        while let Some(c) = chars.next() {
+            let recursive = match c {
+                '.' if chars.peek() == Some(&'.') => {
+                    chars.next();
+                    true
+                }
+                '.' => false,
+                '[' => {
+                    let selector = parse_bracket(&mut chars)?;
+                    segments.push(Segment {
+                        selector,
+                        recursive: false,
+                    });
+                    continue;
+                }
+                _ => return Err(Error::InvalidPath),
+            };
+            let selector = match chars.peek() {
+                Some('[') if recursive => {
+                    chars.next();
+                    parse_bracket(&mut chars)?
+                }
+                Some('*') => {
+                    chars.next();
+                    Selector::Wildcard
+                }
+                _ => {
+                    let mut name = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c == '.' || c == '[' {
+                            break;
+                        }
+                        name.push(c);
+                        chars.next();
+                    }
+                    if name.is_empty() {
+                        return Err(Error::InvalidPath);
+                    }
+                    Selector::Key(name)
+                }
+            };
+            segments.push(Segment {
+                selector,
+                recursive,
+            });
+        }
+        Ok(Path { segments })
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Returns the location of every node in `doc` matched by this path, in document order.
+    ///
+    /// A node reached through several recursive descents is only returned once.
+    pub fn locate(&self, doc: &Json) -> Vec<Vec<Step>> {
+        let mut frontier = vec![Vec::new()];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for location in frontier {
+                let Some(node) = resolve(doc, &location) else {
+                    continue;
+                };
+                if segment.recursive {
+                    let mut stack = vec![(location, node)];
+                    while let Some((location, node)) = stack.pop() {
+                        select(&segment.selector, node, &location, &mut next);
+                        for (step, child) in children(node).into_iter().rev() {
+                            let mut child_location = location.clone();
+                            child_location.push(step);
+                            stack.push((child_location, child));
+                        }
+                    }
+                } else {
+                    select(&segment.selector, node, &location, &mut next);
+                }
+            }
+            frontier = next;
+        }
+        frontier.sort_unstable();
+        frontier.dedup();
+        frontier
+    }
+
+    /// Returns every node in `doc` matched by this path.
+    pub fn query<'a>(&self, doc: &'a Json) -> Vec<&'a Json> {
+        self.locate(doc)
+            .iter()
+            .filter_map(|location| resolve(doc, location))
+            .collect()
+    }
+
+    /// Replaces every node matched by this path with `value`. If nothing matches and the path
+    /// ends with an object member, the member is created in each matching parent object.
+    /// Returns whether the document changed.
+    pub fn set(&self, doc: &mut Json, value: Json) -> bool {
+        if self.is_root() {
+            *doc = value;
+            return true;
+        }
+        let locations = self.locate(doc);
+        if !locations.is_empty() {
+            for location in &locations {
+                if let Some(node) = resolve_mut(doc, location) {
+                    *node = value.clone();
+                }
+            }
+            return true;
+        }
+        let Some((last, parent)) = self.segments.split_last() else {
+            return false;
+        };
+        let Segment {
+            selector: Selector::Key(key),
+            recursive: false,
+        } = last
+        else {
+            return false;
+        };
+        let parent = Path {
+            segments: parent.to_vec(),
+        };
+        let mut changed = false;
+        for location in parent.locate(doc) {
+            if let Some(Json::Object(object)) = resolve_mut(doc, &location) {
+                object.insert(key.clone(), value.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Removes every node matched by this path, returning how many were removed. The root
+    /// cannot be removed from within a document.
+    pub fn delete(&self, doc: &mut Json) -> usize {
+        let mut locations = self.locate(doc);
+        // Remove later array elements (and children before their parents) first, so the
+        // remaining locations stay valid.
+        locations.sort_unstable_by(|a, b| b.cmp(a));
+        let mut removed = 0;
+        for mut location in locations {
+            let Some(step) = location.pop() else {
+                continue;
+            };
+            let found = match (resolve_mut(doc, &location), step) {
+                (Some(Json::Object(object)), Step::Key(key)) => object.remove(&key).is_some(),
+                (Some(Json::Array(array)), Step::Index(index)) if index < array.len() => {
+                    array.remove(index);
+                    true
+                }
+                _ => false,
+            };
+            if found {
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+fn parse_bracket(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Selector, Error> {
+    let mut inner = String::new();
+    let mut quote = None;
+    loop {
+        match (chars.next(), quote) {
+            (None, _) => return Err(Error::InvalidPath),
+            (Some(']'), None) => break,
+            (Some(c @ ('\'' | '"')), None) if inner.is_empty() => quote = Some(c),
+            (Some(c), Some(q)) if c == q => {
+                if chars.next() != Some(']') {
+                    return Err(Error::InvalidPath);
+                }
+                return Ok(Selector::Key(inner));
+            }
+            (Some(c), _) => inner.push(c),
+        }
+    }
+    let inner = inner.trim();
+    if inner == "*" {
+        return Ok(Selector::Wildcard);
+    }
+    match inner.parse::<i64>() {
+        Ok(index) => Ok(Selector::Index(index)),
+        Err(_) => Err(Error::InvalidPath),
+    }
+}
+
+fn select(selector: &Selector, node: &Json, location: &[Step], out: &mut Vec<Vec<Step>>) {
+    let mut push = |step| {
+        let mut location = location.to_vec();
+        location.push(step);
+        out.push(location);
+    };
+    match (selector, node) {
+        (Selector::Key(key), Json::Object(object)) if object.contains_key(key) => {
+            push(Step::Key(key.clone()))
+        }
+        (Selector::Index(index), Json::Array(array)) => {
+            let len = array.len() as i64;
+            let index = if *index < 0 { len + index } else { *index };
+            if (0..len).contains(&index) {
+                push(Step::Index(index as usize))
+            }
+        }
+        (Selector::Wildcard, _) => {
+            for (step, _) in children(node) {
+                push(step)
+            }
+        }
+        _ => {}
+    }
+}
+
+fn children(node: &Json) -> Vec<(Step, &Json)> {
+    match node {
+        Json::Object(object) => object
+            .iter()
+            .map(|(key, child)| (Step::Key(key.clone()), child))
+            .collect(),
+        Json::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(i, child)| (Step::Index(i), child))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn resolve<'a>(mut node: &'a Json, location: &[Step]) -> Option<&'a Json> {
+    for step in location {
+        node = match (node, step) {
+            (Json::Object(object), Step::Key(key)) => object.get(key)?,
+            (Json::Array(array), Step::Index(index)) => array.get(*index)?,
+            _ => return None,
+        };
+    }
+    Some(node)
+}
+
+pub fn resolve_mut<'a>(mut node: &'a mut Json, location: &[Step]) -> Option<&'a mut Json> {
+    for step in location {
+        node = match (node, step) {
+            (Json::Object(object), Step::Key(key)) => object.get_mut(key)?,
+            (Json::Array(array), Step::Index(index)) => array.get_mut(*index)?,
+            _ => return None,
+        };
+    }
+    Some(node)
+}
+
+/// The name reported by `JSON.TYPE` for a node.
+pub fn type_name(node: &Json) -> &'static str {
+    match node {
+        Json::Null => "null",
+        Json::Bool(_) => "boolean",
+        Json::Number(number) if number.is_f64() => "number",
+        Json::Number(_) => "integer",
+        Json::String(_) => "string",
+        Json::Array(_) => "array",
+        Json::Object(_) => "object",
+    }
+}
+
+pub fn parse(text: &str) -> Result<Json, Error> {
+    serde_json::from_str(text).map_err(|_| Error::InvalidJson)
+}
+
+/// Parses a stored value as a JSON document, returning `None` if it holds another type.
+pub fn decode(buf: &[u8]) -> crate::Result<Option<Json>> {
+    match Value::parse(buf)? {
+        Value::Json(text) => match parse(text) {
+            Ok(doc) => Ok(Some(doc)),
+            Err(_) => Err(crate::Error::ParseValue(value::Error::Invalid)),
+        },
+        _ => Ok(None),
+    }
+}
+
+pub fn encode(doc: &Json) -> Vec<u8> {
+    Value::Json(&doc.to_string()).into_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recursive_descent_matches_each_node_once() {
+        let path = Path::parse("$..*..[0]").unwrap();
+        let mut doc = parse(r#"{"a":{"b":[1,2]}}"#).unwrap();
+        assert_eq!(path.query(&doc), vec![&Json::from(1)]);
+        assert_eq!(path.delete(&mut doc), 1);
+        assert_eq!(doc, parse(r#"{"a":{"b":[2]}}"#).unwrap());
+    }
+}
@@ -0,0 +1,36 @@
+// Lists are stored as their elements, in order, each prefixed by its length.
+//
+// LIST = [LENGTH(32) BYTES]...
+
+use std::collections::VecDeque;
+
+use crate::value::{self, Value};
+
+/// Parses a stored value as a list, returning `None` if it holds another type.
+pub fn decode(buf: &[u8]) -> crate::Result<Option<VecDeque<String>>> {
+    let Value::List(mut bytes) = Value::parse(buf)? else {
+        return Ok(None);
+    };
+    let invalid = || crate::Error::ParseValue(value::Error::Invalid);
+    let mut list = VecDeque::new();
+    while !bytes.is_empty() {
+        let (len, rest) = bytes.split_first_chunk::<4>().ok_or_else(invalid)?;
+        let len = u32::from_be_bytes(*len) as usize;
+        if rest.len() < len {
+            return Err(invalid());
+        }
+        let (element, rest) = rest.split_at(len);
+        list.push_back(String::from_utf8(element.to_vec()).map_err(|_| crate::Error::InvalidUtf8)?);
+        bytes = rest;
+    }
+    Ok(Some(list))
+}
+
+pub fn encode(list: &VecDeque<String>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(list.iter().map(|element| 4 + element.len()).sum());
+    for element in list {
+        bytes.extend_from_slice(&(element.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(element.as_bytes());
+    }
+    Value::List(&bytes).into_vec()
+}
@@ -0,0 +1,239 @@
+// Queues are stored as their settings followed by their messages, in the order they were
+// enqueued.
+//
+// QUEUE = NEXT_ID(64) VISIBILITY(64) MAX_RECEIVES(32) LENGTH(16) DEAD_LETTER [MESSAGE]...
+// MESSAGE = ID(64) VISIBLE_AT(64) RECEIVES(32) LENGTH(32) BODY
+//
+// A message is in flight while it's invisible after being received, and delayed while it's
+// invisible before its first receive.
+
+use std::collections::VecDeque;
+
+use crate::value::{self, Value};
+
+/// How long a received message stays invisible by default, in milliseconds.
+pub const DEFAULT_VISIBILITY: u64 = 30_000;
+
+#[derive(Debug, Default)]
+pub struct Queue {
+    next_id: u64,
+    /// How long a received message stays invisible, unless the receive says otherwise.
+    pub visibility: u64,
+    /// How many times a message can be received before it's moved to the dead-letter queue, or
+    /// zero for no limit.
+    pub max_receives: u32,
+    pub dead_letter: Option<String>,
+    messages: VecDeque<Message>,
+}
+
+#[derive(Debug)]
+struct Message {
+    id: u64,
+    /// When the message can next be received, in milliseconds since the unix epoch.
+    visible_at: u64,
+    receives: u32,
+    body: String,
+}
+
+/// A message handed out by [`Queue::receive`].
+pub struct Received<'a> {
+    pub id: u64,
+    /// Identifies this receive of the message, to acknowledge it with.
+    pub receipt: String,
+    pub receives: u32,
+    pub body: &'a str,
+}
+
+pub struct Stats {
+    /// Messages which can be received now.
+    pub ready: usize,
+    pub in_flight: usize,
+    pub delayed: usize,
+}
+
+impl Queue {
+    pub fn new(visibility: u64, max_receives: u32, dead_letter: Option<String>) -> Queue {
+        Queue {
+            visibility,
+            max_receives,
+            dead_letter,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a message, which can't be received until `visible_at`, returning its id.
+    pub fn enqueue(&mut self, body: String, visible_at: u64) -> u64 {
+        self.next_id += 1;
+        self.messages.push_back(Message {
+            id: self.next_id,
+            visible_at,
+            receives: 0,
+            body,
+        });
+        self.next_id
+    }
+
+    /// Removes the visible messages which have already been received as many times as allowed,
+    /// returning their bodies.
+    pub fn take_exhausted(&mut self, now: u64) -> Vec<String> {
+        if self.max_receives == 0 {
+            return Vec::new();
+        }
+        let (exhausted, kept) = std::mem::take(&mut self.messages)
+            .into_iter()
+            .partition(|m| m.visible_at <= now && m.receives >= self.max_receives);
+        self.messages = kept;
+        exhausted.into_iter().map(|m: Message| m.body).collect()
+    }
+
+    /// Receives the oldest visible message, making it invisible for `visibility` milliseconds.
+    pub fn receive(&mut self, now: u64, visibility: u64) -> Option<Received<'_>> {
+        let message = self.messages.iter_mut().find(|m| m.visible_at <= now)?;
+        message.receives += 1;
+        message.visible_at = now.saturating_add(visibility);
+        Some(Received {
+            id: message.id,
+            receipt: receipt(message.id, message.receives),
+            receives: message.receives,
+            body: &message.body,
+        })
+    }
+
+    /// Deletes the message with the receipt, returning whether it was found. Receipts from
+    /// before a message's latest receive are no longer valid.
+    pub fn ack(&mut self, receipt: &str) -> bool {
+        match self.position(receipt) {
+            Some(i) => self.messages.remove(i).is_some(),
+            None => false,
+        }
+    }
+
+    /// Makes the message with the receipt visible again at `visible_at`, returning whether it
+    /// was found.
+    pub fn nack(&mut self, receipt: &str, visible_at: u64) -> bool {
+        match self.position(receipt) {
+            Some(i) => {
+                self.messages[i].visible_at = visible_at;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn stats(&self, now: u64) -> Stats {
+        let mut stats = Stats {
+            ready: 0,
+            in_flight: 0,
+            delayed: 0,
+        };
+        for message in &self.messages {
+            match (message.visible_at <= now, message.receives) {
+                (true, _) => stats.ready += 1,
+                (false, 0) => stats.delayed += 1,
+                (false, _) => stats.in_flight += 1,
+            }
+        }
+        stats
+    }
+
+    fn position(&self, receipt: &str) -> Option<usize> {
+        let (id, receives) = receipt.split_once(':')?;
+        let (id, receives): (u64, u32) = (id.parse().ok()?, receives.parse().ok()?);
+        self.messages
+            .iter()
+            .position(|m| m.id == id && m.receives == receives)
+    }
+
+    /// Parses a stored value as a queue, returning `None` if it holds another type.
+    pub fn decode(buf: &[u8]) -> crate::Result<Option<Queue>> {
+        let Value::Queue(buf) = Value::parse(buf)? else {
+            return Ok(None);
+        };
+        match decode_queue(&mut Reader { buf }) {
+            Some(queue) => Ok(Some(queue)),
+            None => Err(crate::Error::ParseValue(value::Error::Invalid)),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&self.next_id.to_be_bytes());
+        buf.extend_from_slice(&self.visibility.to_be_bytes());
+        buf.extend_from_slice(&self.max_receives.to_be_bytes());
+        let dead_letter = self.dead_letter.as_deref().unwrap_or_default();
+        buf.extend_from_slice(&(dead_letter.len() as u16).to_be_bytes());
+        buf.extend_from_slice(dead_letter.as_bytes());
+        for message in &self.messages {
+            buf.extend_from_slice(&message.id.to_be_bytes());
+            buf.extend_from_slice(&message.visible_at.to_be_bytes());
+            buf.extend_from_slice(&message.receives.to_be_bytes());
+            buf.extend_from_slice(&(message.body.len() as u32).to_be_bytes());
+            buf.extend_from_slice(message.body.as_bytes());
+        }
+        Value::Queue(&buf).into_vec()
+    }
+}
+
+fn receipt(id: u64, receives: u32) -> String {
+    format!("{id}:{receives}")
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.buf.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Some(bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.bytes(2)?.try_into().ok()?))
+    }
+
+    fn string(&mut self, len: usize) -> Option<String> {
+        String::from_utf8(self.bytes(len)?.to_vec()).ok()
+    }
+}
+
+fn decode_queue(reader: &mut Reader) -> Option<Queue> {
+    let next_id = reader.u64()?;
+    let visibility = reader.u64()?;
+    let max_receives = reader.u32()?;
+    let len = reader.u16()? as usize;
+    let dead_letter = Some(reader.string(len)?).filter(|key| !key.is_empty());
+    let mut messages = VecDeque::new();
+    while !reader.buf.is_empty() {
+        let id = reader.u64()?;
+        let visible_at = reader.u64()?;
+        let receives = reader.u32()?;
+        let len = reader.u32()? as usize;
+        let body = reader.string(len)?;
+        messages.push_back(Message {
+            id,
+            visible_at,
+            receives,
+            body,
+        });
+    }
+    Some(Queue {
+        next_id,
+        visibility,
+        max_receives,
+        dead_letter,
+        messages,
+    })
+}
@@ -0,0 +1,551 @@
+// Time series are stored as a header followed by a list of compressed chunks.
+//
+// HEADER = RETENTION(64) RULE_COUNT(8) [RULE]... CHUNK_COUNT(32) [CHUNK]...
+// RULE = LENGTH(16) DEST AGGREGATION(8) BUCKET(64) STARTED(8) BUCKET_START(64)
+//        COUNT(64) SUM(64) MIN(64) MAX(64)
+// CHUNK = COUNT(16) FIRST(64) LAST(64) LAST_DELTA(64) LAST_VALUE(64) LEADING(8)
+//         TRAILING(8) BIT_LENGTH(32) [BYTE]...
+//
+// Within a chunk, timestamps are encoded as delta-of-deltas and values as the XOR with the
+// previous value, following Facebook's Gorilla paper. The trailing encoder state is kept in the
+// chunk header so samples can be appended without decoding the chunk.
+
+use crate::value::{self, Value};
+
+/// The maximum number of samples stored in a single chunk.
+const CHUNK_CAPACITY: u16 = 256;
+
+/// The most compaction rules a series can have, as their count is stored in a byte.
+pub const MAX_RULES: usize = u8::MAX as usize;
+
+/// Marks that no XOR window has been established yet in a chunk.
+const NO_WINDOW: u8 = u8::MAX;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Samples must be added in strictly increasing timestamp order.
+    OutOfOrder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Avg,
+    Min,
+    Max,
+    Sum,
+    Count,
+}
+
+impl Aggregation {
+    pub fn parse(name: &str) -> Option<Aggregation> {
+        match name.to_ascii_lowercase().as_str() {
+            "avg" => Some(Aggregation::Avg),
+            "min" => Some(Aggregation::Min),
+            "max" => Some(Aggregation::Max),
+            "sum" => Some(Aggregation::Sum),
+            "count" => Some(Aggregation::Count),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregation::Avg => "avg",
+            Aggregation::Min => "min",
+            Aggregation::Max => "max",
+            Aggregation::Sum => "sum",
+            Aggregation::Count => "count",
+        }
+    }
+
+    fn from_u8(byte: u8) -> Option<Aggregation> {
+        match byte {
+            0 => Some(Aggregation::Avg),
+            1 => Some(Aggregation::Min),
+            2 => Some(Aggregation::Max),
+            3 => Some(Aggregation::Sum),
+            4 => Some(Aggregation::Count),
+            _ => None,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Aggregation::Avg => 0,
+            Aggregation::Min => 1,
+            Aggregation::Max => 2,
+            Aggregation::Sum => 3,
+            Aggregation::Count => 4,
+        }
+    }
+}
+
+/// Accumulates samples falling into a single bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct Accumulator {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Accumulator {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Accumulator {
+    pub fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    pub fn finish(&self, aggregation: Aggregation) -> f64 {
+        match aggregation {
+            Aggregation::Avg => self.sum / self.count as f64,
+            Aggregation::Min => self.min,
+            Aggregation::Max => self.max,
+            Aggregation::Sum => self.sum,
+            Aggregation::Count => self.count as f64,
+        }
+    }
+}
+
+/// Downsamples every sample added to a series into `dest`.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub dest: String,
+    pub aggregation: Aggregation,
+    pub bucket: u64,
+    /// The start of the bucket currently being accumulated, if any samples have arrived.
+    bucket_start: Option<u64>,
+    acc: Accumulator,
+}
+
+impl Rule {
+    pub fn new(dest: String, aggregation: Aggregation, bucket: u64) -> Rule {
+        Rule {
+            dest,
+            aggregation,
+            bucket,
+            bucket_start: None,
+            acc: Accumulator::default(),
+        }
+    }
+
+    /// Adds a sample, returning the aggregate of the previous bucket if this sample closed it.
+    fn add(&mut self, timestamp: u64, value: f64) -> Option<(u64, f64)> {
+        let start = timestamp - timestamp % self.bucket;
+        let mut closed = None;
+        match self.bucket_start {
+            Some(current) if current == start => {}
+            Some(current) => {
+                closed = Some((current, self.acc.finish(self.aggregation)));
+                self.acc = Accumulator::default();
+                self.bucket_start = Some(start);
+            }
+            None => self.bucket_start = Some(start),
+        }
+        self.acc.add(value);
+        closed
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TimeSeries {
+    /// How long samples are kept relative to the newest sample, in milliseconds. Zero keeps
+    /// samples forever.
+    pub retention: u64,
+    pub rules: Vec<Rule>,
+    chunks: Vec<Chunk>,
+}
+
+impl TimeSeries {
+    pub fn new(retention: u64) -> TimeSeries {
+        TimeSeries {
+            retention,
+            ..Default::default()
+        }
+    }
+
+    pub fn last_timestamp(&self) -> Option<u64> {
+        self.chunks.last().map(|chunk| chunk.last)
+    }
+
+    /// Appends a sample, returning the samples produced for each compaction rule's destination.
+    pub fn add(&mut self, timestamp: u64, value: f64) -> Result<Vec<(String, u64, f64)>, Error> {
+        if self.last_timestamp().is_some_and(|last| timestamp <= last) {
+            return Err(Error::OutOfOrder);
+        }
+        match self.chunks.last_mut() {
+            Some(chunk) if chunk.count < CHUNK_CAPACITY => chunk.push(timestamp, value),
+            _ => self.chunks.push(Chunk::new(timestamp, value)),
+        }
+        if self.retention > 0 {
+            let oldest = timestamp.saturating_sub(self.retention);
+            self.chunks.retain(|chunk| chunk.last >= oldest);
+        }
+        Ok(self
+            .rules
+            .iter_mut()
+            .filter_map(|rule| {
+                let (timestamp, value) = rule.add(timestamp, value)?;
+                Some((rule.dest.clone(), timestamp, value))
+            })
+            .collect())
+    }
+
+    /// Returns the samples between `from` and `to` inclusive.
+    pub fn range(&self, from: u64, to: u64) -> Vec<(u64, f64)> {
+        let from = match (self.retention, self.last_timestamp()) {
+            (0, _) | (_, None) => from,
+            (retention, Some(last)) => from.max(last.saturating_sub(retention)),
+        };
+        self.chunks
+            .iter()
+            .filter(|chunk| chunk.last >= from && chunk.first <= to)
+            .flat_map(Chunk::samples)
+            .filter(|(timestamp, _)| (from..=to).contains(timestamp))
+            .collect()
+    }
+
+    /// Parses a stored value as a time series, returning `None` if it holds another type.
+    pub fn decode(buf: &[u8]) -> crate::Result<Option<TimeSeries>> {
+        let Value::TimeSeries(buf) = Value::parse(buf)? else {
+            return Ok(None);
+        };
+        let mut reader = ByteReader { buf, pos: 0 };
+        match decode_series(&mut reader) {
+            Some(series) => Ok(Some(series)),
+            None => Err(crate::Error::ParseValue(value::Error::Invalid)),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&self.retention.to_be_bytes());
+        buf.push(self.rules.len() as u8);
+        for rule in &self.rules {
+            buf.extend_from_slice(&(rule.dest.len() as u16).to_be_bytes());
+            buf.extend_from_slice(rule.dest.as_bytes());
+            buf.push(rule.aggregation.to_u8());
+            buf.extend_from_slice(&rule.bucket.to_be_bytes());
+            buf.push(rule.bucket_start.is_some() as u8);
+            buf.extend_from_slice(&rule.bucket_start.unwrap_or(0).to_be_bytes());
+            buf.extend_from_slice(&rule.acc.count.to_be_bytes());
+            buf.extend_from_slice(&rule.acc.sum.to_be_bytes());
+            buf.extend_from_slice(&rule.acc.min.to_be_bytes());
+            buf.extend_from_slice(&rule.acc.max.to_be_bytes());
+        }
+        buf.extend_from_slice(&(self.chunks.len() as u32).to_be_bytes());
+        for chunk in &self.chunks {
+            buf.extend_from_slice(&chunk.count.to_be_bytes());
+            buf.extend_from_slice(&chunk.first.to_be_bytes());
+            buf.extend_from_slice(&chunk.last.to_be_bytes());
+            buf.extend_from_slice(&chunk.last_delta.to_be_bytes());
+            buf.extend_from_slice(&chunk.last_value.to_be_bytes());
+            buf.push(chunk.leading);
+            buf.push(chunk.trailing);
+            buf.extend_from_slice(&chunk.bits.len.to_be_bytes());
+            buf.extend_from_slice(&chunk.bits.bytes);
+        }
+        Value::TimeSeries(&buf).into_vec()
+    }
+}
+
+/// Whether a stored value is a time series with compaction rules, read from its header.
+pub fn has_rules(buf: &[u8]) -> bool {
+    match Value::parse(buf) {
+        Ok(Value::TimeSeries(series)) => series.get(8).is_some_and(|&rules| rules > 0),
+        _ => false,
+    }
+}
+
+/// Aggregates `samples` into buckets of `bucket` milliseconds, aligned to the epoch.
+pub fn aggregate(samples: &[(u64, f64)], aggregation: Aggregation, bucket: u64) -> Vec<(u64, f64)> {
+    let mut buckets = Vec::new();
+    let mut current: Option<(u64, Accumulator)> = None;
+    for &(timestamp, value) in samples {
+        let start = timestamp - timestamp % bucket;
+        match &mut current {
+            Some((current_start, acc)) if *current_start == start => acc.add(value),
+            _ => {
+                if let Some((start, acc)) = current.take() {
+                    buckets.push((start, acc.finish(aggregation)));
+                }
+                let mut acc = Accumulator::default();
+                acc.add(value);
+                current = Some((start, acc));
+            }
+        }
+    }
+    if let Some((start, acc)) = current {
+        buckets.push((start, acc.finish(aggregation)));
+    }
+    buckets
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    count: u16,
+    first: u64,
+    last: u64,
+    last_delta: i64,
+    last_value: u64,
+    leading: u8,
+    trailing: u8,
+    bits: BitWriter,
+}
+
+impl Chunk {
+    fn new(timestamp: u64, value: f64) -> Chunk {
+        let mut bits = BitWriter::default();
+        bits.write(value.to_bits(), 64);
+        Chunk {
+            count: 1,
+            first: timestamp,
+            last: timestamp,
+            last_delta: 0,
+            last_value: value.to_bits(),
+            leading: NO_WINDOW,
+            trailing: 0,
+            bits,
+        }
+    }
+
+    fn push(&mut self, timestamp: u64, value: f64) {
+        // Deltas wrap for samples more than i64::MAX apart, and unwrap the same way when read.
+        let delta = (timestamp - self.last) as i64;
+        let dod = delta.wrapping_sub(self.last_delta);
+        match dod {
+            0 => self.bits.write(0b0, 1),
+            -64..=63 => {
+                self.bits.write(0b10, 2);
+                self.bits.write(dod as u64, 7);
+            }
+            -256..=255 => {
+                self.bits.write(0b110, 3);
+                self.bits.write(dod as u64, 9);
+            }
+            -2048..=2047 => {
+                self.bits.write(0b1110, 4);
+                self.bits.write(dod as u64, 12);
+            }
+            _ => {
+                self.bits.write(0b1111, 4);
+                self.bits.write(dod as u64, 64);
+            }
+        }
+
+        let value = value.to_bits();
+        let xor = value ^ self.last_value;
+        if xor == 0 {
+            self.bits.write(0b0, 1);
+        } else {
+            let leading = (xor.leading_zeros() as u8).min(31);
+            let trailing = xor.trailing_zeros() as u8;
+            if self.leading != NO_WINDOW && leading >= self.leading && trailing >= self.trailing {
+                let len = 64 - self.leading - self.trailing;
+                self.bits.write(0b10, 2);
+                self.bits.write(xor >> self.trailing, len);
+            } else {
+                let len = 64 - leading - trailing;
+                self.bits.write(0b11, 2);
+                self.bits.write(leading as u64, 5);
+                self.bits.write((len - 1) as u64, 6);
+                self.bits.write(xor >> trailing, len);
+                self.leading = leading;
+                self.trailing = trailing;
+            }
+        }
+
+        self.count += 1;
+        self.last = timestamp;
+        self.last_delta = delta;
+        self.last_value = value;
+    }
+
+    fn samples(&self) -> Vec<(u64, f64)> {
+        let mut reader = BitReader {
+            bytes: &self.bits.bytes,
+            pos: 0,
+        };
+        let mut samples = Vec::with_capacity(self.count as usize);
+        let mut timestamp = self.first;
+        let mut delta = 0i64;
+        let mut value = reader.read(64);
+        let (mut leading, mut trailing) = (0u8, 0u8);
+        samples.push((timestamp, f64::from_bits(value)));
+        for _ in 1..self.count {
+            let dod = if reader.read(1) == 0 {
+                0
+            } else if reader.read(1) == 0 {
+                sign_extend(reader.read(7), 7)
+            } else if reader.read(1) == 0 {
+                sign_extend(reader.read(9), 9)
+            } else if reader.read(1) == 0 {
+                sign_extend(reader.read(12), 12)
+            } else {
+                reader.read(64) as i64
+            };
+            delta = delta.wrapping_add(dod);
+            timestamp = timestamp.wrapping_add(delta as u64);
+
+            if reader.read(1) == 1 {
+                if reader.read(1) == 1 {
+                    leading = reader.read(5) as u8;
+                    let len = reader.read(6) as u8 + 1;
+                    trailing = 64 - leading - len;
+                }
+                let len = 64 - leading - trailing;
+                value ^= reader.read(len) << trailing;
+            }
+            samples.push((timestamp, f64::from_bits(value)));
+        }
+        samples
+    }
+}
+
+fn sign_extend(value: u64, bits: u8) -> i64 {
+    let shift = 64 - bits;
+    ((value << shift) as i64) >> shift
+}
+
+#[derive(Debug, Clone, Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: u32,
+}
+
+impl BitWriter {
+    /// Writes the low `count` bits of `value`, most significant first.
+    fn write(&mut self, value: u64, count: u8) {
+        for i in (0..count).rev() {
+            let bit = (value >> i) & 1;
+            let offset = (self.len % 8) as u8;
+            if offset == 0 {
+                self.bytes.push(0);
+            }
+            if bit == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> offset;
+            }
+            self.len += 1;
+        }
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: u8) -> u64 {
+        let mut value = 0u64;
+        for _ in 0..count {
+            let byte = self.bytes.get(self.pos / 8).copied().unwrap_or(0);
+            let bit = (byte >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as u64;
+            self.pos += 1;
+        }
+        value
+    }
+}
+
+struct ByteReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.buf.get(self.pos..self.pos + len)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        self.u64().map(f64::from_bits)
+    }
+}
+
+fn decode_series(reader: &mut ByteReader) -> Option<TimeSeries> {
+    let retention = reader.u64()?;
+    let rule_count = reader.u8()?;
+    let mut rules = Vec::with_capacity(rule_count as usize);
+    for _ in 0..rule_count {
+        let len = reader.u16()? as usize;
+        let dest = str::from_utf8(reader.take(len)?).ok()?.to_string();
+        let aggregation = Aggregation::from_u8(reader.u8()?)?;
+        let bucket = reader.u64()?;
+        let started = reader.u8()? != 0;
+        let bucket_start = reader.u64()?;
+        let acc = Accumulator {
+            count: reader.u64()?,
+            sum: reader.f64()?,
+            min: reader.f64()?,
+            max: reader.f64()?,
+        };
+        rules.push(Rule {
+            dest,
+            aggregation,
+            bucket,
+            bucket_start: started.then_some(bucket_start),
+            acc,
+        });
+    }
+    let chunk_count = reader.u32()?;
+    let mut chunks = Vec::with_capacity(chunk_count as usize);
+    for _ in 0..chunk_count {
+        let count = reader.u16()?;
+        let first = reader.u64()?;
+        let last = reader.u64()?;
+        let last_delta = reader.u64()? as i64;
+        let last_value = reader.u64()?;
+        let leading = reader.u8()?;
+        let trailing = reader.u8()?;
+        let len = reader.u32()?;
+        let bytes = reader.take(len.div_ceil(8) as usize)?.to_vec();
+        chunks.push(Chunk {
+            count,
+            first,
+            last,
+            last_delta,
+            last_value,
+            leading,
+            trailing,
+            bits: BitWriter { bytes, len },
+        });
+    }
+    Some(TimeSeries {
+        retention,
+        rules,
+        chunks,
+    })
+}
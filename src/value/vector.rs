@@ -0,0 +1,108 @@
+// Vectors are stored as their components, each a big-endian f32.
+
+use crate::value::{self, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Cosine,
+    L2,
+    Dot,
+}
+
+impl Metric {
+    pub fn parse(name: &str) -> Option<Metric> {
+        match name.to_ascii_lowercase().as_str() {
+            "cosine" => Some(Metric::Cosine),
+            "l2" => Some(Metric::L2),
+            "dot" => Some(Metric::Dot),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Cosine => "cosine",
+            Metric::L2 => "l2",
+            Metric::Dot => "dot",
+        }
+    }
+
+    /// The distance between two vectors of equal length, where smaller is more similar.
+    ///
+    /// This is the euclidean distance for `L2`, one minus the cosine similarity for `Cosine`, and
+    /// one minus the dot product for `Dot`.
+    pub fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            Metric::L2 => a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f32>()
+                .sqrt(),
+            Metric::Cosine => {
+                let norms = norm(a) * norm(b);
+                if norms == 0.0 {
+                    1.0
+                } else {
+                    1.0 - dot(a, b) / norms
+                }
+            }
+            Metric::Dot => 1.0 - dot(a, b),
+        }
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn norm(a: &[f32]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+/// Parses a comma separated list of finite components.
+pub fn parse(text: &str) -> Option<Vec<f32>> {
+    let vector = text
+        .split(',')
+        .map(|component| {
+            component
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|c| c.is_finite())
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if vector.is_empty() {
+        return None;
+    }
+    Some(vector)
+}
+
+pub fn to_string(vector: &[f32]) -> String {
+    vector
+        .iter()
+        .map(f32::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parses a stored value as a vector, returning `None` if it holds another type.
+pub fn decode(buf: &[u8]) -> crate::Result<Option<Vec<f32>>> {
+    let Value::Vector(bytes) = Value::parse(buf)? else {
+        return Ok(None);
+    };
+    if bytes.len() % 4 != 0 {
+        return Err(crate::Error::ParseValue(value::Error::Invalid));
+    }
+    Ok(Some(
+        bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    ))
+}
+
+pub fn encode(vector: &[f32]) -> Vec<u8> {
+    let bytes: Vec<u8> = vector.iter().flat_map(|c| c.to_be_bytes()).collect();
+    Value::Vector(&bytes).into_vec()
+}